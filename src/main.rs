@@ -1,20 +1,51 @@
 mod plugins;
 mod style;
+mod widgets;
 
 use chrono::prelude::*;
 use iced::{
-    button, pick_list, scrollable, Align, Application, Button, Clipboard, Column, 
-    Command, Container, Element, HorizontalAlignment, PickList, Row, 
-    Scrollable, Settings, Subscription, Text, text_input, 
-    TextInput, time, VerticalAlignment};
+    button, pick_list, scrollable, slider, Align, Application, Button, Canvas, Checkbox, Clipboard, Column, 
+    Command, Container, Element, HorizontalAlignment, PickList, ProgressBar, Row, 
+    Scrollable, Settings, Slider, Space, Subscription, Text, text_input, 
+    TextInput, time, VerticalAlignment, window};
 use iced::Length;
 use rss::Channel;
-use plugins::{news, state, weather};
+use plugins::{air, alerts, astronomy, audio, backlight, caldav, calendar, carousel, clock, config, cursor, dashboard, history, layout, mpd, mqtt, news, night, notes, podcast, remote, screensaver, sensors, state, status, system, theme, todo, transit, weather};
+use widgets::analog_clock::AnalogClock;
+use widgets::sparkline::Sparkline;
+use widgets::weather_chart::WeatherChart;
 
 
 pub fn main() -> iced::Result {
-    let mut settings_app = Settings::default();
-    settings_app.window.size = (1024,600);
+    let mut cli = config::parse_args(std::env::args_os()).unwrap_or_else(|e| e.exit());
+    let system_config = config::load_system_config(&cli).unwrap_or_else(|e| {
+        eprintln!("Impossible de lire la configuration système : {}", e);
+        std::process::exit(2);
+    });
+    let remote_command = cli.remote.take();
+    let launch = config::LaunchConfig::resolve(cli, system_config);
+
+    env_logger::Builder::new().parse_filters(&launch.log_level).init();
+    if let Some(data_dir) = &launch.data_dir {
+        std::env::set_var(state::DATA_DIR_VAR, data_dir);
+    }
+    if let Some(command) = remote_command {
+        match remote::send(&launch.control_socket(), &command) {
+            Ok(reply) => {
+                println!("{}", reply);
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Impossible de joindre l'interface : {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    log::info!("Démarrage sur la page {} en {}x{}", launch.page, launch.size.0, launch.size.1);
+
+    let mut settings_app = Settings::with_flags(launch.clone());
+    settings_app.window.size = launch.size;
+    settings_app.window.decorations = !launch.kiosk;
     Assistant::run(settings_app)
 }
 
@@ -25,274 +56,2386 @@ struct State {
     weatherapi_value: String,
     input_searchcity: text_input::State,
     searchcity_value: String,
+    weather_provider: weather::Provider,
+    pick_list_weather_provider: pick_list::State<weather::Provider>,
+    input_calendar_files: text_input::State,
+    calendar_files_value: String,
+    input_calendar_days: text_input::State,
+    calendar_days_value: String,
+    calendar_watch: bool,
+    input_caldav_url: text_input::State,
+    caldav_url_value: String,
+    input_caldav_username: text_input::State,
+    caldav_username_value: String,
+    input_caldav_password: text_input::State,
+    caldav_password_value: String,
+    input_mqtt_broker: text_input::State,
+    mqtt_broker_value: String,
+    input_mqtt_username: text_input::State,
+    mqtt_username_value: String,
+    input_mqtt_password: text_input::State,
+    mqtt_password_value: String,
+    input_mqtt_tiles: text_input::State,
+    mqtt_tiles_value: String,
+    input_mqtt_actions: text_input::State,
+    mqtt_actions_value: String,
+    input_mqtt_command_topic: text_input::State,
+    mqtt_command_topic_value: String,
+    mqtt_connect: button::State,
+    mqtt_connection: Option<mqtt::MqttConnection>,
+    mqtt_action_buttons: Vec<button::State>,
+    scroll_mqtt: scrollable::State,
+    go_to_calendar: button::State,
     go_to_clock: button::State,
+    go_to_departures: button::State,
+    go_to_home: button::State,
     go_to_meteo: button::State,
+    go_to_mqtt: button::State,
     go_to_news: button::State,
+    go_to_notes: button::State,
     go_to_parameter: button::State,
+    go_to_system: button::State,
+    go_to_todo: button::State,
+    scroll_menu: scrollable::State,
+    fullscreen: bool,
+    input_screensaver_delay: text_input::State,
+    screensaver_delay_value: String,
+    last_activity: Option<DateTime<Local>>,
+    screensaver_since: Option<DateTime<Local>>,
+    kiosk: bool,
+    cursor: Option<cursor::Cursor>,
+    previous_page: String,
+    carousel_enabled: bool,
+    carousel_pages: Vec<String>,
+    input_carousel_dwell: text_input::State,
+    carousel_dwell_value: String,
+    dashboard_widgets: Vec<dashboard::Widget>,
+    dashboard_columns: u8,
+    pick_list_dashboard_columns: pick_list::State<u8>,
+    dashboard_buttons: Vec<button::State>,
+    dashboard_move_up: Vec<button::State>,
+    toggle_menu: button::State,
+    menu_collapsed: bool,
+    layout: layout::Layout,
+    pick_list_font_scale: pick_list::State<layout::FontScale>,
     dirty: bool,
     saving: bool,
+    settings_baseline: serde_json::Value,
+    settings_written: serde_json::Value,
+    // Same as `tasks_read_only`, for assistant.json.
+    settings_read_only: bool,
     local_date: String,
     local_time: String,
-    weather_json: String,
+    analog_clock: AnalogClock,
+    clock_face: clock::ClockFace,
+    pick_list_clock_face: pick_list::State<clock::ClockFace>,
+    calendar_events: Vec<calendar::CalendarEvent>,
+    caldav_cache: caldav::CalDavCache,
+    caldav_syncing: bool,
+    caldav_error: Option<String>,
+    calendar_errors: Vec<String>,
+    calendar_modified: Option<std::time::SystemTime>,
+    next_event: Option<calendar::Occurrence>,
+    scroll_calendar: scrollable::State,
+    scroll_dashboard: scrollable::State,
+    scroll_parameter: scrollable::State,
+    task_list: todo::TaskList,
+    task_controls: Vec<TaskControls>,
+    input_task: text_input::State,
+    task_value: String,
+    input_task_due: text_input::State,
+    task_due_value: String,
+    task_due_error: Option<String>,
+    pick_list_task_priority: pick_list::State<todo::Priority>,
+    task_priority: todo::Priority,
+    add_task: button::State,
+    scroll_todo: scrollable::State,
+    tasks_dirty: bool,
+    tasks_saving: bool,
+    // Set when todo.json could neither be read nor moved aside: it is then never overwritten.
+    tasks_read_only: bool,
+    note_board: notes::NoteBoard,
+    notes_dirty: bool,
+    notes_saving: bool,
+    notes_read_only: bool,
+    note_controls: Vec<NoteControls>,
+    input_note: text_input::State,
+    note_value: String,
+    pick_list_note_color: pick_list::State<notes::NoteColor>,
+    note_color: notes::NoteColor,
+    add_note: button::State,
+    scroll_notes: scrollable::State,
+    theme_choice: theme::ThemeChoice,
+    theme: style::Theme,
+    pick_list_theme: pick_list::State<theme::ThemeChoice>,
+    input_latitude: text_input::State,
+    latitude_value: String,
+    input_longitude: text_input::State,
+    longitude_value: String,
+    night_schedule: night::NightSchedule,
+    pick_list_night_schedule: pick_list::State<night::NightSchedule>,
+    input_night_start: text_input::State,
+    night_start_value: String,
+    input_night_end: text_input::State,
+    night_end_value: String,
+    day_brightness: u8,
+    slider_day_brightness: slider::State,
+    night_brightness: u8,
+    slider_night_brightness: slider::State,
+    night_active: bool,
+    backlight: Option<backlight::Backlight>,
+    input_sensor_path: text_input::State,
+    sensor_path_value: String,
+    sensor: Option<sensors::Sensor>,
+    indoor_history: sensors::History,
+    indoor_sparkline: Sparkline,
+    system_monitor: system::Monitor,
+    system_status: system::SystemStatus,
+    weather_report: Option<weather::WeatherReport>,
+    weather_error: Option<String>,
+    weather_fetching: bool,
+    air_quality: Option<air::AirQuality>,
+    uv_index: Option<f32>,
+    weather_recorded: Option<i64>,
+    go_to_history: button::State,
+    history_range: history::Range,
+    pick_list_history_range: pick_list::State<history::Range>,
+    weather_chart: WeatherChart,
     rss_newspaper: Channel,
+    news_fetching: bool,
+    status: status::SharedStatus,
+    remote_queue: remote::Queue,
+    notifications: Vec<(remote::Notification, DateTime<Local>)>,
+    notification_history: Vec<(DateTime<Local>, remote::Notification)>,
+    input_weather_rules: text_input::State,
+    weather_rules_value: String,
+    alert_tracker: alerts::Tracker,
+    go_to_alerts: button::State,
+    scroll_alerts: scrollable::State,
+    input_gtfs_path: text_input::State,
+    gtfs_path_value: String,
+    input_gtfs_stops: text_input::State,
+    gtfs_stops_value: String,
+    input_gtfs_realtime: text_input::State,
+    gtfs_realtime_value: String,
+    transit_feed: transit::Feed,
+    // Path and stops the feed was loaded for, to reload it when they change.
+    transit_source: Option<transit::Source>,
+    transit_loading: bool,
+    transit_boards: Vec<transit::Board>,
+    transit_error: Option<String>,
+    scroll_departures: scrollable::State,
+    go_to_podcast: button::State,
+    input_podcast_feeds: text_input::State,
+    podcast_feeds_value: String,
+    input_radio_stations: text_input::State,
+    radio_stations_value: String,
+    input_audio_player: text_input::State,
+    audio_player_value: String,
+    apply_audio_player: button::State,
+    audio_volume: u8,
+    player: Option<audio::Player>,
+    player_error: Option<String>,
+    podcasts: Vec<podcast::Podcast>,
+    podcast_errors: Vec<String>,
+    podcasts_loading: bool,
+    station_buttons: Vec<button::State>,
+    episode_buttons: Vec<button::State>,
+    player_controls: PlayerControls,
+    scroll_podcast: scrollable::State,
+    go_to_music: button::State,
+    input_mpd_address: text_input::State,
+    mpd_address_value: String,
+    mpd_snapshot: Option<mpd::Snapshot>,
+    mpd_error: Option<String>,
+    mpd_connection: mpd::Connection,
+    mpd_refreshing: bool,
+    mpd_failures: u32,
+    mpd_retry_at: Option<std::time::Instant>,
+    mpd_controls: MpdControls,
+    mpd_queue_buttons: Vec<button::State>,
+    mpd_playlist_buttons: Vec<button::State>,
+    scroll_music: scrollable::State,
     pick_list: pick_list::State<news::Newspaper>,
     selected_newspaper: news::Newspaper,
     scroll: scrollable::State,
 }
 
+#[derive(Debug, Default)]
+struct TaskControls {
+    move_up: button::State,
+    move_down: button::State,
+    delete: button::State,
+}
+
+#[derive(Debug, Default)]
+struct PlayerControls {
+    pause: button::State,
+    stop: button::State,
+    volume_down: button::State,
+    volume_up: button::State,
+}
+
+#[derive(Debug, Default)]
+struct MpdControls {
+    previous: button::State,
+    toggle: button::State,
+    next: button::State,
+    volume_down: button::State,
+    volume_up: button::State,
+}
+
+#[derive(Debug, Default)]
+struct NoteControls {
+    input: text_input::State,
+    pin: button::State,
+    color: button::State,
+    delete: button::State,
+}
+
+impl State {
+    fn saved_state(&self) -> state::SavedState {
+        state::SavedState {
+            weatherapi_value: self.weatherapi_value.clone(),
+            searchcity_value: self.searchcity_value.clone(),
+            weather_provider: self.weather_provider,
+            clock_face: self.clock_face,
+            calendar_files_value: self.calendar_files_value.clone(),
+            calendar_days_value: self.calendar_days_value.clone(),
+            calendar_watch: self.calendar_watch,
+            caldav_url_value: self.caldav_url_value.clone(),
+            caldav_username_value: self.caldav_username_value.clone(),
+            caldav_password_value: self.caldav_password_value.clone(),
+            theme_choice: self.theme_choice,
+            latitude_value: self.latitude_value.clone(),
+            longitude_value: self.longitude_value.clone(),
+            night_schedule: self.night_schedule,
+            night_start_value: self.night_start_value.clone(),
+            night_end_value: self.night_end_value.clone(),
+            day_brightness: self.day_brightness,
+            night_brightness: self.night_brightness,
+            font_scale: self.layout.font_scale,
+            screensaver_delay_value: self.screensaver_delay_value.clone(),
+            carousel_enabled: self.carousel_enabled,
+            carousel_pages: self.carousel_pages.clone(),
+            carousel_dwell_value: self.carousel_dwell_value.clone(),
+            dashboard_widgets: self.dashboard_widgets.clone(),
+            dashboard_columns: self.dashboard_columns,
+            mqtt_broker_value: self.mqtt_broker_value.clone(),
+            mqtt_username_value: self.mqtt_username_value.clone(),
+            mqtt_password_value: self.mqtt_password_value.clone(),
+            mqtt_tiles_value: self.mqtt_tiles_value.clone(),
+            mqtt_actions_value: self.mqtt_actions_value.clone(),
+            mqtt_command_topic_value: self.mqtt_command_topic_value.clone(),
+            sensor_path_value: self.sensor_path_value.clone(),
+            weather_rules_value: self.weather_rules_value.clone(),
+            gtfs_path_value: self.gtfs_path_value.clone(),
+            gtfs_stops_value: self.gtfs_stops_value.clone(),
+            gtfs_realtime_value: self.gtfs_realtime_value.clone(),
+            podcast_feeds_value: self.podcast_feeds_value.clone(),
+            radio_stations_value: self.radio_stations_value.clone(),
+            audio_player_value: self.audio_player_value.clone(),
+            audio_volume: self.audio_volume,
+            mpd_address_value: self.mpd_address_value.clone(),
+        }
+    }
+
+    // Called once the settings are loaded and whenever they change: only a real change is saved.
+    fn publish_settings(&mut self) -> bool {
+        let saved_state = self.saved_state();
+        let user_settings = saved_state.user_settings(&self.settings_baseline);
+        if user_settings == self.settings_written {
+            return false;
+        }
+        self.settings_written = user_settings;
+        if let Ok(mut snapshot) = self.status.lock() {
+            snapshot.settings = status::public_settings(&saved_state);
+        }
+        true
+    }
+
+    fn start_status_server(&self, launch: &config::LaunchConfig) {
+        if let Some(address) = &launch.status_address {
+            match status::start(address, self.status.clone(), self.remote_queue.clone()) {
+                Ok(bound) => log::info!("API d'état disponible sur http://{}/api", bound),
+                Err(e) => log::error!("Impossible de démarrer l'API d'état sur {} : {}", address, e),
+            }
+        }
+        let socket = launch.control_socket();
+        match remote::listen(&socket, self.remote_queue.clone()) {
+            Ok(()) => log::info!("Commandes à distance acceptées sur {}", socket.display()),
+            Err(e) => log::error!("Impossible d'ouvrir le socket de commande {} : {}", socket.display(), e),
+        }
+    }
+
+    fn handle_remote_command(&mut self, command: remote::RemoteCommand, now: DateTime<Local>) -> Command<Message> {
+        match command {
+            remote::RemoteCommand::ShowPage { page } => {
+                if self.page_show == "screensaver" {
+                    self.stop_screensaver();
+                }
+                self.last_activity = Some(now);
+                self.open_page(&page)
+            }
+            remote::RemoteCommand::Refresh { page } => {
+                let page = page.unwrap_or_else(|| self.page_show.clone());
+                self.refresh_page(&page)
+            }
+            remote::RemoteCommand::Notify(notification) => {
+                self.notify(notification, now);
+                Command::none()
+            }
+        }
+    }
+
+    // Shows a banner and keeps a trace of it on the alerts page.
+    fn notify(&mut self, notification: remote::Notification, now: DateTime<Local>) {
+        let expires = notification.expires(now);
+        self.notification_history.push((now, notification.clone()));
+        if self.notification_history.len() > alerts::HISTORY_LENGTH {
+            self.notification_history.remove(0);
+        }
+        self.notifications.push((notification, expires));
+    }
+
+    fn check_weather_alerts(&mut self, report: &weather::WeatherReport, one_call: Option<&json::JsonValue>) {
+        let rules = alerts::parse_rules(&self.weather_rules_value);
+        let mut active = alerts::triggered(&rules, &alerts::conditions(report, one_call));
+        if let Some(one_call) = one_call {
+            active.extend(alerts::official_alerts(one_call));
+        }
+        let now = Local::now();
+        for alert in self.alert_tracker.update(active) {
+            self.notify(alert.notification(), now);
+        }
+    }
+
+    fn show_page(&mut self, page: &str) {
+        self.page_show = String::from(page);
+        if self.layout.is_compact() {
+            self.menu_collapsed = true;
+        }
+    }
+
+    fn start_screensaver(&mut self, now: DateTime<Local>) -> Command<Message> {
+        self.previous_page = std::mem::replace(&mut self.page_show, String::from("screensaver"));
+        self.screensaver_since = Some(now);
+        if let Some(cursor) = &mut self.cursor {
+            cursor.set_hidden(true);
+        }
+        if self.weather_provider.is_configured(&self.weatherapi_value) {
+            return self.refresh_weather();
+        }
+        Command::none()
+    }
+
+    fn stop_screensaver(&mut self) {
+        self.page_show = std::mem::take(&mut self.previous_page);
+        self.screensaver_since = None;
+        if let Some(cursor) = &mut self.cursor {
+            cursor.set_hidden(self.kiosk);
+        }
+    }
+
+    fn open_page(&mut self, page: &str) -> Command<Message> {
+        let refresh = self.refresh_page(page);
+        self.show_page(page);
+        refresh
+    }
+
+    // Slow refreshes come back as a command so that the page shows at once.
+    fn refresh_page(&mut self, page: &str) -> Command<Message> {
+        match page {
+            "calendar" => {
+                self.load_calendar();
+                return self.sync_caldav();
+            }
+            "home" => {
+                let mut commands = Vec::new();
+                if self.dashboard_widgets.contains(&dashboard::Widget::Headlines) {
+                    commands.push(self.refresh_news());
+                }
+                if self.dashboard_widgets.contains(&dashboard::Widget::Weather) && self.weather_provider.is_configured(&self.weatherapi_value) {
+                    commands.push(self.refresh_weather());
+                }
+                return Command::batch(commands);
+            }
+            "meteo" => return self.refresh_weather(),
+            "news" => return self.refresh_news(),
+            "history" => self.load_history(),
+            "system" => self.system_status = self.system_monitor.sample(),
+            "departures" => return self.refresh_departures(),
+            "podcast" => return self.refresh_podcasts(),
+            "music" => {
+                self.mpd_snapshot = None;
+                self.mpd_retry_at = None;
+                return self.refresh_music();
+            }
+            _ => {}
+        }
+        Command::none()
+    }
+
+    fn refresh_departures(&mut self) -> Command<Message> {
+        if self.gtfs_path_value.trim().is_empty() {
+            self.transit_source = None;
+            self.transit_boards.clear();
+            self.transit_error = Some(String::from("Aucun fichier GTFS configuré"));
+            return Command::none();
+        }
+        if self.transit_loading {
+            return Command::none();
+        }
+        self.transit_loading = true;
+        let source = transit::Source::new(&self.gtfs_path_value, &self.gtfs_stops_value);
+        let reload = self.transit_source.as_ref() != Some(&source);
+        Command::perform(
+            transit::fetch_in_background(source.clone(), reload, self.gtfs_realtime_value.trim().to_string()),
+            move |result| Message::DeparturesLoaded(source.clone(), result),
+        )
+    }
+
+    fn show_departures(&mut self, source: transit::Source, result: Result<transit::Update, String>) {
+        let update = match result {
+            Ok(update) => update,
+            Err(e) => {
+                log::warn!("Impossible de lire le fichier GTFS : {}", e);
+                self.transit_source = None;
+                self.transit_boards.clear();
+                self.transit_error = Some(["Impossible de lire le fichier GTFS : ".to_string(), e].concat());
+                return;
+            }
+        };
+        if let Some(feed) = update.feed {
+            self.transit_feed = feed;
+            self.transit_source = Some(source);
+        }
+        self.transit_boards = transit::boards(&self.transit_feed, &update.realtime, Local::now());
+        self.transit_error = if self.transit_feed.is_empty() {
+            Some(String::from("Aucun des arrêts configurés n'est dans le fichier GTFS"))
+        } else {
+            None
+        };
+    }
+
+    fn refresh_podcasts(&mut self) -> Command<Message> {
+        if self.podcasts_loading {
+            return Command::none();
+        }
+        self.podcasts_loading = true;
+        Command::perform(
+            podcast::fetch_in_background(podcast::parse_feeds(&self.podcast_feeds_value)),
+            Message::PodcastsLoaded,
+        )
+    }
+
+    fn show_podcasts(&mut self, results: Vec<Result<podcast::Podcast, String>>) {
+        self.podcasts.clear();
+        self.podcast_errors.clear();
+        for result in results {
+            match result {
+                Ok(podcast) => self.podcasts.push(podcast),
+                Err(e) => self.podcast_errors.push(e),
+            }
+        }
+    }
+
+    // Skipped while the previous refresh runs, and for a while after MPD could not be reached.
+    fn refresh_music(&mut self) -> Command<Message> {
+        if self.mpd_refreshing || matches!(self.mpd_retry_at, Some(retry_at) if std::time::Instant::now() < retry_at) {
+            return Command::none();
+        }
+        self.mpd_refreshing = true;
+        let previous = self.mpd_snapshot.clone();
+        Command::perform(
+            mpd::run_in_background(
+                self.mpd_connection.clone(),
+                mpd::Address::parse(&self.mpd_address_value),
+                move |client| client.snapshot(previous.as_ref()),
+            ),
+            Message::MusicRefreshed,
+        )
+    }
+
+    fn show_music(&mut self, result: Result<mpd::Snapshot, String>) {
+        match result {
+            Ok(snapshot) => {
+                // A failed command stays on screen until the next one; only connection errors clear here.
+                if self.mpd_snapshot.is_none() {
+                    self.mpd_error = None;
+                }
+                self.mpd_snapshot = Some(snapshot);
+                self.mpd_failures = 0;
+                self.mpd_retry_at = None;
+            }
+            Err(e) => {
+                self.mpd_snapshot = None;
+                self.mpd_error = Some(["MPD injoignable : ".to_string(), e].concat());
+                self.mpd_failures += 1;
+                self.mpd_retry_at = Some(std::time::Instant::now() + mpd::retry_delay(self.mpd_failures));
+            }
+        }
+    }
+
+    fn mpd_command(&mut self, command: impl FnMut(&mut mpd::Client) -> Result<(), Box<dyn std::error::Error>> + Send + 'static) -> Command<Message> {
+        Command::perform(
+            mpd::run_in_background(self.mpd_connection.clone(), mpd::Address::parse(&self.mpd_address_value), command),
+            Message::MpdCommandDone,
+        )
+    }
+
+    fn start_player(&mut self) {
+        // Dropping the previous player stops what it was playing.
+        self.player = None;
+        let backend = audio::Mpv::new(&self.audio_player_value, audio::Mpv::socket_path());
+        self.player = Some(audio::Player::new(Box::new(backend), state::data_path(audio::POSITIONS_FILE), self.audio_volume));
+    }
+
+    fn play(&mut self, track: audio::Track) {
+        if let Some(player) = &mut self.player {
+            self.player_error = player
+                .play(track)
+                .err()
+                .map(|e| ["Lecture impossible : ".to_string(), e.to_string()].concat());
+        }
+    }
+
+    fn control_player(&mut self, control: impl FnOnce(&mut audio::Player) -> Result<(), Box<dyn std::error::Error>>) {
+        if let Some(player) = &mut self.player {
+            self.player_error = control(player).err().map(|e| ["Lecteur : ".to_string(), e.to_string()].concat());
+            self.audio_volume = player.volume();
+        }
+    }
+
+    fn refresh_news(&mut self) -> Command<Message> {
+        if self.news_fetching {
+            return Command::none();
+        }
+        self.news_fetching = true;
+        let newspaper = self.selected_newspaper;
+        Command::perform(news::fetch_in_background(newspaper), move |result| Message::NewsFetched(newspaper, result.map(Box::new)))
+    }
+
+    fn show_news(&mut self, result: Result<Box<Channel>, String>) {
+        self.rss_newspaper = match result {
+            Ok(channel) => *channel,
+            Err(e) => {
+                log::warn!("Impossible de lire les actualités : {}", e);
+                Channel::default()
+            }
+        };
+        if let Ok(mut snapshot) = self.status.lock() {
+            snapshot.news = news::items(&self.rss_newspaper);
+        }
+    }
+
+    fn refresh_weather(&mut self) -> Command<Message> {
+        if self.weather_fetching {
+            return Command::none();
+        }
+        self.weather_fetching = true;
+        let query = weather::Query {
+            city: self.searchcity_value.trim().to_string(),
+            coordinates: astronomy::parse_coordinates(&self.latitude_value, &self.longitude_value),
+        };
+        Command::perform(
+            weather::fetch_in_background(self.weather_provider, self.weatherapi_value.clone(), query),
+            Message::WeatherFetched,
+        )
+    }
+
+    fn apply_weather(&mut self, result: Result<weather::Update, String>) {
+        match result {
+            Ok(update) => {
+                self.record_weather(&update.report);
+                self.check_weather_alerts(&update.report, update.one_call.as_ref());
+                self.air_quality = update.air_quality;
+                self.uv_index = update.uv_index;
+                self.weather_report = Some(update.report);
+                self.weather_error = None;
+            }
+            Err(e) => {
+                log::warn!("Météo indisponible ({}) : {}", self.weather_provider, e);
+                self.weather_report = None;
+                self.weather_error = Some(e);
+                self.air_quality = None;
+                self.uv_index = None;
+            }
+        }
+        if let Ok(mut snapshot) = self.status.lock() {
+            snapshot.weather = self.weather_report.as_ref().and_then(|report| serde_json::to_value(report).ok());
+        }
+    }
+
+    fn record_weather(&mut self, report: &weather::WeatherReport) {
+        let observation = history::Observation::from_report(report);
+        if self.weather_recorded != Some(observation.time) {
+            match history::append(&state::data_path(history::FILE_NAME), &observation) {
+                Ok(()) => self.weather_recorded = Some(observation.time),
+                Err(e) => log::warn!("Impossible d'enregistrer la météo : {}", e),
+            }
+        }
+    }
+
+    fn load_history(&mut self) {
+        let now = Local::now().timestamp();
+        let from = now - self.history_range.seconds();
+        let observations = history::load(&state::data_path(history::FILE_NAME), from).unwrap_or_else(|e| {
+            log::warn!("Impossible de lire l'historique météo : {}", e);
+            Vec::new()
+        });
+        self.weather_chart.update(observations, from, now);
+    }
+
+    fn load_calendar(&mut self) {
+        let paths = calendar::parse_paths(&self.calendar_files_value);
+        self.calendar_modified = calendar::last_modified(&paths);
+        let (events, errors) = calendar::load_calendars(&paths);
+        self.calendar_events = events;
+        self.calendar_errors = errors;
+        self.calendar_events.append(&mut caldav::events(&self.caldav_cache));
+        self.next_event = calendar::next_event(&self.calendar_events, Local::now().naive_local());
+    }
+
+    fn apply_theme(&mut self) {
+        self.theme = if self.night_active {
+            style::Theme::night()
+        } else {
+            style::Theme::from_choice(self.theme_choice)
+        };
+        self.analog_clock.set_theme(self.theme);
+        self.indoor_sparkline.set_theme(self.theme);
+        self.weather_chart.set_theme(self.theme);
+    }
+
+    fn apply_brightness(&self) {
+        if let Some(backlight) = &self.backlight {
+            let percent = if self.night_active { self.night_brightness } else { self.day_brightness };
+            // Some panels flicker on every write, even of the same value.
+            if backlight.percent().ok() != Some(percent) {
+                let _ = backlight.set_percent(percent);
+            }
+        }
+    }
+
+    fn update_night_mode(&mut self, now: DateTime<Local>) {
+        let night_active = night::is_night(
+            self.night_schedule,
+            now,
+            &self.night_start_value,
+            &self.night_end_value,
+            astronomy::parse_coordinates(&self.latitude_value, &self.longitude_value),
+        );
+        if night_active != self.night_active {
+            self.night_active = night_active;
+            self.apply_theme();
+            self.apply_brightness();
+        }
+    }
+
+    fn sync_caldav(&mut self) -> Command<Message> {
+        let account = caldav::CalDavAccount {
+            url: self.caldav_url_value.clone(),
+            username: self.caldav_username_value.clone(),
+            password: self.caldav_password_value.clone(),
+        };
+        if !account.is_configured() {
+            self.caldav_error = None;
+            return Command::none();
+        }
+        if self.caldav_syncing {
+            return Command::none();
+        }
+        self.caldav_syncing = true;
+        let url = account.url.clone();
+        Command::perform(caldav::synchronize_in_background(account, self.caldav_cache.clone()), move |result| {
+            Message::CalDavSynced(url.clone(), result)
+        })
+    }
+
+    fn locate_sensor(&mut self) {
+        self.sensor = sensors::Sensor::locate(&self.sensor_path_value, std::path::Path::new(backlight::SYSFS_ROOT));
+        self.indoor_history.clear();
+        self.sample_sensor(Local::now());
+    }
+
+    fn sample_sensor(&mut self, now: DateTime<Local>) {
+        if let Some(sensor) = &self.sensor {
+            match sensor.read() {
+                Ok(reading) => self.indoor_history.push(now, reading),
+                Err(e) => log::warn!("Lecture du capteur {} impossible : {}", sensor.name(), e),
+            }
+        }
+        self.indoor_sparkline.update(self.indoor_history.temperatures());
+    }
+
+    fn connect_mqtt(&mut self) {
+        if let Some(connection) = self.mqtt_connection.take() {
+            connection.disconnect();
+        }
+        let settings = mqtt::MqttSettings {
+            broker: self.mqtt_broker_value.clone(),
+            username: self.mqtt_username_value.clone(),
+            password: self.mqtt_password_value.clone(),
+            command_topic: self.mqtt_command_topic_value.clone(),
+            tiles: mqtt::parse_tiles(&self.mqtt_tiles_value),
+        };
+        self.mqtt_connection = mqtt::MqttConnection::connect(&settings, self.remote_queue.clone());
+    }
+}
+
+// There is only ever one of it, so the loaded state is not boxed.
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 enum Assistant {
-    Loading,
+    Loading(config::LaunchConfig),
     Loaded(State)
 }
 
 #[derive(Debug, Clone)]
 enum Message {
+    CalendarPressed,
+    CalendarDaysEdited(String),
+    CalendarFilesEdited(String),
+    CalendarRefresh,
+    CalendarWatchToggled(bool),
+    CarouselAdvance,
+    CarouselDwellEdited(String),
+    CarouselPageToggled(&'static str, bool),
+    CarouselToggled(bool),
+    DayBrightnessChanged(u8),
+    DeparturesLoaded(transit::Source, Result<transit::Update, String>),
+    DeparturesPressed,
+    DeparturesRefresh,
+    AudioPlayerApplied,
+    AudioPlayerEdited(String),
+    FontScaleSelected(layout::FontScale),
+    CalDavPasswordEdited(String),
+    CalDavSync,
+    CalDavSynced(String, Result<caldav::CalDavCache, String>),
+    CalDavUrlEdited(String),
+    CalDavUsernameEdited(String),
     ClockPressed,
+    DashboardColumnsSelected(u8),
+    DashboardWidgetMovedUp(dashboard::Widget),
+    DashboardWidgetPressed(dashboard::Widget),
+    DashboardWidgetToggled(dashboard::Widget, bool),
+    ClockFaceSelected(clock::ClockFace),
+    AlertsPressed,
+    GtfsPathEdited(String),
+    GtfsRealtimeEdited(String),
+    GtfsStopsEdited(String),
+    HistoryPressed,
+    HistoryRangeSelected(history::Range),
+    HomePressed,
+    LatitudeEdited(String),
+    LongitudeEdited(String),
+    MenuToggled,
     MeteoPressed,
+    MpdAddressEdited(String),
+    MpdCommandDone(Result<(), String>),
+    MpdControlPressed(mpd::Control),
+    MpdPlaylistPressed(usize),
+    MpdQueuePressed(u32),
+    MqttActionPressed(usize),
+    MqttActionsEdited(String),
+    MqttBrokerEdited(String),
+    MqttCommandTopicEdited(String),
+    MqttConnect,
+    MqttPasswordEdited(String),
+    MqttPressed,
+    MqttTilesEdited(String),
+    MqttUsernameEdited(String),
+    MusicPressed,
+    MusicRefresh,
+    MusicRefreshed(Result<mpd::Snapshot, String>),
     NewsPressed,
     ParameterPressed,
+    PlayerPauseToggled,
+    PlayerStopped,
+    PlayerTick,
+    PlayerVolumeChanged(bool),
+    PodcastEpisodePressed(usize, usize),
+    PodcastFeedsEdited(String),
+    PodcastsLoaded(Vec<Result<podcast::Podcast, String>>),
+    PodcastPressed,
+    RadioStationPressed(usize),
+    RadioStationsEdited(String),
+    ScreensaverDelayEdited(String),
+    SensorPathEdited(String),
+    SensorSampled,
+    SystemPressed,
+    SystemRefresh,
     Saved(Result<(), state::SaveError>),
-    Loaded(Result<state::SavedState, state::LoadError>),
+    Loaded(Result<Box<state::SavedState>, state::LoadError>),
     NewspaperSelected(news::Newspaper),
+    NewsFetched(news::Newspaper, Result<Box<Channel>, String>),
+    NightBrightnessChanged(u8),
+    NightEndEdited(String),
+    NightScheduleSelected(night::NightSchedule),
+    NightStartEdited(String),
+    NoteAdded,
+    NoteChanged(usize, String),
+    NoteColorCycled(usize),
+    NoteColorSelected(notes::NoteColor),
+    NoteDeleted(usize),
+    NoteEdited(String),
+    NotePinToggled(usize),
+    NotesLoaded(Result<notes::NoteBoard, state::LoadError>),
+    NotesPressed,
+    NotesSaved(Result<(), state::SaveError>),
     SearchCityEdited(String),
+    TaskAdded,
+    TaskDeleted(usize),
+    TaskDueEdited(String),
+    TaskEdited(String),
+    TaskMovedDown(usize),
+    TaskMovedUp(usize),
+    TaskPrioritySelected(todo::Priority),
+    TaskToggled(usize, bool),
+    TasksLoaded(Result<todo::TaskList, state::LoadError>),
+    TasksSaved(Result<(), state::SaveError>),
+    TodoPressed,
     WeatherApiEdited(String),
+    WeatherFetched(Result<weather::Update, String>),
+    WeatherProviderSelected(weather::Provider),
+    WeatherRefresh,
+    WeatherRulesEdited(String),
+    ThemeSelected(theme::ThemeChoice),
     Tick(chrono::DateTime<chrono::Local>),
+    UserActivity,
+    WindowResized(u32, u32),
 }
 
 impl Application for Assistant {
     type Executor = iced::executor::Default;
     type Message = Message;
-    type Flags = ();
-    fn new(_flags: ()) -> (Self, Command<Message>) {
+    type Flags = config::LaunchConfig;
+    fn new(flags: config::LaunchConfig) -> (Self, Command<Message>) {
+        let defaults = flags.settings.clone();
         (
-            Assistant::Loading,
-            Command::perform(state::SavedState::load(), Message::Loaded),
+            Assistant::Loading(flags),
+            Command::perform(state::SavedState::load(defaults), |result| Message::Loaded(result.map(Box::new))),
         )
     }
 
+    fn mode(&self) -> window::Mode {
+        let fullscreen = match self {
+            Assistant::Loading(launch) => launch.fullscreen,
+            Assistant::Loaded(state) => state.fullscreen,
+        };
+        if fullscreen {
+            window::Mode::Fullscreen
+        } else {
+            window::Mode::Windowed
+        }
+    }
+
     fn title(&self) -> String {
         String::from("Assistant personnel")
     }
 
     fn update(&mut self, message: Message, _: &mut Clipboard) -> Command<Message> {
         match self {
-            Assistant::Loading => {
+            Assistant::Loading(launch) => {
+                let launch = launch.clone();
                 match message {
-                    Message::Loaded(Ok(state)) => {
-                        *self = Assistant::Loaded(State {
-                            page_show: String::from("clock"),
+                    Message::Loaded(result) => {
+                        let mut settings_read_only = false;
+                        let state = match result {
+                            Ok(state) => *state,
+                            Err(e) => {
+                                if let state::LoadError::OpenFileError = e {
+                                    log::info!("Aucun paramètre enregistré");
+                                }
+                                settings_read_only = !state::set_aside_unreadable(state::SETTINGS_FILE, &e);
+                                state::SavedState::from_defaults(&launch.settings)
+                            }
+                        };
+                        let mut loaded_state = State {
+                            page_show: launch.page.clone(),
+                            fullscreen: launch.fullscreen,
                             weatherapi_value: state.weatherapi_value,
                             searchcity_value: state.searchcity_value,
+                            weather_provider: state.weather_provider,
+                            clock_face: state.clock_face,
+                            calendar_files_value: state.calendar_files_value,
+                            calendar_days_value: state.calendar_days_value,
+                            calendar_watch: state.calendar_watch,
+                            caldav_url_value: state.caldav_url_value,
+                            caldav_username_value: state.caldav_username_value,
+                            caldav_password_value: state.caldav_password_value,
+                            theme_choice: state.theme_choice,
+                            latitude_value: state.latitude_value,
+                            longitude_value: state.longitude_value,
+                            night_schedule: state.night_schedule,
+                            night_start_value: state.night_start_value,
+                            night_end_value: state.night_end_value,
+                            day_brightness: state.day_brightness,
+                            night_brightness: state.night_brightness,
+                            layout: layout::Layout::new(launch.size.0, launch.size.1, state.font_scale),
+                            screensaver_delay_value: state.screensaver_delay_value,
+                            carousel_enabled: state.carousel_enabled,
+                            carousel_pages: state.carousel_pages,
+                            carousel_dwell_value: state.carousel_dwell_value,
+                            dashboard_widgets: state.dashboard_widgets,
+                            dashboard_columns: dashboard::clamp_columns(state.dashboard_columns),
+                            mqtt_broker_value: state.mqtt_broker_value,
+                            mqtt_username_value: state.mqtt_username_value,
+                            mqtt_password_value: state.mqtt_password_value,
+                            mqtt_tiles_value: state.mqtt_tiles_value,
+                            mqtt_actions_value: state.mqtt_actions_value,
+                            mqtt_command_topic_value: state.mqtt_command_topic_value,
+                            sensor_path_value: state.sensor_path_value,
+                            weather_rules_value: state.weather_rules_value,
+                            gtfs_path_value: state.gtfs_path_value,
+                            gtfs_stops_value: state.gtfs_stops_value,
+                            gtfs_realtime_value: state.gtfs_realtime_value,
+                            podcast_feeds_value: state.podcast_feeds_value,
+                            radio_stations_value: state.radio_stations_value,
+                            audio_player_value: state.audio_player_value,
+                            audio_volume: state.audio_volume,
+                            mpd_address_value: state.mpd_address_value,
+                            backlight: backlight::Backlight::find(std::path::Path::new(backlight::SYSFS_ROOT)),
+                            kiosk: launch.kiosk,
+                            cursor: cursor::Cursor::open(),
+                            settings_baseline: state::SavedState::baseline(&launch.settings),
+                            settings_written: serde_json::Value::Null,
+                            settings_read_only,
                             ..State::default()
-                        });
-                    }
-                    Message::Loaded(Err(_)) => {
-                        *self = Assistant::Loaded(State {
-                            page_show: String::from("clock"),
-                            ..State::default()
-                        });
+                        };
+                        loaded_state.publish_settings();
+                        if let Some(cursor) = &mut loaded_state.cursor {
+                            cursor.set_hidden(launch.kiosk);
+                        }
+                        loaded_state.start_status_server(&launch);
+                        loaded_state.connect_mqtt();
+                        loaded_state.locate_sensor();
+                        loaded_state.start_player();
+                        loaded_state.apply_theme();
+                        loaded_state.update_night_mode(Local::now());
+                        // `update_night_mode` only touches the backlight when day and night swap.
+                        loaded_state.apply_brightness();
+                        loaded_state.load_calendar();
+                        let sync = loaded_state.sync_caldav();
+                        *self = Assistant::Loaded(loaded_state);
+                        Command::batch(vec![
+                            sync,
+                            Command::perform(todo::TaskList::load(), Message::TasksLoaded),
+                            Command::perform(notes::NoteBoard::load(), Message::NotesLoaded),
+                        ])
                     }
-                    _ => {}
+                    _ => Command::none()
                 }
-
-                Command::none()
             }
             Assistant::Loaded(state) => {
-                let mut saved = false;
+                let mut tasks_changed = false;
+                let mut notes_changed = false;
+                let mut commands = Vec::new();
                 match message {
+                    Message::CalendarPressed => {
+                        commands.push(state.open_page("calendar"));
+                    }
+                    Message::CalendarDaysEdited(new_calendar_days_value) => {
+                        state.calendar_days_value = new_calendar_days_value;
+                    }
+                    Message::CalendarFilesEdited(new_calendar_files_value) => {
+                        state.calendar_files_value = new_calendar_files_value;
+                    }
+                    Message::CalendarRefresh => {
+                        let paths = calendar::parse_paths(&state.calendar_files_value);
+                        if state.calendar_watch && calendar::last_modified(&paths) != state.calendar_modified {
+                            state.load_calendar();
+                        } else {
+                            state.next_event = calendar::next_event(&state.calendar_events, Local::now().naive_local());
+                        }
+                    }
+                    Message::CalendarWatchToggled(calendar_watch) => {
+                        state.calendar_watch = calendar_watch;
+                    }
+                    Message::CarouselAdvance => {
+                        let now = Local::now();
+                        if state.page_show != "screensaver" && !carousel::is_paused(state.last_activity, now) {
+                            if let Some(page) = carousel::next_page(&state.carousel_pages, &state.page_show).map(String::from) {
+                                commands.push(state.open_page(&page));
+                            }
+                        }
+                    }
+                    Message::CarouselDwellEdited(new_carousel_dwell_value) => {
+                        state.carousel_dwell_value = new_carousel_dwell_value;
+                    }
+                    Message::CarouselPageToggled(page, selected) => {
+                        carousel::toggle_page(&mut state.carousel_pages, page, selected);
+                    }
+                    Message::CarouselToggled(carousel_enabled) => {
+                        state.carousel_enabled = carousel_enabled;
+                    }
+                    Message::CalDavPasswordEdited(new_caldav_password_value) => {
+                        state.caldav_password_value = new_caldav_password_value;
+                    }
+                    Message::CalDavSync => {
+                        commands.push(state.sync_caldav());
+                    }
+                    Message::CalDavSynced(url, result) => {
+                        state.caldav_syncing = false;
+                        // The account changed while this sync was running.
+                        if url == state.caldav_url_value {
+                            match result {
+                                Ok(cache) => {
+                                    state.caldav_cache = cache;
+                                    state.caldav_error = None;
+                                    state.load_calendar();
+                                }
+                                Err(e) => state.caldav_error = Some(["Synchronisation CalDAV impossible : ".to_string(), e].concat()),
+                            }
+                        }
+                    }
+                    Message::CalDavUrlEdited(new_caldav_url_value) => {
+                        state.caldav_cache = caldav::CalDavCache::default();
+                        state.caldav_url_value = new_caldav_url_value;
+                    }
+                    Message::CalDavUsernameEdited(new_caldav_username_value) => {
+                        state.caldav_username_value = new_caldav_username_value;
+                    }
+                    Message::DayBrightnessChanged(day_brightness) => {
+                        state.day_brightness = day_brightness;
+                        state.apply_brightness();
+                    }
+                    Message::FontScaleSelected(font_scale) => {
+                        state.layout.font_scale = font_scale;
+                    }
                     Message::ClockPressed => {
-                        state.page_show = String::from("clock");
+                        state.show_page("clock");
+                    }
+                    Message::DashboardColumnsSelected(dashboard_columns) => {
+                        state.dashboard_columns = dashboard_columns;
+                    }
+                    Message::DashboardWidgetMovedUp(widget) => {
+                        dashboard::move_up(&mut state.dashboard_widgets, widget);
+                    }
+                    Message::DashboardWidgetPressed(widget) => {
+                        commands.push(state.open_page(widget.page()));
+                    }
+                    Message::DashboardWidgetToggled(widget, shown) => {
+                        dashboard::toggle(&mut state.dashboard_widgets, widget, shown);
+                    }
+                    Message::HomePressed => {
+                        commands.push(state.open_page("home"));
+                    }
+                    Message::ClockFaceSelected(clock_face) => {
+                        state.clock_face = clock_face;
+                    }
+                    Message::LatitudeEdited(new_latitude_value) => {
+                        state.latitude_value = new_latitude_value;
+                    }
+                    Message::LongitudeEdited(new_longitude_value) => {
+                        state.longitude_value = new_longitude_value;
+                    }
+                    Message::MenuToggled => {
+                        state.menu_collapsed = !state.menu_collapsed;
+                    }
+                    Message::AlertsPressed => {
+                        state.show_page("alerts");
+                    }
+                    Message::HistoryPressed => {
+                        commands.push(state.open_page("history"));
+                    }
+                    Message::HistoryRangeSelected(history_range) => {
+                        state.history_range = history_range;
+                        state.load_history();
                     }
                     Message::MeteoPressed => {
-                        let weather_response = weather::get_weather_from_search(&state.searchcity_value, &state.weatherapi_value);
-                        state.weather_json = match weather_response {
-                            Ok(v) => { v.to_string() },
-                            Err(_e) => { json::parse(r#"{"error":"Vous avez besoin d'une clé API et d'une connexion Internet pour utiliser Météo"}"#).unwrap().to_string() }
-                        };
-                        state.page_show = String::from("meteo");
+                        commands.push(state.open_page("meteo"));
+                    }
+                    Message::MqttActionPressed(index) => {
+                        if let (Some(connection), Some(action)) = (&state.mqtt_connection, mqtt::parse_actions(&state.mqtt_actions_value).get(index)) {
+                            if let Err(e) = connection.publish(action) {
+                                log::warn!("Publication MQTT impossible sur {} : {}", action.topic, e);
+                            }
+                        }
+                    }
+                    Message::MqttActionsEdited(new_mqtt_actions_value) => {
+                        state.mqtt_actions_value = new_mqtt_actions_value;
+                    }
+                    Message::MqttBrokerEdited(new_mqtt_broker_value) => {
+                        state.mqtt_broker_value = new_mqtt_broker_value;
+                    }
+                    Message::MqttCommandTopicEdited(new_mqtt_command_topic_value) => {
+                        state.mqtt_command_topic_value = new_mqtt_command_topic_value;
+                    }
+                    Message::MqttConnect => {
+                        state.connect_mqtt();
+                    }
+                    Message::MqttPasswordEdited(new_mqtt_password_value) => {
+                        state.mqtt_password_value = new_mqtt_password_value;
+                    }
+                    Message::MqttPressed => {
+                        state.show_page("mqtt");
+                    }
+                    Message::MqttTilesEdited(new_mqtt_tiles_value) => {
+                        state.mqtt_tiles_value = new_mqtt_tiles_value;
+                    }
+                    Message::MqttUsernameEdited(new_mqtt_username_value) => {
+                        state.mqtt_username_value = new_mqtt_username_value;
                     }
                     Message::NewsPressed => {
-
-                        let newspaper_response = news::get_news(state.selected_newspaper);
-
-                        state.rss_newspaper = match newspaper_response{
-                            Ok(v) => { v },
-                            Err(_e) => { Channel::default() }  
-                        };
-                        state.page_show = String::from("news");
+                        commands.push(state.open_page("news"));
                     }
                     Message::NewspaperSelected(newspaper) => {
                         state.selected_newspaper = newspaper;
-
-                        let newspaper_response = news::get_news(newspaper);
-
-                        state.rss_newspaper = match newspaper_response{
-                            Ok(v) => { v },
-                            Err(_e) => { Channel::default() }  
-                        };
+                        commands.push(state.refresh_news());
+                    }
+                    Message::NewsFetched(newspaper, result) => {
+                        state.news_fetching = false;
+                        // Another newspaper was picked while this one downloaded.
+                        if newspaper == state.selected_newspaper {
+                            state.show_news(result);
+                        } else {
+                            commands.push(state.refresh_news());
+                        }
+                    }
+                    Message::NightBrightnessChanged(night_brightness) => {
+                        state.night_brightness = night_brightness;
+                        state.apply_brightness();
+                    }
+                    Message::NightEndEdited(new_night_end_value) => {
+                        state.night_end_value = new_night_end_value;
+                    }
+                    Message::NightScheduleSelected(night_schedule) => {
+                        state.night_schedule = night_schedule;
+                    }
+                    Message::NightStartEdited(new_night_start_value) => {
+                        state.night_start_value = new_night_start_value;
+                    }
+                    Message::NoteAdded => {
+                        notes_changed = state.note_board.add(&state.note_value, state.note_color);
+                        if notes_changed {
+                            state.note_value.clear();
+                        }
+                    }
+                    Message::NoteChanged(index, text) => {
+                        state.note_board.edit(index, text);
+                        notes_changed = true;
+                    }
+                    Message::NoteColorCycled(index) => {
+                        state.note_board.cycle_color(index);
+                        notes_changed = true;
+                    }
+                    Message::NoteColorSelected(note_color) => {
+                        state.note_color = note_color;
+                    }
+                    Message::NoteDeleted(index) => {
+                        state.note_board.remove(index);
+                        notes_changed = true;
+                    }
+                    Message::NoteEdited(new_note_value) => {
+                        state.note_value = new_note_value;
+                    }
+                    Message::NotePinToggled(index) => {
+                        state.note_board.toggle_pin(index);
+                        notes_changed = true;
+                    }
+                    Message::NotesLoaded(Ok(note_board)) => {
+                        state.note_board = note_board;
+                    }
+                    Message::NotesLoaded(Err(e)) => {
+                        state.notes_read_only = !state::set_aside_unreadable(notes::FILE_NAME, &e);
+                    }
+                    Message::NotesSaved(result) => {
+                        state.notes_saving = false;
+                        if let Err(e) = result {
+                            log::warn!("Enregistrement des notes impossible : {:?}", e);
+                        }
+                    }
+                    Message::NotesPressed => {
+                        state.show_page("notes");
                     }
                     Message::ParameterPressed => {
-                        state.page_show = String::from("parameter");
+                        state.show_page("parameter");
                     }
-                    Message::SearchCityEdited(new_searchcity_value) => {
-                        state.searchcity_value = new_searchcity_value;
+                    Message::ScreensaverDelayEdited(new_screensaver_delay_value) => {
+                        state.screensaver_delay_value = new_screensaver_delay_value;
                     }
-                    Message::Tick(new_local_datetime) => {
-                        state.local_date = new_local_datetime.format_localized("%A %e %B %Y", Locale::fr_FR).to_string();
-                        state.local_time = new_local_datetime.format_localized("%T", Locale::fr_FR).to_string();
+                    Message::SensorPathEdited(new_sensor_path_value) => {
+                        state.sensor_path_value = new_sensor_path_value;
+                        state.locate_sensor();
                     }
-                    Message::WeatherApiEdited(new_weatherapi_value) => {
-                        state.weatherapi_value = new_weatherapi_value;
+                    Message::SensorSampled => {
+                        state.sample_sensor(Local::now());
                     }
-                    Message::Saved(_) => {
-                        state.saving = false;
-                        saved = true;
+                    Message::DeparturesLoaded(source, result) => {
+                        state.transit_loading = false;
+                        state.show_departures(source, result);
                     }
-                    _ => {}
-                }
-
-
-                if !saved {
-                    state.dirty = true;
-                }
-
-                if state.dirty && !state.saving {
-                    state.dirty = false;
-                    state.saving = true;
-                    Command::perform(
-                        state::SavedState {
-                            weatherapi_value: state.weatherapi_value.clone(),
-                            searchcity_value: state.searchcity_value.clone(),
-                        }.save(),
-                        Message::Saved,
-                    )
-                }else{
-                    Command::none()
-                }
-            }
+                    Message::DeparturesPressed => {
+                        commands.push(state.open_page("departures"));
+                    }
+                    Message::DeparturesRefresh => {
+                        commands.push(state.refresh_page("departures"));
+                    }
+                    Message::GtfsPathEdited(new_gtfs_path_value) => {
+                        state.gtfs_path_value = new_gtfs_path_value;
+                    }
+                    Message::GtfsRealtimeEdited(new_gtfs_realtime_value) => {
+                        state.gtfs_realtime_value = new_gtfs_realtime_value;
+                    }
+                    Message::GtfsStopsEdited(new_gtfs_stops_value) => {
+                        state.gtfs_stops_value = new_gtfs_stops_value;
+                    }
+                    Message::PodcastPressed => {
+                        commands.push(state.open_page("podcast"));
+                    }
+                    Message::PodcastEpisodePressed(feed, index) => {
+                        if let Some(episode) = state.podcasts.get(feed).and_then(|podcast| podcast.episodes.get(index)) {
+                            let track = audio::Track {
+                                title: episode.title.clone(),
+                                url: episode.url.clone(),
+                                resume_key: Some(episode.key.clone()),
+                            };
+                            state.play(track);
+                        }
+                    }
+                    Message::RadioStationPressed(index) => {
+                        if let Some(station) = podcast::parse_stations(&state.radio_stations_value).into_iter().nth(index) {
+                            state.play(audio::Track { title: station.name, url: station.url, resume_key: None });
+                        }
+                    }
+                    Message::PlayerPauseToggled => {
+                        state.control_player(audio::Player::toggle_pause);
+                    }
+                    Message::PlayerStopped => {
+                        state.control_player(|player| {
+                            player.stop();
+                            Ok(())
+                        });
+                    }
+                    Message::PlayerVolumeChanged(louder) => {
+                        state.control_player(|player| player.change_volume(louder));
+                    }
+                    Message::PlayerTick => {
+                        if let Some(player) = &mut state.player {
+                            player.tick();
+                        }
+                    }
+                    Message::PodcastsLoaded(results) => {
+                        state.podcasts_loading = false;
+                        state.show_podcasts(results);
+                    }
+                    Message::PodcastFeedsEdited(new_podcast_feeds_value) => {
+                        state.podcast_feeds_value = new_podcast_feeds_value;
+                    }
+                    Message::RadioStationsEdited(new_radio_stations_value) => {
+                        state.radio_stations_value = new_radio_stations_value;
+                    }
+                    Message::AudioPlayerApplied => {
+                        state.start_player();
+                    }
+                    Message::AudioPlayerEdited(new_audio_player_value) => {
+                        state.audio_player_value = new_audio_player_value;
+                    }
+                    Message::MusicPressed => {
+                        commands.push(state.open_page("music"));
+                    }
+                    Message::MusicRefresh => {
+                        commands.push(state.refresh_music());
+                    }
+                    Message::MusicRefreshed(result) => {
+                        state.mpd_refreshing = false;
+                        state.show_music(result);
+                    }
+                    Message::MpdControlPressed(control) => {
+                        commands.push(state.mpd_command(move |client| client.control(control)));
+                    }
+                    Message::MpdQueuePressed(position) => {
+                        commands.push(state.mpd_command(move |client| client.play_position(position)));
+                    }
+                    Message::MpdPlaylistPressed(index) => {
+                        if let Some(name) = state.mpd_snapshot.as_ref().and_then(|snapshot| snapshot.playlists.get(index)).cloned() {
+                            commands.push(state.mpd_command(move |client| client.play_playlist(&name)));
+                        }
+                    }
+                    Message::MpdCommandDone(result) => {
+                        match result {
+                            Ok(()) => {
+                                state.mpd_error = None;
+                                state.mpd_retry_at = None;
+                                commands.push(state.refresh_music());
+                            }
+                            Err(e) => state.mpd_error = Some(["MPD : ".to_string(), e].concat()),
+                        }
+                    }
+                    Message::MpdAddressEdited(new_mpd_address_value) => {
+                        state.mpd_address_value = new_mpd_address_value;
+                        state.mpd_snapshot = None;
+                        state.mpd_failures = 0;
+                        state.mpd_retry_at = None;
+                    }
+                    Message::SystemPressed => {
+                        commands.push(state.open_page("system"));
+                    }
+                    Message::SystemRefresh => {
+                        commands.push(state.refresh_page("system"));
+                    }
+                    Message::SearchCityEdited(new_searchcity_value) => {
+                        state.searchcity_value = new_searchcity_value;
+                    }
+                    Message::TaskAdded => {
+                        let due = todo::parse_due_date(&state.task_due_value);
+                        if due.is_none() && !state.task_due_value.trim().is_empty() {
+                            state.task_due_error = Some("Échéance invalide, utilisez JJ/MM/AAAA".to_string());
+                        } else if state.task_list.add(&state.task_value, due, state.task_priority) {
+                            state.task_value.clear();
+                            state.task_due_value.clear();
+                            state.task_due_error = None;
+                            tasks_changed = true;
+                        }
+                    }
+                    Message::TaskDeleted(index) => {
+                        state.task_list.remove(index);
+                        tasks_changed = true;
+                    }
+                    Message::TaskDueEdited(new_task_due_value) => {
+                        state.task_due_value = new_task_due_value;
+                        state.task_due_error = None;
+                    }
+                    Message::TaskEdited(new_task_value) => {
+                        state.task_value = new_task_value;
+                    }
+                    Message::TaskMovedDown(index) => {
+                        state.task_list.move_down(index);
+                        tasks_changed = true;
+                    }
+                    Message::TaskMovedUp(index) => {
+                        state.task_list.move_up(index);
+                        tasks_changed = true;
+                    }
+                    Message::TaskPrioritySelected(priority) => {
+                        state.task_priority = priority;
+                    }
+                    Message::TaskToggled(index, completed) => {
+                        state.task_list.toggle(index, completed);
+                        tasks_changed = true;
+                    }
+                    Message::TasksLoaded(Ok(task_list)) => {
+                        state.task_list = task_list;
+                    }
+                    Message::TasksLoaded(Err(e)) => {
+                        state.tasks_read_only = !state::set_aside_unreadable(todo::FILE_NAME, &e);
+                    }
+                    Message::TasksSaved(result) => {
+                        state.tasks_saving = false;
+                        if let Err(e) = result {
+                            log::warn!("Enregistrement des tâches impossible : {:?}", e);
+                        }
+                    }
+                    Message::TodoPressed => {
+                        state.show_page("todo");
+                    }
+                    Message::ThemeSelected(theme_choice) => {
+                        state.theme_choice = theme_choice;
+                        state.apply_theme();
+                    }
+                    Message::Tick(new_local_datetime) => {
+                        state.local_date = new_local_datetime.format_localized("%A %e %B %Y", Locale::fr_FR).to_string();
+                        state.local_time = new_local_datetime.format_localized("%T", Locale::fr_FR).to_string();
+                        state.analog_clock.update(new_local_datetime);
+                        state.update_night_mode(new_local_datetime);
+                        let last_activity = *state.last_activity.get_or_insert(new_local_datetime);
+                        if let Some(delay) = screensaver::parse_delay(&state.screensaver_delay_value) {
+                            if state.page_show != "screensaver" && screensaver::is_idle(last_activity, new_local_datetime, delay) {
+                                commands.push(state.start_screensaver(new_local_datetime));
+                            }
+                        }
+                        for command in remote::drain(&state.remote_queue) {
+                            commands.push(state.handle_remote_command(command, new_local_datetime));
+                        }
+                        state.notifications.retain(|(_, expires)| *expires > new_local_datetime);
+                    }
+                    Message::UserActivity => {
+                        state.last_activity = Some(Local::now());
+                        if state.page_show == "screensaver" {
+                            state.stop_screensaver();
+                        }
+                    }
+                    Message::WindowResized(width, height) => {
+                        let was_compact = state.layout.is_compact();
+                        state.layout = layout::Layout::new(width, height, state.layout.font_scale);
+                        if state.layout.is_compact() != was_compact {
+                            state.menu_collapsed = state.layout.is_compact();
+                        }
+                    }
+                    Message::WeatherApiEdited(new_weatherapi_value) => {
+                        state.weatherapi_value = new_weatherapi_value;
+                    }
+                    Message::WeatherFetched(result) => {
+                        state.weather_fetching = false;
+                        state.apply_weather(result);
+                    }
+                    Message::WeatherRefresh if state.weather_provider.is_configured(&state.weatherapi_value) => {
+                        commands.push(state.refresh_weather());
+                    }
+                    Message::WeatherProviderSelected(weather_provider) => {
+                        state.weather_provider = weather_provider;
+                    }
+                    Message::WeatherRulesEdited(new_weather_rules_value) => {
+                        state.weather_rules_value = new_weather_rules_value;
+                    }
+                    Message::Saved(result) => {
+                        state.saving = false;
+                        if let Err(e) = result {
+                            log::warn!("Enregistrement des paramètres impossible : {:?}", e);
+                            // Forgetting what was written makes the next update save the settings again.
+                            state.settings_written = serde_json::Value::Null;
+                        }
+                    }
+                    _ => {}
+                }
+
+                if state.publish_settings() {
+                    state.dirty = true;
+                }
+
+                // Like the settings, one save at a time: changes made meanwhile go out once it lands.
+                state.tasks_dirty |= tasks_changed;
+                let save_tasks = if state.tasks_dirty && !state.tasks_saving && !state.tasks_read_only {
+                    state.tasks_dirty = false;
+                    state.tasks_saving = true;
+                    Command::perform(state.task_list.clone().save(), Message::TasksSaved)
+                } else {
+                    Command::none()
+                };
+
+                state.notes_dirty |= notes_changed;
+                let save_notes = if state.notes_dirty && !state.notes_saving && !state.notes_read_only {
+                    state.notes_dirty = false;
+                    state.notes_saving = true;
+                    Command::perform(state.note_board.clone().save(), Message::NotesSaved)
+                } else {
+                    Command::none()
+                };
+
+                let save_settings = if state.dirty && !state.saving && !state.settings_read_only {
+                    state.dirty = false;
+                    state.saving = true;
+                    Command::perform(
+                        state::save_settings(state.settings_written.clone()),
+                        Message::Saved,
+                    )
+                }else{
+                    Command::none()
+                };
+
+                commands.extend(vec![save_settings, save_tasks, save_notes]);
+                Command::batch(commands)
+            }
         }
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        time::every(std::time::Duration::from_millis(500))
-            .map(|_| Message::Tick(chrono::Local::now()))
+        let mut subscriptions = vec![
+            time::every(std::time::Duration::from_millis(500))
+                .map(|_| Message::Tick(chrono::Local::now())),
+            time::every(std::time::Duration::from_secs(30))
+                .map(|_| Message::CalendarRefresh),
+            time::every(std::time::Duration::from_secs(300))
+                .map(|_| Message::CalDavSync),
+            time::every(std::time::Duration::from_secs(weather::REFRESH_SECONDS))
+                .map(|_| Message::WeatherRefresh),
+            iced_native::subscription::events_with(|event, _status| match event {
+                iced_native::Event::Window(iced_native::window::Event::Resized { width, height }) => {
+                    Some(Message::WindowResized(width, height))
+                }
+                iced_native::Event::Mouse(iced_native::mouse::Event::ButtonPressed(_))
+                | iced_native::Event::Mouse(iced_native::mouse::Event::WheelScrolled { .. })
+                | iced_native::Event::Touch(iced_native::touch::Event::FingerPressed { .. })
+                | iced_native::Event::Keyboard(iced_native::keyboard::Event::KeyPressed { .. }) => {
+                    Some(Message::UserActivity)
+                }
+                _ => None,
+            }),
+        ];
+
+        if let Assistant::Loaded(state) = self {
+            if state.page_show == "departures" {
+                subscriptions.push(
+                    time::every(std::time::Duration::from_secs(transit::REFRESH_SECONDS))
+                        .map(|_| Message::DeparturesRefresh),
+                );
+            }
+            if matches!(&state.player, Some(player) if player.track().is_some() && !player.is_paused()) {
+                subscriptions.push(
+                    time::every(std::time::Duration::from_secs(audio::TICK_SECONDS))
+                        .map(|_| Message::PlayerTick),
+                );
+            }
+            if state.page_show == "music" {
+                subscriptions.push(
+                    time::every(std::time::Duration::from_secs(mpd::REFRESH_SECONDS))
+                        .map(|_| Message::MusicRefresh),
+                );
+            }
+            if state.page_show == "system" {
+                subscriptions.push(
+                    time::every(std::time::Duration::from_secs(system::REFRESH_SECONDS))
+                        .map(|_| Message::SystemRefresh),
+                );
+            }
+            if state.sensor.is_some() {
+                subscriptions.push(
+                    time::every(std::time::Duration::from_secs(sensors::SAMPLE_SECONDS))
+                        .map(|_| Message::SensorSampled),
+                );
+            }
+            if state.carousel_enabled && !state.carousel_pages.is_empty() {
+                // The timer is rebuilt when the next page has another dwell.
+                subscriptions.push(
+                    time::every(carousel::parse_dwell(&state.carousel_dwell_value, &state.page_show))
+                        .map(|_| Message::CarouselAdvance),
+                );
+            }
+        }
+
+        Subscription::batch(subscriptions)
     }
 
-    fn view(&mut self) -> Element<Message> {
+    fn view(&mut self) -> Element<'_, Message> {
 
         match self {
-            Assistant::Loading => Row::new().push(Text::new("En cours de chargement...")).into(),
+            Assistant::Loading(_) => Row::new().push(Text::new("En cours de chargement...")).into(),
             Assistant::Loaded(State {
                 page_show,
                 input_weatherapi,
                 weatherapi_value,
                 input_searchcity,
                 searchcity_value,
+                weather_provider,
+                pick_list_weather_provider,
+                input_calendar_files,
+                calendar_files_value,
+                input_calendar_days,
+                calendar_days_value,
+                calendar_watch,
+                input_caldav_url,
+                caldav_url_value,
+                input_caldav_username,
+                caldav_username_value,
+                input_caldav_password,
+                caldav_password_value,
+                input_mqtt_broker,
+                mqtt_broker_value,
+                input_mqtt_username,
+                mqtt_username_value,
+                input_mqtt_password,
+                mqtt_password_value,
+                input_mqtt_tiles,
+                mqtt_tiles_value,
+                input_mqtt_actions,
+                mqtt_actions_value,
+                input_mqtt_command_topic,
+                mqtt_command_topic_value,
+                mqtt_connect,
+                mqtt_connection,
+                mqtt_action_buttons,
+                scroll_mqtt,
+                go_to_calendar,
                 go_to_clock,
+                go_to_home,
                 go_to_meteo,
+                go_to_mqtt,
                 go_to_news,
+                go_to_notes,
                 go_to_parameter,
+                go_to_system,
+                go_to_departures,
+                go_to_todo,
+                scroll_menu,
+                input_screensaver_delay,
+                screensaver_delay_value,
+                screensaver_since,
+                carousel_enabled,
+                carousel_pages,
+                input_carousel_dwell,
+                carousel_dwell_value,
+                dashboard_widgets,
+                dashboard_columns,
+                pick_list_dashboard_columns,
+                dashboard_buttons,
+                dashboard_move_up,
+                toggle_menu,
+                menu_collapsed,
+                layout,
+                pick_list_font_scale,
                 local_date,
                 local_time,
-                weather_json,
+                notifications,
+                notification_history,
+                input_weather_rules,
+                weather_rules_value,
+                go_to_alerts,
+                scroll_alerts,
+                input_gtfs_path,
+                gtfs_path_value,
+                input_gtfs_stops,
+                gtfs_stops_value,
+                input_gtfs_realtime,
+                gtfs_realtime_value,
+                transit_boards,
+                transit_error,
+                scroll_departures,
+                go_to_podcast,
+                input_podcast_feeds,
+                podcast_feeds_value,
+                input_radio_stations,
+                radio_stations_value,
+                input_audio_player,
+                audio_player_value,
+                apply_audio_player,
+                player,
+                player_error,
+                podcasts,
+                podcast_errors,
+                station_buttons,
+                episode_buttons,
+                player_controls,
+                scroll_podcast,
+                go_to_music,
+                input_mpd_address,
+                mpd_address_value,
+                mpd_snapshot,
+                mpd_error,
+                mpd_controls,
+                mpd_queue_buttons,
+                mpd_playlist_buttons,
+                scroll_music,
+                analog_clock,
+                clock_face,
+                pick_list_clock_face,
+                calendar_events,
+                caldav_error,
+                calendar_errors,
+                next_event,
+                scroll_calendar,
+                scroll_dashboard,
+                scroll_parameter,
+                task_list,
+                task_controls,
+                input_task,
+                task_value,
+                input_task_due,
+                task_due_value,
+                task_due_error,
+                pick_list_task_priority,
+                task_priority,
+                add_task,
+                scroll_todo,
+                note_board,
+                note_controls,
+                input_note,
+                note_value,
+                pick_list_note_color,
+                note_color,
+                add_note,
+                scroll_notes,
+                theme_choice,
+                theme,
+                pick_list_theme,
+                input_latitude,
+                latitude_value,
+                input_longitude,
+                longitude_value,
+                night_schedule,
+                pick_list_night_schedule,
+                input_night_start,
+                night_start_value,
+                input_night_end,
+                night_end_value,
+                day_brightness,
+                slider_day_brightness,
+                night_brightness,
+                slider_night_brightness,
+                backlight,
+                input_sensor_path,
+                sensor_path_value,
+                sensor,
+                indoor_history,
+                indoor_sparkline,
+                system_status,
+                weather_report,
+                weather_error,
+                air_quality,
+                uv_index,
+                go_to_history,
+                history_range,
+                pick_list_history_range,
+                weather_chart,
                 rss_newspaper,
                 pick_list,
                 selected_newspaper,
                 scroll,
                 ..
             }) => {
+                let theme = *theme;
+                let layout = *layout;
+                let menu_visible = !*menu_collapsed;
+                let menu_width = layout.menu_width();
+                let menu_button_height = layout.menu_button_height();
+                let banners = notification_banners(notifications, theme, layout);
+
+                if page_show.as_str() == "screensaver" {
+                    let (offset_x, offset_y) = screensaver::drift_offset(
+                        Local::now() - screensaver_since.unwrap_or_else(Local::now),
+                        (layout.width as f32 * 0.4) as u16,
+                        (layout.height as f32 * 0.4) as u16,
+                    );
+                    let weather_summary = weather_report.as_ref().map(weather::summary).unwrap_or_default();
+
+                    return Container::new(Column::new()
+                    .push(banners)
+                    .push(Space::with_height(Length::Units(offset_y)))
+                    .push(Row::new()
+                    .push(Space::with_width(Length::Units(offset_x)))
+                    .push(Column::new()
+                    .spacing(layout.text_size(10))
+                    .align_items(Align::Center)
+                    .push(Text::new(&*local_time).size(layout.text_size(120)))
+                    .push(Text::new(&*local_date).size(layout.text_size(25)))
+                    .push(Text::new(weather_summary).size(layout.text_size(30))))))
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .style(style::Container(theme))
+                    .into();
+                }
+
+                let home_button =
+                    Button::new(go_to_home, Text::new("Accueil").size(layout.text_size(20)).horizontal_alignment(HorizontalAlignment::Center).vertical_alignment(VerticalAlignment::Center))
+                        .width(Length::Units(menu_width))
+                        .min_height(menu_button_height)
+                        .style(style::Button(style::ButtonKind::Home, theme))
+                        .on_press(Message::HomePressed);
+
                 let news_button =
-                Button::new(go_to_news, Text::new("Actualités").horizontal_alignment(HorizontalAlignment::Center).vertical_alignment(VerticalAlignment::Center))
-                    .min_width(120)
-                    .min_height(150)
-                    .style(style::Button::News)
+                Button::new(go_to_news, Text::new("Actualités").size(layout.text_size(20)).horizontal_alignment(HorizontalAlignment::Center).vertical_alignment(VerticalAlignment::Center))
+                    .width(Length::Units(menu_width))
+                    .min_height(menu_button_height)
+                    .style(style::Button(style::ButtonKind::News, theme))
                     .on_press(Message::NewsPressed);
             
+                let calendar_button =
+                    Button::new(go_to_calendar, Text::new("Agenda").size(layout.text_size(20)).horizontal_alignment(HorizontalAlignment::Center).vertical_alignment(VerticalAlignment::Center))
+                        .width(Length::Units(menu_width))
+                        .min_height(menu_button_height)
+                        .style(style::Button(style::ButtonKind::Calendar, theme))
+                        .on_press(Message::CalendarPressed);
+
                 let clock_button =
-                    Button::new(go_to_clock, Text::new("Horloge").horizontal_alignment(HorizontalAlignment::Center).vertical_alignment(VerticalAlignment::Center))
-                        .min_width(120)
-                        .min_height(150)
-                        .style(style::Button::Clock)
+                    Button::new(go_to_clock, Text::new("Horloge").size(layout.text_size(20)).horizontal_alignment(HorizontalAlignment::Center).vertical_alignment(VerticalAlignment::Center))
+                        .width(Length::Units(menu_width))
+                        .min_height(menu_button_height)
+                        .style(style::Button(style::ButtonKind::Clock, theme))
                         .on_press(Message::ClockPressed);
         
                 let meteo_button =
-                    Button::new(go_to_meteo, Text::new("Meteo").horizontal_alignment(HorizontalAlignment::Center).vertical_alignment(VerticalAlignment::Center))
-                        .min_width(120)
-                        .min_height(150)
-                        .style(style::Button::Meteo)
+                    Button::new(go_to_meteo, Text::new("Meteo").size(layout.text_size(20)).horizontal_alignment(HorizontalAlignment::Center).vertical_alignment(VerticalAlignment::Center))
+                        .width(Length::Units(menu_width))
+                        .min_height(menu_button_height)
+                        .style(style::Button(style::ButtonKind::Meteo, theme))
                         .on_press(Message::MeteoPressed);
         
+                let notes_button =
+                    Button::new(go_to_notes, Text::new("Notes").size(layout.text_size(20)).horizontal_alignment(HorizontalAlignment::Center).vertical_alignment(VerticalAlignment::Center))
+                        .width(Length::Units(menu_width))
+                        .min_height(menu_button_height)
+                        .style(style::Button(style::ButtonKind::Notes, theme))
+                        .on_press(Message::NotesPressed);
+
+                let mqtt_button =
+                    Button::new(go_to_mqtt, Text::new("Maison").size(layout.text_size(20)).horizontal_alignment(HorizontalAlignment::Center).vertical_alignment(VerticalAlignment::Center))
+                        .width(Length::Units(menu_width))
+                        .min_height(menu_button_height)
+                        .style(style::Button(style::ButtonKind::Mqtt, theme))
+                        .on_press(Message::MqttPressed);
+
+                let departures_button =
+                    Button::new(go_to_departures, Text::new("Départs").size(layout.text_size(20)).horizontal_alignment(HorizontalAlignment::Center).vertical_alignment(VerticalAlignment::Center))
+                        .width(Length::Units(menu_width))
+                        .min_height(menu_button_height)
+                        .style(style::Button(style::ButtonKind::Calendar, theme))
+                        .on_press(Message::DeparturesPressed);
+
+                let podcast_button =
+                    Button::new(go_to_podcast, Text::new("Écoute").size(layout.text_size(20)).horizontal_alignment(HorizontalAlignment::Center).vertical_alignment(VerticalAlignment::Center))
+                        .width(Length::Units(menu_width))
+                        .min_height(menu_button_height)
+                        .style(style::Button(style::ButtonKind::News, theme))
+                        .on_press(Message::PodcastPressed);
+
+                let music_button =
+                    Button::new(go_to_music, Text::new("Musique").size(layout.text_size(20)).horizontal_alignment(HorizontalAlignment::Center).vertical_alignment(VerticalAlignment::Center))
+                        .width(Length::Units(menu_width))
+                        .min_height(menu_button_height)
+                        .style(style::Button(style::ButtonKind::News, theme))
+                        .on_press(Message::MusicPressed);
+
+                let system_button =
+                    Button::new(go_to_system, Text::new("Système").size(layout.text_size(20)).horizontal_alignment(HorizontalAlignment::Center).vertical_alignment(VerticalAlignment::Center))
+                        .width(Length::Units(menu_width))
+                        .min_height(menu_button_height)
+                        .style(style::Button(style::ButtonKind::Parameters, theme))
+                        .on_press(Message::SystemPressed);
+
                 let parameter_button =
-                    Button::new(go_to_parameter, Text::new("Paramètres").horizontal_alignment(HorizontalAlignment::Center).vertical_alignment(VerticalAlignment::Center))
-                        .min_width(120)
-                        .min_height(150)
-                        .style(style::Button::Parameters)
+                    Button::new(go_to_parameter, Text::new("Paramètres").size(layout.text_size(20)).horizontal_alignment(HorizontalAlignment::Center).vertical_alignment(VerticalAlignment::Center))
+                        .width(Length::Units(menu_width))
+                        .min_height(menu_button_height)
+                        .style(style::Button(style::ButtonKind::Parameters, theme))
                         .on_press(Message::ParameterPressed);
         
+                let today = Local::now().naive_local().date();
+                let overdue_count = task_list.overdue_count(today);
+                let todo_label = if overdue_count > 0 {
+                    Row::new()
+                    .spacing(5)
+                    .align_items(Align::Center)
+                    .push(Text::new("Tâches").size(layout.text_size(20)))
+                    .push(Container::new(Text::new(overdue_count.to_string()).size(layout.text_size(16)))
+                    .padding(4)
+                    .style(style::Badge(theme)))
+                } else {
+                    Row::new().push(Text::new("Tâches").size(layout.text_size(20)))
+                };
+
+                let todo_button =
+                    Button::new(go_to_todo, todo_label)
+                        .width(Length::Units(menu_width))
+                        .min_height(menu_button_height)
+                        .style(style::Button(style::ButtonKind::Todo, theme))
+                        .on_press(Message::TodoPressed);
+
                 let content: Element<_> = match page_show.as_str() {
+                    "home" => {
+                        let columns = match layout.orientation() {
+                            layout::Orientation::Landscape => dashboard::clamp_columns(*dashboard_columns) as usize,
+                            layout::Orientation::Portrait => 1,
+                        };
+                        let weather_summary = weather_report
+                            .as_ref()
+                            .map(weather::summary)
+                            .unwrap_or_else(|| String::from("Météo indisponible"));
+                        let headlines: Vec<String> = rss_newspaper.items()
+                            .iter()
+                            .take(dashboard::HEADLINES)
+                            .map(|item| item.title().unwrap_or("...").to_string())
+                            .collect();
+                        let next_event_text = match next_event {
+                            Some(occurrence) => [calendar::format_day(occurrence.start.date()), calendar::format_occurrence(occurrence)].join(" "),
+                            None => String::from("Aucun événement à venir"),
+                        };
+
+                        dashboard_buttons.resize_with(dashboard_widgets.len(), button::State::default);
+
+                        let mut cards = dashboard_widgets
+                                .iter()
+                                .zip(dashboard_buttons.iter_mut())
+                                .map(|(widget, button_state)| {
+                                    let title = Text::new(widget.to_string()).size(layout.text_size(18));
+                                    let (content, kind) = match widget {
+                                        dashboard::Widget::Clock => (
+                                            Column::new()
+                                            .push(Text::new(&*local_time).size(layout.text_size(60)))
+                                            .push(Text::new(&*local_date).size(layout.text_size(18))),
+                                            style::ButtonKind::Clock,
+                                        ),
+                                        dashboard::Widget::Weather => (
+                                            Column::new()
+                                            .push(title)
+                                            .push(Text::new(&weather_summary).size(layout.text_size(30))),
+                                            style::ButtonKind::Meteo,
+                                        ),
+                                        dashboard::Widget::Headlines => (
+                                            headlines.iter().fold(Column::new().push(title), |column, headline| {
+                                                column.push(Text::new(headline).size(layout.text_size(18)))
+                                            }),
+                                            style::ButtonKind::News,
+                                        ),
+                                        dashboard::Widget::NextEvent => (
+                                            Column::new()
+                                            .push(title)
+                                            .push(Text::new(&next_event_text).size(layout.text_size(22))),
+                                            style::ButtonKind::Calendar,
+                                        ),
+                                    };
+                                    Button::new(button_state, content.spacing(5).width(Length::Fill))
+                                    .width(Length::Fill)
+                                    .padding(15)
+                                    .style(style::Button(kind, theme))
+                                    .on_press(Message::DashboardWidgetPressed(*widget))
+                                })
+                                .peekable();
+
+                        let mut grid = Column::new().spacing(15).padding(20);
+                        while cards.peek().is_some() {
+                            let row = cards.by_ref().take(columns).fold(Row::new().spacing(15), |row, card| row.push(card));
+                            grid = grid.push(row);
+                        }
+
+                        Scrollable::new(scroll_dashboard)
+                        .push(grid)
+                        .style(style::Scrollable(theme))
+                        .width(Length::Fill)
+                        .height(Length::Fill)
+                        .into()
+                    },
                     "clock" => {
+                        let clock_display: Element<_> = match clock_face {
+                            clock::ClockFace::Analog => {
+                                Canvas::new(analog_clock)
+                                .width(Length::Units(layout.clock_size(menu_visible)))
+                                .height(Length::Units(layout.clock_size(menu_visible)))
+                                .into()
+                            },
+                            clock::ClockFace::Digital => {
+                                Text::new(
+                                    &*local_time
+                                ).size(layout.text_size(150)).into()
+                            }
+                        };
+
+                        let ephemeris = astronomy::parse_coordinates(latitude_value, longitude_value)
+                            .map(|(latitude, longitude)| astronomy::ephemeris(Local::now(), latitude, longitude))
+                            .unwrap_or_default()
+                            .into_iter()
+                            .fold(Column::new().spacing(5).align_items(Align::Center), |column, line| {
+                                column.push(Text::new(line).size(layout.text_size(20)))
+                            });
+
                         Column::new()
-                        .width(Length::Units(900))
-                        .height(Length::Units(600))
+                        .width(Length::Fill)
+                        .height(Length::Fill)
+                        .padding(20)
+                        .spacing(layout.text_size(30))
                         .align_items(Align::Center)
                         .push(Row::new()
                         .align_items(Align::Center)
-                        .height(Length::Units(300))
-                        .push(Text::new(
-                            &*local_time
-                        ).size(150)))
+                        .push(clock_display))
                         .push(Row::new()
                         .align_items(Align::Center)
-                        .height(Length::Units(300))
                         .push(Text::new(
                             &*local_date
-                        )))
+                        ).size(layout.text_size(20))))
+                        .push(Row::new()
+                        .align_items(Align::Center)
+                        .push(Text::new(
+                            match next_event {
+                                Some(occurrence) => ["Prochain événement :".to_string(), calendar::format_day(occurrence.start.date()), calendar::format_occurrence(occurrence)].join(" "),
+                                None => String::new(),
+                            }
+                        ).size(layout.text_size(25))))
+                        .push(Row::new()
+                        .align_items(Align::Center)
+                        .push(Text::new(
+                            match note_board.pinned() {
+                                Some(note) => note.text.clone(),
+                                None => String::new(),
+                            }
+                        ).size(layout.text_size(25))))
+                        .push(ephemeris)
                         .into()
                     },
-                    "meteo" => {
-                        let weather_json_parse = json::parse(&weather_json).unwrap();
-                        if weather_json_parse["cod"]==401 {
-                            Column::new()
-                            .width(Length::Units(900))
-                            .height(Length::Units(600))
-                            .spacing(20)
-                            .push(Text::new("Clé API invalide ou ville manquante. Veuillez vérifier vos paramètres"))
-                            .into()
+                    "calendar" => {
+                        let days = calendar::upcoming_days(calendar_events, today, calendar::parse_days(calendar_days_value));
+
+                        let agenda = days
+                                .iter()
+                                .fold(Column::new().spacing(10).padding(20), |column, (day, occurrences)| {
+                                    let column = column.push(Text::new(calendar::format_day(*day)).size(layout.text_size(35)));
+                                    if occurrences.is_empty() {
+                                        column.push(Text::new("Aucun événement").size(layout.text_size(20)))
+                                    } else {
+                                        occurrences.iter().fold(column, |column, occurrence| {
+                                            column.push(Text::new(calendar::format_occurrence(occurrence)).size(layout.text_size(20)))
+                                        })
+                                    }
+                                });
+
+                        let mut page = Column::new()
+                        .padding(20)
+                        .push(Text::new("Agenda").size(layout.text_size(50)));
+                        if let Some(error) = caldav_error {
+                            page = page.push(Text::new(error.as_str()).size(layout.text_size(16)));
                         }
-                        else if !weather_json_parse["error"].is_string() {
-                            let temperature = weather::calculate_temperature(weather_json_parse["main"]["temp"].to_owned().as_f32().unwrap()).to_string();
-                            let temperature_min = weather::calculate_temperature(weather_json_parse["main"]["temp_min"].to_owned().as_f32().unwrap()).to_string();
-                            let temperature_max = weather::calculate_temperature(weather_json_parse["main"]["temp_max"].to_owned().as_f32().unwrap()).to_string();
+                        let page = calendar_errors.iter().fold(page, |page, error| {
+                            page.push(Text::new(error.as_str()).size(layout.text_size(16)))
+                        });
+                        page
+                        .push(Container::new(Scrollable::new(scroll_calendar).push(Container::new(agenda)).style(style::Scrollable(theme)).padding(10)
+                        .width(Length::Fill)
+                        .height(Length::Fill)
+                    )).into()
+                    },
+                    "meteo" => {
+                        let outdoor: Element<_> = if let Some(report) = weather_report {
+                            let temperatures = [("Temp. :", Some(report.temperature)), ("Min. :", report.temperature_min), ("Max. :", report.temperature_max)]
+                            .iter()
+                            .filter_map(|(label, value)| value.map(|value| (label, value)))
+                            .fold(Column::new().width(Length::Fill).spacing(layout.text_size(100)), |column, (label, value)| {
+                                column.push(Text::new([label.to_string(), weather::format_temperature(value), "°C".to_string()].join(" ")).size(layout.text_size(35)))
+                            });
+                            let place = Column::new()
+                            .width(Length::Fill)
+                            .spacing(layout.text_size(100))
+                            .push(Text::new(&report.place).size(layout.text_size(35)))
+                            .push(Text::new(&report.description).size(layout.text_size(35)));
+                            let weather: Element<_> = match layout.orientation() {
+                                layout::Orientation::Landscape => Row::new().push(temperatures).push(place).into(),
+                                layout::Orientation::Portrait => Column::new().spacing(layout.text_size(50)).push(place).push(temperatures).into(),
+                            };
                             Column::new()
-                            .width(Length::Units(900))
-                            .height(Length::Units(600))
+                            .width(Length::Fill)
+                            .height(Length::Fill)
                             .padding(20)
-                            .push(Row::new()
-                            .push(Column::new()
-                            .width(Length::Units(450))
-                            .spacing(100)
-                            .push(Text::new(["Temp. :".to_string(), temperature, "°C".to_string()].join(" ")).size(35))
-                            .push(Text::new(["Min. :".to_string(), temperature_min, "°C".to_string()].join(" ")).size(35))
-                            .push(Text::new(["Max. :".to_string(), temperature_max, "°C".to_string()].join(" ")).size(35))
-                            )
-                            .push(Column::new()
-                            .width(Length::Units(450))
-                            .spacing(100)
-                            .push(Text::new(&weather_json_parse["name"].to_owned().to_string()).size(35))
-                            .push(Text::new(&weather_json_parse["weather"][0]["main"].to_owned().to_string()).size(35))
-                        )
-                            )
+                            .push(weather)
                             .into()
                         }else{
                             Column::new()
-                            .width(Length::Units(900))
-                            .height(Length::Units(600))
+                            .width(Length::Fill)
+                            .height(Length::Fill)
                             .spacing(20)
-                            .push(Text::new("Meteo a besoin d'une connexion internet et d'une clé API pour fonctionner"))
+                            .push(Text::new(weather_error.as_deref().unwrap_or("Meteo a besoin d'une connexion internet pour fonctionner")))
+                            .into()
+                        };
+
+                        let history_button = Button::new(go_to_history, Text::new("Historique"))
+                            .padding(10)
+                            .style(style::Button(style::ButtonKind::Meteo, theme))
+                            .on_press(Message::HistoryPressed);
+
+                        let alerts_button = Button::new(go_to_alerts, Text::new("Alertes"))
+                            .padding(10)
+                            .style(style::Button(style::ButtonKind::Meteo, theme))
+                            .on_press(Message::AlertsPressed);
+
+                        let weather: Element<_> = match sensor {
+                            None => outdoor,
+                            Some(sensor) => {
+                                let title = ["Intérieur (", &sensor.name(), ")"].concat();
+                                let mut indoor = indoor_history
+                                    .latest()
+                                    .map(|reading| reading.lines())
+                                    .unwrap_or_default()
+                                    .into_iter()
+                                    .fold(Column::new().width(Length::Fill).padding(20).spacing(layout.text_size(30)).push(Text::new(title).size(layout.text_size(25))), |column, line| {
+                                        column.push(Text::new(line).size(layout.text_size(35)))
+                                    });
+                                if !indoor_sparkline.is_empty() {
+                                    indoor = indoor.push(Canvas::new(indoor_sparkline)
+                                    .width(Length::Fill)
+                                    .height(Length::Units(layout.text_size(80))));
+                                }
+                                match layout.orientation() {
+                                    layout::Orientation::Landscape => Row::new().push(outdoor).push(indoor).into(),
+                                    layout::Orientation::Portrait => Column::new().push(outdoor).push(indoor).into(),
+                                }
+                            }
+                        };
+
+                        let air_panels = air_quality
+                            .map(|air_quality| {
+                                let severity = air_quality.severity();
+                                (["Qualité de l'air : ", severity.air_quality_label()].concat(), air_quality.components(), severity.air_quality_advice(), severity)
+                            })
+                            .into_iter()
+                            .chain(uv_index.map(|uv_index| {
+                                let severity = air::Severity::from_uv_index(uv_index);
+                                (format!("Indice UV : {:.0} ({})", uv_index, severity.uv_label()), String::new(), severity.uv_advice(), severity)
+                            }))
+                            .fold(Row::new().padding(20).spacing(10), |row, (title, details, advice, severity)| {
+                                let mut panel = Column::new().spacing(5).push(Text::new(title).size(layout.text_size(25)));
+                                if !details.is_empty() {
+                                    panel = panel.push(Text::new(details).size(layout.text_size(18)));
+                                }
+                                row.push(Container::new(panel.push(Text::new(advice).size(layout.text_size(18))))
+                                .width(Length::Fill)
+                                .padding(10)
+                                .style(style::Severity(severity)))
+                            });
+
+                        Column::new()
+                        .push(weather)
+                        .push(air_panels)
+                        .push(Row::new()
+                        .padding(20)
+                        .spacing(10)
+                        .push(history_button)
+                        .push(alerts_button))
+                        .into()
+                    },
+                    "alerts" => {
+                        let entries = notification_history
+                            .iter()
+                            .rev()
+                            .fold(Column::new().spacing(10), |column, (time, notification)| {
+                                column.push(Container::new(Column::new()
+                                .push(Text::new([time.format("%d/%m %H:%M").to_string(), notification.title.clone()].join(" ")).size(layout.text_size(22)))
+                                .push(Text::new(&notification.body).size(layout.text_size(18))))
+                                .width(Length::Fill)
+                                .padding(10)
+                                .style(style::Banner(notification.level, theme)))
+                            });
+
+                        let content: Element<_> = if notification_history.is_empty() {
+                            Text::new("Aucune alerte pour le moment").size(layout.text_size(25)).into()
+                        } else {
+                            Scrollable::new(scroll_alerts)
+                            .push(entries)
+                            .style(style::Scrollable(theme))
+                            .width(Length::Fill)
+                            .height(Length::Fill)
                             .into()
+                        };
+
+                        Column::new()
+                        .padding(20)
+                        .spacing(10)
+                        .push(Text::new("Alertes").size(layout.text_size(50)))
+                        .push(content)
+                        .into()
+                    },
+                    "departures" => {
+                        let content: Element<_> = match transit_error {
+                            Some(error) => Text::new(error.as_str()).size(layout.text_size(25)).into(),
+                            None => {
+                                let boards = transit_boards.iter().fold(Column::new().spacing(20), |column, board| {
+                                    let title = Text::new(&board.stop).size(layout.text_size(35));
+                                    let departures = if board.departures.is_empty() {
+                                        Column::new().push(Text::new("Aucun départ dans les deux prochaines heures").size(layout.text_size(20)))
+                                    } else {
+                                        board.departures.iter().fold(Column::new().spacing(5), |departures, departure| {
+                                            departures.push(Row::new()
+                                            .spacing(20)
+                                            .push(Text::new(&departure.route).size(layout.text_size(25)).width(Length::FillPortion(1)))
+                                            .push(Text::new(&departure.headsign).size(layout.text_size(25)).width(Length::FillPortion(4)))
+                                            .push(Text::new(departure.wait()).size(layout.text_size(25)).width(Length::FillPortion(2)))
+                                            .push(Text::new(if departure.realtime { "temps réel" } else { "théorique" }).size(layout.text_size(16)).width(Length::FillPortion(1))))
+                                        })
+                                    };
+                                    column.push(Column::new().spacing(10).push(title).push(departures))
+                                });
+                                Scrollable::new(scroll_departures)
+                                .push(boards)
+                                .style(style::Scrollable(theme))
+                                .width(Length::Fill)
+                                .height(Length::Fill)
+                                .into()
+                            }
+                        };
+
+                        Column::new()
+                        .padding(20)
+                        .spacing(10)
+                        .push(Text::new("Départs").size(layout.text_size(50)))
+                        .push(content)
+                        .into()
+                    },
+                    "music" => {
+                        let content: Element<_> = match mpd_snapshot.as_ref() {
+                            Some(snapshot) => {
+                                let status = &snapshot.status;
+                                let (title, album) = match &snapshot.song {
+                                    Some(song) => (song.label(), song.album.clone().unwrap_or_default()),
+                                    None => (String::from("Aucun morceau"), String::new()),
+                                };
+                                let elapsed = status.elapsed.unwrap_or(0.0);
+                                let mut now_playing = Column::new()
+                                .spacing(10)
+                                .push(Text::new(title).size(layout.text_size(35)))
+                                .push(Text::new(album).size(layout.text_size(20)));
+                                if let Some(duration) = status.duration {
+                                    now_playing = now_playing
+                                    .push(ProgressBar::new(0.0..=duration as f32, elapsed as f32).height(Length::Units(10)).style(style::ProgressBar(theme)))
+                                    .push(Text::new([mpd::format_time(elapsed), " / ".to_string(), mpd::format_time(duration)].concat()).size(layout.text_size(16)));
+                                }
+                                now_playing = now_playing.push(Text::new(status.state.to_string()).size(layout.text_size(16)));
+
+                                let volume = status.volume.map(|volume| format!("{} %", volume)).unwrap_or_else(|| String::from("—"));
+                                let toggle_label = if status.state == mpd::PlayState::Play { "Pause" } else { "Lecture" };
+                                let controls = Row::new()
+                                .spacing(10)
+                                .align_items(Align::Center)
+                                .push(Button::new(&mut mpd_controls.previous, Text::new("Précédent").size(layout.text_size(20))).padding(15).style(style::Button(style::ButtonKind::News, theme)).on_press(Message::MpdControlPressed(mpd::Control::Previous)))
+                                .push(Button::new(&mut mpd_controls.toggle, Text::new(toggle_label).size(layout.text_size(20))).padding(15).style(style::Button(style::ButtonKind::News, theme)).on_press(Message::MpdControlPressed(mpd::Control::TogglePause)))
+                                .push(Button::new(&mut mpd_controls.next, Text::new("Suivant").size(layout.text_size(20))).padding(15).style(style::Button(style::ButtonKind::News, theme)).on_press(Message::MpdControlPressed(mpd::Control::Next)))
+                                .push(Button::new(&mut mpd_controls.volume_down, Text::new("Vol −").size(layout.text_size(20))).padding(15).style(style::Button(style::ButtonKind::News, theme)).on_press(Message::MpdControlPressed(mpd::Control::VolumeDown)))
+                                .push(Text::new(volume).size(layout.text_size(20)))
+                                .push(Button::new(&mut mpd_controls.volume_up, Text::new("Vol +").size(layout.text_size(20))).padding(15).style(style::Button(style::ButtonKind::News, theme)).on_press(Message::MpdControlPressed(mpd::Control::VolumeUp)));
+
+                                mpd_queue_buttons.resize_with(snapshot.queue.len(), button::State::default);
+                                let queue = snapshot.queue
+                                    .iter()
+                                    .zip(mpd_queue_buttons.iter_mut())
+                                    .fold(Column::new().spacing(5).width(Length::FillPortion(2)).push(Text::new("File d'attente").size(layout.text_size(25))), |column, (song, button_state)| {
+                                        let current = song.position.is_some() && song.position == status.song;
+                                        let button = Button::new(button_state, Row::new()
+                                            .spacing(10)
+                                            .push(Text::new(song.label()).size(layout.text_size(18)).width(Length::Fill))
+                                            .push(Text::new(song.duration.map(mpd::format_time).unwrap_or_default()).size(layout.text_size(16))))
+                                        .width(Length::Fill)
+                                        .padding(10)
+                                        .style(style::Button(if current { style::ButtonKind::Clock } else { style::ButtonKind::News }, theme));
+                                        column.push(match song.position {
+                                            Some(position) => button.on_press(Message::MpdQueuePressed(position)),
+                                            None => button,
+                                        })
+                                    });
+
+                                mpd_playlist_buttons.resize_with(snapshot.playlists.len(), button::State::default);
+                                let playlists = snapshot.playlists
+                                    .iter()
+                                    .zip(mpd_playlist_buttons.iter_mut())
+                                    .enumerate()
+                                    .fold(Column::new().spacing(5).width(Length::FillPortion(1)).push(Text::new("Listes de lecture").size(layout.text_size(25))), |column, (i, (name, button_state))| {
+                                        column.push(Button::new(button_state, Text::new(name.as_str()).size(layout.text_size(18)))
+                                        .width(Length::Fill)
+                                        .padding(10)
+                                        .style(style::Button(style::ButtonKind::News, theme))
+                                        .on_press(Message::MpdPlaylistPressed(i)))
+                                    });
+
+                                let mut page = Column::new()
+                                .spacing(20)
+                                .push(now_playing)
+                                .push(controls);
+                                if let Some(error) = mpd_error {
+                                    page = page.push(Text::new(error.as_str()).size(layout.text_size(16)));
+                                }
+                                page
+                                .push(Scrollable::new(scroll_music)
+                                .push(Row::new().spacing(20).push(queue).push(playlists))
+                                .style(style::Scrollable(theme))
+                                .width(Length::Fill)
+                                .height(Length::Fill))
+                                .into()
+                            }
+                            None => match mpd_error {
+                                Some(error) => Text::new(error.as_str()).size(layout.text_size(25)).into(),
+                                None => Column::new().into(),
+                            },
+                        };
+
+                        Column::new()
+                        .padding(20)
+                        .spacing(10)
+                        .push(Text::new("Musique").size(layout.text_size(50)))
+                        .push(content)
+                        .into()
+                    },
+                    "podcast" => {
+                        let (now_playing, pause_label) = match player.as_ref().and_then(|player| player.track().map(|track| (player, track))) {
+                            Some((player, track)) => {
+                                let position = player.position().map(audio::format_position).unwrap_or_default();
+                                let status = if player.is_paused() { "En pause" } else { "Lecture" };
+                                ([status, " : ", track.title.as_str(), "  ", position.as_str()].concat(), if player.is_paused() { "Reprendre" } else { "Pause" })
+                            }
+                            None => (String::from("Rien en cours de lecture"), "Pause"),
+                        };
+                        let volume = player.as_ref().map(|player| player.volume()).unwrap_or_default();
+                        let controls = Row::new()
+                        .spacing(10)
+                        .align_items(Align::Center)
+                        .push(Button::new(&mut player_controls.pause, Text::new(pause_label).size(layout.text_size(20))).padding(15).style(style::Button(style::ButtonKind::News, theme)).on_press(Message::PlayerPauseToggled))
+                        .push(Button::new(&mut player_controls.stop, Text::new("Arrêter").size(layout.text_size(20))).padding(15).style(style::Button(style::ButtonKind::News, theme)).on_press(Message::PlayerStopped))
+                        .push(Button::new(&mut player_controls.volume_down, Text::new("Vol −").size(layout.text_size(20))).padding(15).style(style::Button(style::ButtonKind::News, theme)).on_press(Message::PlayerVolumeChanged(false)))
+                        .push(Text::new(format!("{} %", volume)).size(layout.text_size(20)))
+                        .push(Button::new(&mut player_controls.volume_up, Text::new("Vol +").size(layout.text_size(20))).padding(15).style(style::Button(style::ButtonKind::News, theme)).on_press(Message::PlayerVolumeChanged(true)));
+
+                        let stations = podcast::parse_stations(radio_stations_value);
+                        station_buttons.resize_with(stations.len(), button::State::default);
+                        let station_row = stations
+                            .iter()
+                            .zip(station_buttons.iter_mut())
+                            .enumerate()
+                            .fold(Row::new().spacing(10), |row, (i, (station, button_state))| {
+                                row.push(Button::new(button_state, Text::new(&station.name).size(layout.text_size(20)))
+                                .padding(15)
+                                .style(style::Button(style::ButtonKind::News, theme))
+                                .on_press(Message::RadioStationPressed(i)))
+                            });
+
+                        let episodes: Vec<(usize, usize, &podcast::Episode)> = podcasts
+                            .iter()
+                            .enumerate()
+                            .flat_map(|(feed, podcast)| podcast.episodes.iter().enumerate().map(move |(i, episode)| (feed, i, episode)))
+                            .collect();
+                        episode_buttons.resize_with(episodes.len(), button::State::default);
+                        let episode_list = episodes
+                            .into_iter()
+                            .zip(episode_buttons.iter_mut())
+                            .fold(Column::new().spacing(5), |column, ((feed, i, episode), button_state)| {
+                                let column = if i == 0 {
+                                    column.push(Text::new(&podcasts[feed].title).size(layout.text_size(35)))
+                                } else {
+                                    column
+                                };
+                                let resume = player
+                                    .as_ref()
+                                    .and_then(|player| player.saved_position(&episode.key))
+                                    .map(|position| ["reprendre à ".to_string(), audio::format_position(position)].concat())
+                                    .unwrap_or_default();
+                                column.push(Button::new(button_state, Row::new()
+                                    .spacing(20)
+                                    .push(Text::new(&episode.title).size(layout.text_size(20)).width(Length::FillPortion(4)))
+                                    .push(Text::new(&episode.published).size(layout.text_size(16)).width(Length::FillPortion(2)))
+                                    .push(Text::new(resume).size(layout.text_size(16)).width(Length::FillPortion(1))))
+                                .width(Length::Fill)
+                                .padding(10)
+                                .style(style::Button(style::ButtonKind::News, theme))
+                                .on_press(Message::PodcastEpisodePressed(feed, i)))
+                            });
+                        let episode_list = podcast_errors.iter().fold(episode_list, |column, error| {
+                            column.push(Text::new(error.as_str()).size(layout.text_size(16)))
+                        });
+
+                        let mut page = Column::new()
+                        .padding(20)
+                        .spacing(10)
+                        .push(Text::new("Écoute").size(layout.text_size(50)))
+                        .push(Text::new(now_playing).size(layout.text_size(25)))
+                        .push(controls);
+                        if let Some(error) = player_error {
+                            page = page.push(Text::new(error.as_str()).size(layout.text_size(16)));
                         }
+                        page
+                        .push(station_row)
+                        .push(Scrollable::new(scroll_podcast)
+                        .push(episode_list)
+                        .style(style::Scrollable(theme))
+                        .width(Length::Fill)
+                        .height(Length::Fill))
+                        .into()
+                    },
+                    "history" => {
+                        let pick_list_history_range_gui = PickList::new(
+                            pick_list_history_range,
+                            &history::Range::ALL[..],
+                            Some(*history_range),
+                            Message::HistoryRangeSelected,
+                        );
+
+                        let chart: Element<_> = if weather_chart.is_empty() {
+                            Text::new("Aucune observation enregistrée sur cette période").size(layout.text_size(25)).into()
+                        } else {
+                            Canvas::new(weather_chart)
+                            .width(Length::Fill)
+                            .height(Length::Fill)
+                            .into()
+                        };
 
+                        Column::new()
+                        .padding(20)
+                        .spacing(10)
+                        .push(Text::new("Historique météo").size(layout.text_size(50)))
+                        .push(pick_list_history_range_gui)
+                        .push(chart)
+                        .into()
                     },
                     "news" => {
 
@@ -307,19 +2450,264 @@ impl Application for Assistant {
                                 .iter()
                                 .enumerate()
                                 .fold(Column::new().spacing(10).padding(20), |column, (_i, item)| {
-                                    column.push(Text::new(item.title().unwrap_or("...")).size(35))
-                                    .push(Text::new(item.pub_date().unwrap_or("")).size(15))
-                                    .push(Text::new(item.description().unwrap_or("")).size(20))
+                                    column.push(Text::new(item.title().unwrap_or("...")).size(layout.text_size(35)))
+                                    .push(Text::new(item.pub_date().unwrap_or("")).size(layout.text_size(15)))
+                                    .push(Text::new(item.description().unwrap_or("")).size(layout.text_size(20)))
                                 });
 
                         Column::new()
                         .padding(20)
                         .push(pick_list_gui)
-                        .push(Container::new(Scrollable::new(scroll).push(Container::new(news)).style(style::Scrollable).padding(10)
+                        .push(Container::new(Scrollable::new(scroll).push(Container::new(news)).style(style::Scrollable(theme)).padding(10)
+                        .width(Length::Fill)
+                        .height(Length::Fill)
+                    )).into()
+                    },
+                    "todo" => {
+                        let text_input_task = TextInput::new(
+                            input_task,
+                            "Nouvelle tâche",
+                            task_value,
+                            Message::TaskEdited,
+                        )
+                        .on_submit(Message::TaskAdded)
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let text_input_task_due = TextInput::new(
+                            input_task_due,
+                            "Échéance JJ/MM/AAAA",
+                            task_due_value,
+                            Message::TaskDueEdited,
+                        )
+                        .on_submit(Message::TaskAdded)
+                        .width(Length::Units(200))
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let pick_list_task_priority_gui = PickList::new(
+                            pick_list_task_priority,
+                            &todo::Priority::ALL[..],
+                            Some(*task_priority),
+                            Message::TaskPrioritySelected,
+                        );
+
+                        let add_task_button = Button::new(add_task, Text::new("Ajouter"))
+                            .padding(10)
+                            .style(style::Button(style::ButtonKind::Todo, theme))
+                            .on_press(Message::TaskAdded);
+
+                        let task_form: Element<_> = match layout.orientation() {
+                            layout::Orientation::Landscape => Row::new()
+                            .spacing(10)
+                            .push(text_input_task)
+                            .push(text_input_task_due)
+                            .push(pick_list_task_priority_gui)
+                            .push(add_task_button)
+                            .into(),
+                            layout::Orientation::Portrait => Column::new()
+                            .spacing(10)
+                            .push(text_input_task)
+                            .push(Row::new()
+                            .spacing(10)
+                            .push(text_input_task_due)
+                            .push(pick_list_task_priority_gui)
+                            .push(add_task_button))
+                            .into(),
+                        };
+
+                        task_controls.resize_with(task_list.tasks.len(), TaskControls::default);
+
+                        let tasks = task_list.tasks
+                                .iter()
+                                .zip(task_controls.iter_mut())
+                                .enumerate()
+                                .fold(Column::new().spacing(10).padding(20), |column, (i, (task, controls))| {
+                                    let due = match task.due {
+                                        Some(due) => due.format("%d/%m/%Y").to_string(),
+                                        None => String::new(),
+                                    };
+                                    let due_text = if task.is_overdue(today) {
+                                        Text::new(due).color(theme.alert)
+                                    } else {
+                                        Text::new(due)
+                                    };
+                                    column.push(Row::new()
+                                    .spacing(10)
+                                    .align_items(Align::Center)
+                                    .push(Checkbox::new(
+                                        task.completed,
+                                        task.description.clone(),
+                                        move |completed| Message::TaskToggled(i, completed),
+                                    ).width(Length::Fill))
+                                    .push(due_text.width(Length::Units(120)))
+                                    .push(Text::new(task.priority.to_string()).width(Length::Units(80)))
+                                    .push(Button::new(&mut controls.move_up, Text::new("Monter")).on_press(Message::TaskMovedUp(i)))
+                                    .push(Button::new(&mut controls.move_down, Text::new("Descendre")).on_press(Message::TaskMovedDown(i)))
+                                    .push(Button::new(&mut controls.delete, Text::new("Supprimer")).style(style::Button(style::ButtonKind::News, theme)).on_press(Message::TaskDeleted(i))))
+                                });
+
+                        let mut page = Column::new()
+                        .padding(20)
+                        .spacing(10)
+                        .push(Text::new("Tâches").size(layout.text_size(50)))
+                        .push(task_form);
+                        if let Some(error) = task_due_error {
+                            page = page.push(Text::new(error.as_str()).color(theme.alert).size(layout.text_size(16)));
+                        }
+                        page
+                        .push(Container::new(Scrollable::new(scroll_todo).push(Container::new(tasks)).style(style::Scrollable(theme)).padding(10)
                         .width(Length::Fill)
                         .height(Length::Fill)
                     )).into()
                     },
+                    "notes" => {
+                        let text_input_note = TextInput::new(
+                            input_note,
+                            "Nouvelle note",
+                            note_value,
+                            Message::NoteEdited,
+                        )
+                        .on_submit(Message::NoteAdded)
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let pick_list_note_color_gui = PickList::new(
+                            pick_list_note_color,
+                            &notes::NoteColor::ALL[..],
+                            Some(*note_color),
+                            Message::NoteColorSelected,
+                        );
+
+                        let add_note_button = Button::new(add_note, Text::new("Ajouter"))
+                            .padding(10)
+                            .style(style::Button(style::ButtonKind::Notes, theme))
+                            .on_press(Message::NoteAdded);
+
+                        note_controls.resize_with(note_board.notes.len(), NoteControls::default);
+
+                        let mut cards = note_board.notes
+                                .iter()
+                                .zip(note_controls.iter_mut())
+                                .enumerate()
+                                .map(|(i, (note, controls))| {
+                                    let text_input_card = TextInput::new(
+                                        &mut controls.input,
+                                        "Note",
+                                        &note.text,
+                                        move |text| Message::NoteChanged(i, text),
+                                    )
+                                    .padding(5)
+                                    .size(layout.text_size(25))
+                                    .style(style::Note(note.color, theme));
+
+                                    Container::new(Column::new()
+                                    .spacing(10)
+                                    .push(text_input_card)
+                                    .push(Row::new()
+                                    .spacing(5)
+                                    .push(Button::new(&mut controls.pin, Text::new(if note.pinned { "Détacher" } else { "Épingler" }).size(layout.text_size(16))).on_press(Message::NotePinToggled(i)))
+                                    .push(Button::new(&mut controls.color, Text::new("Couleur").size(layout.text_size(16))).on_press(Message::NoteColorCycled(i)))
+                                    .push(Button::new(&mut controls.delete, Text::new("Supprimer").size(layout.text_size(16))).style(style::Button(style::ButtonKind::News, theme)).on_press(Message::NoteDeleted(i)))))
+                                    .padding(10)
+                                    .width(Length::Units(270))
+                                    .style(style::Note(note.color, theme))
+                                })
+                                .peekable();
+
+                        let mut grid = Column::new().spacing(15).padding(20);
+                        while cards.peek().is_some() {
+                            grid = grid.push(cards.by_ref().take(layout.grid_columns(270, menu_visible)).fold(Row::new().spacing(15), |row, card| row.push(card)));
+                        }
+
+                        Column::new()
+                        .padding(20)
+                        .spacing(10)
+                        .push(Text::new("Notes").size(layout.text_size(50)))
+                        .push(Row::new()
+                        .spacing(10)
+                        .push(text_input_note)
+                        .push(pick_list_note_color_gui)
+                        .push(add_note_button))
+                        .push(Container::new(Scrollable::new(scroll_notes).push(grid).style(style::Scrollable(theme)).padding(10)
+                        .width(Length::Fill)
+                        .height(Length::Fill)
+                    )).into()
+                    },
+                    "mqtt" => {
+                        let tiles = mqtt::parse_tiles(mqtt_tiles_value);
+                        let actions = mqtt::parse_actions(mqtt_actions_value);
+                        let connection_text = match mqtt_connection {
+                            None => "Aucun broker MQTT configuré",
+                            Some(connection) if connection.is_connected() => "Connecté",
+                            Some(_) => "Connexion en cours...",
+                        };
+
+                        let mut cards = tiles
+                                .iter()
+                                .map(|tile| {
+                                    let value = mqtt_connection
+                                        .as_ref()
+                                        .and_then(|connection| connection.value(&tile.topic))
+                                        .unwrap_or_else(|| String::from("—"));
+                                    Container::new(Column::new()
+                                    .spacing(5)
+                                    .push(Text::new(&tile.label).size(layout.text_size(18)))
+                                    .push(Text::new(value).size(layout.text_size(40))))
+                                    .padding(15)
+                                    .width(Length::Units(220))
+                                    .style(style::Tile(theme))
+                                })
+                                .peekable();
+
+                        let mut grid = Column::new().spacing(15).padding(20);
+                        while cards.peek().is_some() {
+                            grid = grid.push(cards.by_ref().take(layout.grid_columns(220, menu_visible)).fold(Row::new().spacing(15), |row, card| row.push(card)));
+                        }
+
+                        mqtt_action_buttons.resize_with(actions.len(), button::State::default);
+                        let action_row = actions
+                            .iter()
+                            .zip(mqtt_action_buttons.iter_mut())
+                            .enumerate()
+                            .fold(Row::new().spacing(10).padding(20), |row, (i, (action, button_state))| {
+                                row.push(Button::new(button_state, Text::new(&action.label).size(layout.text_size(20)))
+                                .padding(15)
+                                .style(style::Button(style::ButtonKind::Mqtt, theme))
+                                .on_press(Message::MqttActionPressed(i)))
+                            });
+
+                        Column::new()
+                        .padding(20)
+                        .spacing(10)
+                        .push(Text::new("Maison").size(layout.text_size(50)))
+                        .push(Text::new(connection_text).size(layout.text_size(16)))
+                        .push(Scrollable::new(scroll_mqtt)
+                        .push(grid)
+                        .push(action_row)
+                        .style(style::Scrollable(theme))
+                        .width(Length::Fill)
+                        .height(Length::Fill))
+                        .into()
+                    },
+                    "system" => {
+                        let lines = system_status
+                            .lines()
+                            .into_iter()
+                            .fold(Column::new().spacing(layout.text_size(15)), |column, (label, value)| {
+                                column.push(Row::new()
+                                .spacing(20)
+                                .push(Text::new(label).size(layout.text_size(25)).width(Length::FillPortion(1)))
+                                .push(Text::new(value).size(layout.text_size(25)).width(Length::FillPortion(2))))
+                            });
+
+                        Column::new()
+                        .padding(20)
+                        .spacing(20)
+                        .push(Text::new("Système").size(layout.text_size(50)))
+                        .push(lines)
+                        .into()
+                    },
                     "parameter" => {
 
                         let text_input_openweather = TextInput::new(
@@ -329,7 +2717,14 @@ impl Application for Assistant {
                             Message::WeatherApiEdited,
                         )
                         .padding(10)
-                        .style(style::TextInput);
+                        .style(style::TextInput(theme));
+
+                        let pick_list_weather_provider_gui = PickList::new(
+                            pick_list_weather_provider,
+                            &weather::Provider::ALL[..],
+                            Some(*weather_provider),
+                            Message::WeatherProviderSelected,
+                        );
 
                         let text_input_searchcity = TextInput::new(
                             input_searchcity,
@@ -338,12 +2733,327 @@ impl Application for Assistant {
                             Message::SearchCityEdited,
                         )
                         .padding(10)
-                        .style(style::TextInput);
+                        .style(style::TextInput(theme));
+
+                        let text_input_calendar_files = TextInput::new(
+                            input_calendar_files,
+                            "Fichiers .ics (séparés par ;)",
+                            calendar_files_value,
+                            Message::CalendarFilesEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let text_input_screensaver_delay = TextInput::new(
+                            input_screensaver_delay,
+                            "Désactivé",
+                            screensaver_delay_value,
+                            Message::ScreensaverDelayEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let text_input_carousel_dwell = TextInput::new(
+                            input_carousel_dwell,
+                            "30, ou par page : meteo=30;news=60",
+                            carousel_dwell_value,
+                            Message::CarouselDwellEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let carousel_page_checkboxes = carousel::PAGES
+                            .iter()
+                            .fold(Row::new().spacing(20), |row, &page| {
+                                row.push(Checkbox::new(
+                                    carousel_pages.iter().any(|p| p == page),
+                                    carousel::label(page),
+                                    move |selected| Message::CarouselPageToggled(page, selected),
+                                ))
+                            });
+
+                        let text_input_calendar_days = TextInput::new(
+                            input_calendar_days,
+                            "7",
+                            calendar_days_value,
+                            Message::CalendarDaysEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let text_input_caldav_url = TextInput::new(
+                            input_caldav_url,
+                            "https://exemple.fr/dav/calendars/julien/personnel/",
+                            caldav_url_value,
+                            Message::CalDavUrlEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let text_input_caldav_username = TextInput::new(
+                            input_caldav_username,
+                            "Utilisateur CalDAV",
+                            caldav_username_value,
+                            Message::CalDavUsernameEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let text_input_caldav_password = TextInput::new(
+                            input_caldav_password,
+                            "Mot de passe CalDAV",
+                            caldav_password_value,
+                            Message::CalDavPasswordEdited,
+                        )
+                        .password()
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let text_input_mqtt_broker = TextInput::new(
+                            input_mqtt_broker,
+                            "Broker MQTT (hôte:port)",
+                            mqtt_broker_value,
+                            Message::MqttBrokerEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let text_input_mqtt_username = TextInput::new(
+                            input_mqtt_username,
+                            "Utilisateur MQTT",
+                            mqtt_username_value,
+                            Message::MqttUsernameEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let text_input_mqtt_password = TextInput::new(
+                            input_mqtt_password,
+                            "Mot de passe MQTT",
+                            mqtt_password_value,
+                            Message::MqttPasswordEdited,
+                        )
+                        .password()
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let text_input_mqtt_tiles = TextInput::new(
+                            input_mqtt_tiles,
+                            "Salon=maison/salon/temperature;Porte=maison/porte",
+                            mqtt_tiles_value,
+                            Message::MqttTilesEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let text_input_mqtt_actions = TextInput::new(
+                            input_mqtt_actions,
+                            "Lumière=maison/lumiere/set:ON",
+                            mqtt_actions_value,
+                            Message::MqttActionsEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let text_input_mqtt_command_topic = TextInput::new(
+                            input_mqtt_command_topic,
+                            mqtt::DEFAULT_COMMAND_TOPIC,
+                            mqtt_command_topic_value,
+                            Message::MqttCommandTopicEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let mqtt_connect_button = Button::new(mqtt_connect, Text::new("Se connecter"))
+                            .padding(10)
+                            .style(style::Button(style::ButtonKind::Mqtt, theme))
+                            .on_press(Message::MqttConnect);
+
+                        let text_input_weather_rules = TextInput::new(
+                            input_weather_rules,
+                            "temperature < 0; vent > 50; pluie 3h",
+                            weather_rules_value,
+                            Message::WeatherRulesEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let text_input_podcast_feeds = TextInput::new(
+                            input_podcast_feeds,
+                            "Flux de podcasts (URL séparées par ;)",
+                            podcast_feeds_value,
+                            Message::PodcastFeedsEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let text_input_radio_stations = TextInput::new(
+                            input_radio_stations,
+                            "Radios (nom=URL, séparées par ;)",
+                            radio_stations_value,
+                            Message::RadioStationsEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let text_input_audio_player = TextInput::new(
+                            input_audio_player,
+                            "Lecteur audio (mpv par défaut)",
+                            audio_player_value,
+                            Message::AudioPlayerEdited,
+                        )
+                        .on_submit(Message::AudioPlayerApplied)
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        // Changing the player stops what plays, so it waits for this button.
+                        let apply_audio_player_button = Button::new(apply_audio_player, Text::new("Appliquer"))
+                            .padding(10)
+                            .style(style::Button(style::ButtonKind::News, theme))
+                            .on_press(Message::AudioPlayerApplied);
+
+                        let text_input_mpd_address = TextInput::new(
+                            input_mpd_address,
+                            "Serveur MPD (hôte:port ou chemin du socket, localhost par défaut)",
+                            mpd_address_value,
+                            Message::MpdAddressEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let text_input_gtfs_path = TextInput::new(
+                            input_gtfs_path,
+                            "Fichier GTFS (.zip)",
+                            gtfs_path_value,
+                            Message::GtfsPathEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let text_input_gtfs_stops = TextInput::new(
+                            input_gtfs_stops,
+                            "Arrêts (identifiants ou noms séparés par ;)",
+                            gtfs_stops_value,
+                            Message::GtfsStopsEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let text_input_gtfs_realtime = TextInput::new(
+                            input_gtfs_realtime,
+                            "URL GTFS-Realtime (facultatif)",
+                            gtfs_realtime_value,
+                            Message::GtfsRealtimeEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let text_input_sensor_path = TextInput::new(
+                            input_sensor_path,
+                            "Détection automatique",
+                            sensor_path_value,
+                            Message::SensorPathEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let pick_list_theme_gui = PickList::new(
+                            pick_list_theme,
+                            &theme::ThemeChoice::ALL[..],
+                            Some(*theme_choice),
+                            Message::ThemeSelected,
+                        );
+
+                        let text_input_latitude = TextInput::new(
+                            input_latitude,
+                            "Latitude",
+                            latitude_value,
+                            Message::LatitudeEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let text_input_longitude = TextInput::new(
+                            input_longitude,
+                            "Longitude",
+                            longitude_value,
+                            Message::LongitudeEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let pick_list_night_schedule_gui = PickList::new(
+                            pick_list_night_schedule,
+                            &night::NightSchedule::ALL[..],
+                            Some(*night_schedule),
+                            Message::NightScheduleSelected,
+                        );
+
+                        let text_input_night_start = TextInput::new(
+                            input_night_start,
+                            "Début 22:00",
+                            night_start_value,
+                            Message::NightStartEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let text_input_night_end = TextInput::new(
+                            input_night_end,
+                            "Fin 07:00",
+                            night_end_value,
+                            Message::NightEndEdited,
+                        )
+                        .padding(10)
+                        .style(style::TextInput(theme));
+
+                        let pick_list_font_scale_gui = PickList::new(
+                            pick_list_font_scale,
+                            &layout::FontScale::ALL[..],
+                            Some(layout.font_scale),
+                            Message::FontScaleSelected,
+                        );
+
+                        let pick_list_dashboard_columns_gui = PickList::new(
+                            pick_list_dashboard_columns,
+                            &dashboard::COLUMNS[..],
+                            Some(*dashboard_columns),
+                            Message::DashboardColumnsSelected,
+                        );
+
+                        dashboard_move_up.resize_with(dashboard::Widget::ALL.len(), button::State::default);
+
+                        let dashboard_widget_rows = dashboard::Widget::ALL
+                            .iter()
+                            .zip(dashboard_move_up.iter_mut())
+                            .fold(Column::new().spacing(5), |column, (&widget, move_up)| {
+                                column.push(Row::new()
+                                .spacing(10)
+                                .align_items(Align::Center)
+                                .push(Checkbox::new(
+                                    dashboard_widgets.contains(&widget),
+                                    widget.to_string(),
+                                    move |shown| Message::DashboardWidgetToggled(widget, shown),
+                                ).width(Length::Units(250)))
+                                .push(Button::new(move_up, Text::new("Monter").size(layout.text_size(16)))
+                                .on_press(Message::DashboardWidgetMovedUp(widget))))
+                            });
+
+                        let pick_list_clock_face_gui = PickList::new(
+                            pick_list_clock_face,
+                            &clock::ClockFace::ALL[..],
+                            Some(*clock_face),
+                            Message::ClockFaceSelected,
+                        );
                 
-                        Column::new()
-                        .push(Text::new("Paramètres").size(50))
+                        let parameters = Column::new()
+                        .push(Text::new("Paramètres").size(layout.text_size(50)))
                         .padding(20)
                         .spacing(10)
+                        .push(Text::new(
+                            "Fournisseur météo",
+                        ))
+                        .push(pick_list_weather_provider_gui)
                         .push(Text::new(
                             "API Openweather",
                         ))
@@ -352,24 +3062,205 @@ impl Application for Assistant {
                             "Ville à chercher",
                         ))
                         .push(text_input_searchcity)
+                        .push(Text::new(
+                            "Alertes météo (température, vent, humidité, pluie)",
+                        ))
+                        .push(text_input_weather_rules)
+                        .push(Text::new(
+                            "Thème",
+                        ))
+                        .push(pick_list_theme_gui)
+                        .push(Text::new(
+                            "Taille du texte",
+                        ))
+                        .push(pick_list_font_scale_gui)
+                        .push(Text::new(
+                            "Coordonnées (latitude, longitude)",
+                        ))
+                        .push(Row::new()
+                        .spacing(10)
+                        .push(text_input_latitude)
+                        .push(text_input_longitude))
+                        .push(Text::new(
+                            "Mode nuit",
+                        ))
+                        .push(pick_list_night_schedule_gui)
+                        .push(Row::new()
+                        .spacing(10)
+                        .push(text_input_night_start)
+                        .push(text_input_night_end))
+                        .push(Text::new(
+                            if backlight.is_some() {
+                                ["Luminosité de jour :", &day_brightness.to_string(), "%"].concat()
+                            } else {
+                                "Aucun rétroéclairage détecté".to_string()
+                            }
+                        ))
+                        .push(Slider::new(
+                            slider_day_brightness,
+                            0..=100,
+                            *day_brightness,
+                            Message::DayBrightnessChanged,
+                        ))
+                        .push(Text::new(
+                            ["Luminosité de nuit :", &night_brightness.to_string(), "%"].concat()
+                        ))
+                        .push(Slider::new(
+                            slider_night_brightness,
+                            0..=100,
+                            *night_brightness,
+                            Message::NightBrightnessChanged,
+                        ))
+                        .push(Text::new(
+                            match sensor {
+                                Some(sensor) => ["Capteur intérieur : ", &sensor.name()].concat(),
+                                None => "Capteur intérieur (dossier sysfs hwmon ou IIO) : aucun détecté".to_string(),
+                            }
+                        ))
+                        .push(text_input_sensor_path)
+                        .push(Text::new(
+                            "Économiseur d'écran après (minutes)",
+                        ))
+                        .push(text_input_screensaver_delay)
+                        .push(Checkbox::new(
+                            *carousel_enabled,
+                            "Faire défiler les pages automatiquement",
+                            Message::CarouselToggled,
+                        ))
+                        .push(carousel_page_checkboxes)
+                        .push(Text::new(
+                            "Durée d'affichage des pages (secondes)",
+                        ))
+                        .push(text_input_carousel_dwell)
+                        .push(Text::new(
+                            "Affichage de l'horloge",
+                        ))
+                        .push(pick_list_clock_face_gui)
+                        .push(Text::new(
+                            "Accueil : widgets et nombre de colonnes",
+                        ))
+                        .push(dashboard_widget_rows)
+                        .push(pick_list_dashboard_columns_gui)
+                        .push(Text::new(
+                            "Fichiers agenda",
+                        ))
+                        .push(text_input_calendar_files)
+                        .push(Text::new(
+                            "Nombre de jours affichés",
+                        ))
+                        .push(text_input_calendar_days)
+                        .push(Checkbox::new(
+                            *calendar_watch,
+                            "Recharger les fichiers agenda modifiés",
+                            Message::CalendarWatchToggled,
+                        ))
+                        .push(Text::new(
+                            "Agenda CalDAV",
+                        ))
+                        .push(text_input_caldav_url)
+                        .push(Row::new()
+                        .spacing(10)
+                        .push(text_input_caldav_username)
+                        .push(text_input_caldav_password))
+                        .push(Text::new(
+                            "Départs (GTFS)",
+                        ))
+                        .push(text_input_gtfs_path)
+                        .push(text_input_gtfs_stops)
+                        .push(text_input_gtfs_realtime)
+                        .push(Text::new(
+                            "Écoute",
+                        ))
+                        .push(text_input_podcast_feeds)
+                        .push(text_input_radio_stations)
+                        .push(Row::new()
+                        .spacing(10)
+                        .push(text_input_audio_player)
+                        .push(apply_audio_player_button))
+                        .push(text_input_mpd_address)
+                        .push(Text::new(
+                            "MQTT",
+                        ))
+                        .push(text_input_mqtt_broker)
+                        .push(Row::new()
+                        .spacing(10)
+                        .push(text_input_mqtt_username)
+                        .push(text_input_mqtt_password))
+                        .push(Text::new(
+                            "Tuiles (libellé=topic, séparés par ;)",
+                        ))
+                        .push(text_input_mqtt_tiles)
+                        .push(Text::new(
+                            "Boutons (libellé=topic:message, séparés par ;)",
+                        ))
+                        .push(text_input_mqtt_actions)
+                        .push(Text::new(
+                            "Topic des commandes",
+                        ))
+                        .push(text_input_mqtt_command_topic)
+                        .push(mqtt_connect_button);
+
+                        Scrollable::new(scroll_parameter)
+                        .push(parameters)
+                        .style(style::Scrollable(theme))
+                        .width(Length::Fill)
+                        .height(Length::Fill)
                         .into()
                     },
                     _ => { Column::new().into()}
                 };
             
-                let menu = Column::new()
+                let toggle_menu_button =
+                    Button::new(toggle_menu, Text::new(if menu_visible { "Masquer" } else { "Menu" }).size(layout.text_size(16)).horizontal_alignment(HorizontalAlignment::Center))
+                        .width(if menu_visible { Length::Units(menu_width) } else { Length::Shrink })
+                        .style(style::Button(style::ButtonKind::Parameters, theme))
+                        .on_press(Message::MenuToggled);
+
+                let menu = if menu_visible {
+                    Scrollable::new(scroll_menu)
+                    .height(Length::Fill)
                     .align_items(Align::Center)
+                    .push(toggle_menu_button)
+                    .push(home_button)
                     .push(news_button)
                     .push(clock_button)
+                    .push(calendar_button)
                     .push(meteo_button)
-                    .push(parameter_button);
+                    .push(departures_button)
+                    .push(podcast_button)
+                    .push(music_button)
+                    .push(todo_button)
+                    .push(notes_button)
+                    .push(mqtt_button)
+                    .push(system_button)
+                    .push(parameter_button)
+                } else {
+                    Scrollable::new(scroll_menu)
+                    .push(toggle_menu_button)
+                };
         
-                Container::new(Row::new()
+                Container::new(Column::new()
+                .push(banners)
+                .push(Row::new()
                 .push(menu)
-                .push(content))
-                .style(style::Container)
+                .push(content)))
+                .style(style::Container(theme))
                 .into()
             }
         }
     }
+}
+
+fn notification_banners<'a>(notifications: &[(remote::Notification, DateTime<Local>)], theme: style::Theme, layout: layout::Layout) -> Column<'a, Message> {
+    notifications.iter().fold(Column::new().spacing(5).padding(if notifications.is_empty() { 0 } else { 5 }), |column, (notification, _)| {
+        let mut text = Column::new()
+            .push(Text::new(&notification.title).size(layout.text_size(24)));
+        if !notification.body.is_empty() {
+            text = text.push(Text::new(&notification.body).size(layout.text_size(18)));
+        }
+        column.push(Container::new(text)
+            .width(Length::Fill)
+            .padding(10)
+            .style(style::Banner(notification.level, theme)))
+    })
 }
\ No newline at end of file
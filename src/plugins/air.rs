@@ -0,0 +1,190 @@
+use json::JsonValue;
+use reqwest::Url;
+use std::error::Error;
+
+// Shared by the air quality index and the UV index, from the lowest risk to the highest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Low,
+    Moderate,
+    High,
+    VeryHigh,
+    Extreme,
+}
+
+impl Severity {
+    // OpenWeatherMap's index goes from 1 (good) to 5 (very poor).
+    pub fn from_aqi(aqi: u8) -> Option<Severity> {
+        match aqi {
+            1 => Some(Severity::Low),
+            2 => Some(Severity::Moderate),
+            3 => Some(Severity::High),
+            4 => Some(Severity::VeryHigh),
+            5 => Some(Severity::Extreme),
+            _ => None,
+        }
+    }
+
+    // WHO bands: 0-2, 3-5, 6-7, 8-10 and 11 or more.
+    pub fn from_uv_index(uv_index: f32) -> Severity {
+        match uv_index.round() as i32 {
+            i32::MIN..=2 => Severity::Low,
+            3..=5 => Severity::Moderate,
+            6..=7 => Severity::High,
+            8..=10 => Severity::VeryHigh,
+            _ => Severity::Extreme,
+        }
+    }
+
+    pub fn air_quality_label(self) -> &'static str {
+        match self {
+            Severity::Low => "Bonne",
+            Severity::Moderate => "Correcte",
+            Severity::High => "Moyenne",
+            Severity::VeryHigh => "Mauvaise",
+            Severity::Extreme => "Très mauvaise",
+        }
+    }
+
+    pub fn air_quality_advice(self) -> &'static str {
+        match self {
+            Severity::Low => "Aucune précaution particulière.",
+            Severity::Moderate => "Les personnes très sensibles peuvent limiter les efforts prolongés en extérieur.",
+            Severity::High => "Personnes sensibles : réduisez les activités physiques intenses en extérieur.",
+            Severity::VeryHigh => "Limitez les efforts en extérieur ; les personnes sensibles devraient les éviter.",
+            Severity::Extreme => "Évitez les activités en extérieur et aérez aux heures les moins polluées.",
+        }
+    }
+
+    pub fn uv_label(self) -> &'static str {
+        match self {
+            Severity::Low => "Faible",
+            Severity::Moderate => "Modéré",
+            Severity::High => "Élevé",
+            Severity::VeryHigh => "Très élevé",
+            Severity::Extreme => "Extrême",
+        }
+    }
+
+    pub fn uv_advice(self) -> &'static str {
+        match self {
+            Severity::Low => "Pas de protection nécessaire.",
+            Severity::Moderate => "Lunettes de soleil et crème solaire en cas d'exposition prolongée.",
+            Severity::High => "Crème solaire, chapeau et ombre entre 12 h et 16 h.",
+            Severity::VeryHigh => "Évitez le soleil entre 12 h et 16 h, protection indispensable.",
+            Severity::Extreme => "Évitez toute exposition au soleil aux heures chaudes.",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AirQuality {
+    pub aqi: u8,
+    // µg/m³
+    pub pm2_5: Option<f32>,
+    pub pm10: Option<f32>,
+    pub o3: Option<f32>,
+    pub no2: Option<f32>,
+}
+
+impl AirQuality {
+    pub fn severity(&self) -> Severity {
+        Severity::from_aqi(self.aqi).unwrap_or(Severity::Low)
+    }
+
+    pub fn components(&self) -> String {
+        let values = [("PM2.5", self.pm2_5), ("PM10", self.pm10), ("O3", self.o3), ("NO2", self.no2)];
+        let parts: Vec<String> = values
+            .iter()
+            .filter_map(|(name, value)| value.map(|value| format!("{} {:.0}", name, value)))
+            .collect();
+        if parts.is_empty() {
+            String::new()
+        } else {
+            [parts.join(" · "), " µg/m³".to_string()].concat()
+        }
+    }
+}
+
+pub fn parse_air_pollution(response: &JsonValue) -> Option<AirQuality> {
+    let entry = &response["list"][0];
+    let aqi = entry["main"]["aqi"].as_u8().filter(|aqi| Severity::from_aqi(*aqi).is_some())?;
+    let components = &entry["components"];
+    Some(AirQuality {
+        aqi,
+        pm2_5: components["pm2_5"].as_f32(),
+        pm10: components["pm10"].as_f32(),
+        o3: components["o3"].as_f32(),
+        no2: components["no2"].as_f32(),
+    })
+}
+
+// The UV index comes with One Call for OpenWeatherMap subscribers, from Open-Meteo otherwise.
+pub fn one_call_uv_index(one_call: &JsonValue) -> Option<f32> {
+    one_call["current"]["uvi"].as_f32()
+}
+
+pub fn parse_open_meteo_uv_index(response: &JsonValue) -> Option<f32> {
+    response["current"]["uv_index"].as_f32()
+}
+
+pub fn get_air_pollution(latitude: f64, longitude: f64, api_key: &str) -> Result<AirQuality, Box<dyn Error>> {
+    let url = Url::parse_with_params("https://api.openweathermap.org/data/2.5/air_pollution", &[
+        ("lat", latitude.to_string()),
+        ("lon", longitude.to_string()),
+        ("appid", api_key.trim().to_string()),
+    ])?;
+    let response = json::parse(&reqwest::blocking::get(url)?.text()?)?;
+    parse_air_pollution(&response)
+        .ok_or_else(|| response["message"].as_str().unwrap_or("Réponse inattendue").to_string().into())
+}
+
+pub fn get_uv_index(latitude: f64, longitude: f64) -> Result<f32, Box<dyn Error>> {
+    let url = Url::parse_with_params("https://api.open-meteo.com/v1/forecast", &[
+        ("latitude", latitude.to_string()),
+        ("longitude", longitude.to_string()),
+        ("current", "uv_index".to_string()),
+    ])?;
+    let response = json::parse(&reqwest::blocking::get(url)?.text()?)?;
+    parse_open_meteo_uv_index(&response)
+        .ok_or_else(|| response["reason"].as_str().unwrap_or("Réponse inattendue").to_string().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_air_pollution() {
+        let response = json::parse(r#"{"coord": {"lon": 3.06, "lat": 50.63}, "list": [{"dt": 1620000000,
+            "main": {"aqi": 3},
+            "components": {"co": 230.3, "no": 0.1, "no2": 14.6, "o3": 68.7, "so2": 1.2, "pm2_5": 12.4, "pm10": 18.9, "nh3": 3.1}}]}"#).unwrap();
+        let air = parse_air_pollution(&response).unwrap();
+        assert_eq!(air.aqi, 3);
+        assert_eq!(air.severity(), Severity::High);
+        assert_eq!(air.severity().air_quality_label(), "Moyenne");
+        assert_eq!(air.components(), "PM2.5 12 · PM10 19 · O3 69 · NO2 15 µg/m³");
+        assert!(parse_air_pollution(&json::parse(r#"{"cod": 401, "message": "Invalid API key"}"#).unwrap()).is_none());
+        assert!(parse_air_pollution(&json::parse(r#"{"list": [{"main": {"aqi": 7}}]}"#).unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_uv_bands() {
+        let bands: Vec<Severity> = [0.0, 2.4, 2.6, 5.0, 6.0, 7.4, 8.0, 10.4, 11.0, 14.0].iter().map(|uv| Severity::from_uv_index(*uv)).collect();
+        assert_eq!(bands, vec![
+            Severity::Low,
+            Severity::Low,
+            Severity::Moderate,
+            Severity::Moderate,
+            Severity::High,
+            Severity::High,
+            Severity::VeryHigh,
+            Severity::VeryHigh,
+            Severity::Extreme,
+            Severity::Extreme,
+        ]);
+        assert_eq!(Severity::from_uv_index(9.0).uv_label(), "Très élevé");
+        assert_eq!(one_call_uv_index(&json::parse(r#"{"current": {"uvi": 6.2}}"#).unwrap()), Some(6.2));
+        assert_eq!(parse_open_meteo_uv_index(&json::parse(r#"{"current": {"time": "2021-05-03T12:00", "uv_index": 4.5}}"#).unwrap()), Some(4.5));
+    }
+}
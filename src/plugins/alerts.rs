@@ -0,0 +1,237 @@
+use json::JsonValue;
+use std::error::Error;
+
+use crate::plugins::remote::{Level, Notification};
+use crate::plugins::weather::WeatherReport;
+
+// Alert banners stay this long; the rule is only reported again once it has cleared.
+pub const BANNER_SECONDS: u64 = 120;
+pub const HISTORY_LENGTH: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rule {
+    TemperatureBelow(f32),
+    TemperatureAbove(f32),
+    WindAbove(f32),
+    HumidityAbove(f32),
+    RainWithin(u32),
+}
+
+// `temperature < 0; vent > 50; pluie 3h`, in French or English; unknown entries are skipped.
+pub fn parse_rules(value: &str) -> Vec<Rule> {
+    value.split(';').filter_map(parse_rule).collect()
+}
+
+fn parse_rule(entry: &str) -> Option<Rule> {
+    let entry = entry.trim().to_lowercase();
+    let mut parts = entry.split_whitespace();
+    let metric = parts.next()?;
+    if ["rain", "pluie"].contains(&metric) {
+        let hours = parts.next()?.trim_end_matches('h').parse().ok()?;
+        return Some(Rule::RainWithin(hours));
+    }
+    let comparison = parts.next()?;
+    let threshold: f32 = parts.next()?.parse().ok()?;
+    match (metric, comparison) {
+        ("temperature" | "température" | "temp", "<") => Some(Rule::TemperatureBelow(threshold)),
+        ("temperature" | "température" | "temp", ">") => Some(Rule::TemperatureAbove(threshold)),
+        ("wind" | "vent", ">") => Some(Rule::WindAbove(threshold)),
+        ("humidity" | "humidité", ">") => Some(Rule::HumidityAbove(threshold)),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Conditions {
+    pub temperature: Option<f32>,
+    // km/h
+    pub wind: Option<f32>,
+    pub humidity: Option<f32>,
+    // Hours until the first rainy hour of the forecast.
+    pub rain_in: Option<u32>,
+}
+
+// `one_call` is a One Call response in metric units; it gives the rain forecast of
+// OpenWeatherMap, whose current weather has none.
+pub fn conditions(report: &WeatherReport, one_call: Option<&JsonValue>) -> Conditions {
+    let rain_in = one_call.and_then(|one_call| {
+        let now = one_call["current"]["dt"].as_i64().unwrap_or(report.time);
+        one_call["hourly"]
+            .members()
+            .find(|hour| hour["rain"]["1h"].as_f32().unwrap_or(0.0) > 0.0 || hour["weather"][0]["main"] == "Rain")
+            .and_then(|hour| hour["dt"].as_i64())
+            .map(|time| ((time - now).max(0) / 3600) as u32)
+    });
+    Conditions {
+        temperature: Some(report.temperature),
+        wind: report.wind,
+        humidity: report.humidity,
+        rain_in: rain_in.or(report.rain_in),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    // Identifies the alert while it stays active.
+    pub key: String,
+    pub title: String,
+    pub body: String,
+    pub level: Level,
+}
+
+impl Alert {
+    pub fn notification(&self) -> Notification {
+        Notification {
+            title: self.title.clone(),
+            body: self.body.clone(),
+            level: self.level,
+            duration: BANNER_SECONDS,
+        }
+    }
+}
+
+fn rule_alert(rule: Rule, conditions: &Conditions) -> Option<Alert> {
+    let (title, body) = match rule {
+        Rule::TemperatureBelow(threshold) => {
+            let temperature = conditions.temperature.filter(|t| *t < threshold)?;
+            (format!("Température sous {} °C", threshold), format!("{:.0} °C actuellement", temperature))
+        }
+        Rule::TemperatureAbove(threshold) => {
+            let temperature = conditions.temperature.filter(|t| *t > threshold)?;
+            (format!("Température au-dessus de {} °C", threshold), format!("{:.0} °C actuellement", temperature))
+        }
+        Rule::WindAbove(threshold) => {
+            let wind = conditions.wind.filter(|w| *w > threshold)?;
+            (format!("Vent au-dessus de {} km/h", threshold), format!("{:.0} km/h actuellement", wind))
+        }
+        Rule::HumidityAbove(threshold) => {
+            let humidity = conditions.humidity.filter(|h| *h > threshold)?;
+            (format!("Humidité au-dessus de {} %", threshold), format!("{:.0} % actuellement", humidity))
+        }
+        Rule::RainWithin(hours) => {
+            let rain_in = conditions.rain_in.filter(|r| *r <= hours)?;
+            let body = if rain_in == 0 { String::from("Pluie en cours") } else { format!("Pluie dans {} h", rain_in) };
+            (format!("Pluie attendue dans les {} heures", hours), body)
+        }
+    };
+    Some(Alert { key: format!("{:?}", rule), title, body, level: Level::Warning })
+}
+
+pub fn triggered(rules: &[Rule], conditions: &Conditions) -> Vec<Alert> {
+    rules.iter().filter_map(|rule| rule_alert(*rule, conditions)).collect()
+}
+
+// Alerts issued by the national weather services, as relayed by One Call.
+pub fn official_alerts(one_call: &JsonValue) -> Vec<Alert> {
+    one_call["alerts"]
+        .members()
+        .map(|alert| {
+            let event = alert["event"].as_str().unwrap_or("Alerte météo");
+            let sender = alert["sender_name"].as_str().unwrap_or("");
+            let description = alert["description"].as_str().unwrap_or("").trim();
+            Alert {
+                key: format!("{}-{}", event, alert["start"].as_i64().unwrap_or(0)),
+                title: if sender.is_empty() { event.to_string() } else { format!("{} ({})", event, sender) },
+                body: description.lines().next().unwrap_or("").to_string(),
+                level: Level::Error,
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Tracker {
+    active: Vec<String>,
+}
+
+impl Tracker {
+    // Returns the alerts that were not active at the previous refresh.
+    pub fn update(&mut self, alerts: Vec<Alert>) -> Vec<Alert> {
+        let fresh = alerts.iter().filter(|alert| !self.active.contains(&alert.key)).cloned().collect();
+        self.active = alerts.into_iter().map(|alert| alert.key).collect();
+        fresh
+    }
+}
+
+pub fn get_one_call(latitude: f64, longitude: f64, api_key: &str) -> Result<JsonValue, Box<dyn Error>> {
+    let url = format!(
+        "https://api.openweathermap.org/data/3.0/onecall?lat={}&lon={}&exclude=minutely,daily&units=metric&appid={}",
+        latitude, longitude, api_key
+    );
+    let response = json::parse(&reqwest::blocking::get(&url)?.text()?)?;
+    if response["hourly"].is_array() {
+        Ok(response)
+    } else {
+        Err(response["message"].as_str().unwrap_or("unexpected One Call response").into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURRENT: &str = r#"{"dt": 1000, "main": {"temp": -2.0, "humidity": 95}, "wind": {"speed": 15}}"#;
+    const ONE_CALL: &str = r#"{
+        "current": {"dt": 1000},
+        "hourly": [
+            {"dt": 1000, "weather": [{"main": "Clouds"}]},
+            {"dt": 4600, "weather": [{"main": "Clouds"}]},
+            {"dt": 8200, "weather": [{"main": "Snow"}], "rain": {"1h": 0.4}}
+        ],
+        "alerts": [{"sender_name": "Météo-France", "event": "Vigilance orange neige", "start": 1000, "description": "Chutes de neige\nDétails"}]
+    }"#;
+
+    #[test]
+    fn test_parse_rules() {
+        assert_eq!(parse_rules("temperature < 0; Vent > 50;pluie 3h; humidité > 90; soleil > 2; temp = 4"), vec![
+            Rule::TemperatureBelow(0.0),
+            Rule::WindAbove(50.0),
+            Rule::RainWithin(3),
+            Rule::HumidityAbove(90.0),
+        ]);
+        assert!(parse_rules("").is_empty());
+    }
+
+    #[test]
+    fn test_conditions_and_rules() {
+        let current = crate::plugins::weather::parse_openweathermap(&json::parse(CURRENT).unwrap()).unwrap();
+        let one_call = json::parse(ONE_CALL).unwrap();
+        let conditions = conditions(&current, Some(&one_call));
+        assert_eq!(conditions.temperature, Some(-2.0));
+        assert_eq!(conditions.wind, Some(54.0));
+        assert_eq!(conditions.rain_in, Some(2));
+        assert_eq!(super::conditions(&current, None).rain_in, None);
+        let forecast = WeatherReport { rain_in: Some(1), ..current };
+        assert_eq!(super::conditions(&forecast, None).rain_in, Some(1));
+
+        let alerts = triggered(&parse_rules("temperature < 0; temperature > 30; vent > 50; pluie 1h; pluie 3h"), &conditions);
+        let titles: Vec<&str> = alerts.iter().map(|alert| alert.title.as_str()).collect();
+        assert_eq!(titles, vec!["Température sous 0 °C", "Vent au-dessus de 50 km/h", "Pluie attendue dans les 3 heures"]);
+        assert_eq!(alerts[0].body, "-2 °C actuellement");
+        assert_eq!(alerts[2].body, "Pluie dans 2 h");
+    }
+
+    #[test]
+    fn test_official_alerts() {
+        let alerts = official_alerts(&json::parse(ONE_CALL).unwrap());
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].title, "Vigilance orange neige (Météo-France)");
+        assert_eq!(alerts[0].body, "Chutes de neige");
+        assert_eq!(alerts[0].level, Level::Error);
+        assert!(official_alerts(&json::parse("{}").unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_tracker_reports_new_alerts_once() {
+        let conditions = Conditions { temperature: Some(-3.0), wind: Some(60.0), ..Conditions::default() };
+        let rules = parse_rules("temperature < 0; vent > 50");
+        let mut tracker = Tracker::default();
+        assert_eq!(tracker.update(triggered(&rules, &conditions)).len(), 2);
+        assert!(tracker.update(triggered(&rules, &conditions)).is_empty());
+        let calmer = Conditions { wind: Some(20.0), ..conditions };
+        assert!(tracker.update(triggered(&rules, &calmer)).is_empty());
+        let fresh = tracker.update(triggered(&rules, &conditions));
+        assert_eq!(fresh.len(), 1);
+        assert_eq!(fresh[0].title, "Vent au-dessus de 50 km/h");
+    }
+}
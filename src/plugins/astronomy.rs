@@ -0,0 +1,304 @@
+use chrono::prelude::*;
+use chrono::Duration;
+
+const J2000: f64 = 2451545.0;
+const UNIX_EPOCH_JULIAN_DAY: f64 = 2440587.5;
+
+// Altitude of the sun's centre at sunrise and sunset, accounting for refraction and the solar disc.
+pub const SUNRISE_ALTITUDE: f64 = -0.833;
+// Civil twilight ends when the sun's centre is 6 degrees below the horizon.
+pub const CIVIL_TWILIGHT_ALTITUDE: f64 = -6.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SunTimes {
+    Rises { rise: DateTime<Utc>, set: DateTime<Utc> },
+    AlwaysUp,
+    AlwaysDown,
+}
+
+pub fn parse_coordinates(latitude: &str, longitude: &str) -> Option<(f64, f64)> {
+    let latitude: f64 = latitude.trim().replace(',', ".").parse().ok()?;
+    let longitude: f64 = longitude.trim().replace(',', ".").parse().ok()?;
+    // "NaN" and "inf" parse as f64 too.
+    if !latitude.is_finite() || !longitude.is_finite() || latitude.abs() > 90.0 || longitude.abs() > 180.0 {
+        return None;
+    }
+    Some((latitude, longitude))
+}
+
+fn julian_day_to_utc(julian_day: f64) -> DateTime<Utc> {
+    let seconds = (julian_day - UNIX_EPOCH_JULIAN_DAY) * 86400.0;
+    Utc.timestamp_opt(seconds.round() as i64, 0).unwrap()
+}
+
+fn days_since_j2000(date: NaiveDate) -> f64 {
+    (date - NaiveDate::from_ymd_opt(2000, 1, 1).unwrap()).num_days() as f64
+}
+
+// Sunrise equation: times at which the sun crosses `altitude` degrees on `date`,
+// for a location given in degrees (longitude positive to the east).
+pub fn sun_times(date: NaiveDate, latitude: f64, longitude: f64, altitude: f64) -> SunTimes {
+    let mean_solar_noon = days_since_j2000(date) - longitude / 360.0;
+    let mean_anomaly = (357.5291 + 0.98560028 * mean_solar_noon).rem_euclid(360.0).to_radians();
+    let center = 1.9148 * mean_anomaly.sin() + 0.0200 * (2.0 * mean_anomaly).sin() + 0.0003 * (3.0 * mean_anomaly).sin();
+    let ecliptic_longitude = (mean_anomaly.to_degrees() + center + 180.0 + 102.9372).rem_euclid(360.0).to_radians();
+    let transit = J2000 + mean_solar_noon + 0.0053 * mean_anomaly.sin() - 0.0069 * (2.0 * ecliptic_longitude).sin();
+    let declination = (ecliptic_longitude.sin() * 23.4397_f64.to_radians().sin()).asin();
+
+    let latitude = latitude.to_radians();
+    let cos_hour_angle = (altitude.to_radians().sin() - latitude.sin() * declination.sin())
+        / (latitude.cos() * declination.cos());
+    if cos_hour_angle < -1.0 {
+        return SunTimes::AlwaysUp;
+    }
+    if cos_hour_angle > 1.0 {
+        return SunTimes::AlwaysDown;
+    }
+    let hour_angle = cos_hour_angle.acos().to_degrees();
+    SunTimes::Rises {
+        rise: julian_day_to_utc(transit - hour_angle / 360.0),
+        set: julian_day_to_utc(transit + hour_angle / 360.0),
+    }
+}
+
+pub fn sunrise_sunset(date: NaiveDate, latitude: f64, longitude: f64) -> SunTimes {
+    sun_times(date, latitude, longitude, SUNRISE_ALTITUDE)
+}
+
+pub fn civil_twilight(date: NaiveDate, latitude: f64, longitude: f64) -> SunTimes {
+    sun_times(date, latitude, longitude, CIVIL_TWILIGHT_ALTITUDE)
+}
+
+pub fn day_length(sun: SunTimes) -> Duration {
+    match sun {
+        SunTimes::Rises { rise, set } => set - rise,
+        SunTimes::AlwaysUp => Duration::hours(24),
+        SunTimes::AlwaysDown => Duration::zero(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    NewMoon,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    FullMoon,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+}
+
+impl std::fmt::Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Phase::NewMoon => "Nouvelle lune",
+                Phase::WaxingCrescent => "Premier croissant",
+                Phase::FirstQuarter => "Premier quartier",
+                Phase::WaxingGibbous => "Gibbeuse croissante",
+                Phase::FullMoon => "Pleine lune",
+                Phase::WaningGibbous => "Gibbeuse décroissante",
+                Phase::LastQuarter => "Dernier quartier",
+                Phase::WaningCrescent => "Dernier croissant",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Moon {
+    // Between 0 (new moon) and 1 (full moon).
+    pub illumination: f64,
+    // Angle between the moon and the sun seen from the earth, 0 to 360 degrees along the lunation.
+    pub elongation: f64,
+}
+
+impl Moon {
+    pub fn phase(&self) -> Phase {
+        const PHASES: [Phase; 8] = [
+            Phase::NewMoon,
+            Phase::WaxingCrescent,
+            Phase::FirstQuarter,
+            Phase::WaxingGibbous,
+            Phase::FullMoon,
+            Phase::WaningGibbous,
+            Phase::LastQuarter,
+            Phase::WaningCrescent,
+        ];
+        PHASES[((self.elongation + 22.5) / 45.0) as usize % 8]
+    }
+}
+
+// Low precision lunar theory (Meeus, Astronomical Algorithms, chapter 48): the main
+// periodic terms of the phase angle are enough for a fraction of a percent.
+pub fn moon(time: DateTime<Utc>) -> Moon {
+    let julian_day = time.timestamp() as f64 / 86400.0 + UNIX_EPOCH_JULIAN_DAY;
+    let centuries = (julian_day - J2000) / 36525.0;
+    let elongation = (297.8501921 + 445267.1114034 * centuries).rem_euclid(360.0).to_radians();
+    let sun_anomaly = (357.5291092 + 35999.0502909 * centuries).rem_euclid(360.0).to_radians();
+    let moon_anomaly = (134.9633964 + 477198.8675055 * centuries).rem_euclid(360.0).to_radians();
+    let phase_angle = 180.0 - elongation.to_degrees()
+        - 6.289 * moon_anomaly.sin()
+        + 2.100 * sun_anomaly.sin()
+        - 1.274 * (2.0 * elongation - moon_anomaly).sin()
+        - 0.658 * (2.0 * elongation).sin()
+        - 0.214 * (2.0 * moon_anomaly).sin()
+        - 0.110 * elongation.sin();
+    Moon {
+        illumination: (1.0 + phase_angle.to_radians().cos()) / 2.0,
+        elongation: (180.0 - phase_angle).rem_euclid(360.0),
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes();
+    format!("{} h {:02}", minutes / 60, minutes % 60)
+}
+
+// Lines of the ephemeris panel, with times in the time zone of `now`.
+pub fn ephemeris<Tz: TimeZone>(now: DateTime<Tz>, latitude: f64, longitude: f64) -> Vec<String>
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let date = now.naive_local().date();
+    let local = |time: DateTime<Utc>| time.with_timezone(&now.timezone()).format("%H:%M").to_string();
+    let sun = sunrise_sunset(date, latitude, longitude);
+    let sun_line = match sun {
+        SunTimes::Rises { rise, set } => format!("Lever du soleil {} · Coucher {}", local(rise), local(set)),
+        SunTimes::AlwaysUp => String::from("Soleil de minuit"),
+        SunTimes::AlwaysDown => String::from("Nuit polaire"),
+    };
+    let twilight_line = match civil_twilight(date, latitude, longitude) {
+        SunTimes::Rises { rise, set } => format!("Aube civile {} · Crépuscule civil {}", local(rise), local(set)),
+        SunTimes::AlwaysUp => String::from("Crépuscule civil toute la nuit"),
+        SunTimes::AlwaysDown => String::from("Pas de crépuscule civil"),
+    };
+    let moon = moon(now.with_timezone(&Utc));
+    vec![
+        sun_line,
+        ["Durée du jour ", &format_duration(day_length(sun))].concat(),
+        twilight_line,
+        format!("{}, éclairée à {:.0} %", moon.phase(), moon.illumination * 100.0),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: DateTime<Utc>, expected: (i32, u32, u32, u32, u32)) {
+        let (y, m, d, h, min) = expected;
+        let expected = Utc.from_utc_datetime(&NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, 0).unwrap());
+        let difference = (actual - expected).num_seconds().abs();
+        assert!(difference <= 120, "{} is not close to {}", actual, expected);
+    }
+
+    #[test]
+    fn test_sunrise_sunset_paris_summer_solstice() {
+        // Paris, 21 June 2021: sunrise 05:47 CEST, sunset 21:58 CEST.
+        match sunrise_sunset(NaiveDate::from_ymd_opt(2021, 6, 21).unwrap(), 48.8566, 2.3522) {
+            SunTimes::Rises { rise, set } => {
+                assert_close(rise, (2021, 6, 21, 3, 47));
+                assert_close(set, (2021, 6, 21, 19, 58));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_sunrise_sunset_new_york_winter() {
+        // New York, 1 January 2021: sunrise 07:20 EST, sunset 16:39 EST.
+        match sunrise_sunset(NaiveDate::from_ymd_opt(2021, 1, 1).unwrap(), 40.7128, -74.0060) {
+            SunTimes::Rises { rise, set } => {
+                assert_close(rise, (2021, 1, 1, 12, 20));
+                assert_close(set, (2021, 1, 1, 21, 39));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_polar_day_and_night() {
+        let tromso = (69.6492, 18.9553);
+        assert_eq!(sunrise_sunset(NaiveDate::from_ymd_opt(2021, 6, 21).unwrap(), tromso.0, tromso.1), SunTimes::AlwaysUp);
+        assert_eq!(sunrise_sunset(NaiveDate::from_ymd_opt(2021, 12, 21).unwrap(), tromso.0, tromso.1), SunTimes::AlwaysDown);
+    }
+
+    #[test]
+    fn test_civil_twilight() {
+        // Paris, 21 June 2021: civil dawn 05:04 CEST, civil dusk 22:41 CEST.
+        match civil_twilight(NaiveDate::from_ymd_opt(2021, 6, 21).unwrap(), 48.8566, 2.3522) {
+            SunTimes::Rises { rise, set } => {
+                assert_close(rise, (2021, 6, 21, 3, 4));
+                assert_close(set, (2021, 6, 21, 20, 41));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        // New York, 1 January 2021: civil dawn 06:49 EST, civil dusk 17:10 EST.
+        match civil_twilight(NaiveDate::from_ymd_opt(2021, 1, 1).unwrap(), 40.7128, -74.0060) {
+            SunTimes::Rises { rise, set } => {
+                assert_close(rise, (2021, 1, 1, 11, 49));
+                assert_close(set, (2021, 1, 1, 22, 10));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_day_length() {
+        // Paris, 21 June 2021: 16 h 11 of daylight.
+        let length = day_length(sunrise_sunset(NaiveDate::from_ymd_opt(2021, 6, 21).unwrap(), 48.8566, 2.3522));
+        assert!((length.num_minutes() - 971).abs() <= 3, "{}", length);
+        assert_eq!(day_length(SunTimes::AlwaysUp), Duration::hours(24));
+        assert_eq!(format_duration(Duration::minutes(545)), "9 h 05");
+    }
+
+    #[test]
+    fn test_moon_phases() {
+        let at = |y, m, d, h, min| Utc.from_utc_datetime(&NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, 0).unwrap());
+        // Full moon of the 26 May 2021 lunar eclipse, 11:14 UTC.
+        let full = moon(at(2021, 5, 26, 11, 14));
+        assert!(full.illumination > 0.995);
+        assert_eq!(full.phase(), Phase::FullMoon);
+        // New moon of the 10 June 2021 solar eclipse, 10:53 UTC.
+        let new = moon(at(2021, 6, 10, 10, 53));
+        assert!(new.illumination < 0.005);
+        assert_eq!(new.phase(), Phase::NewMoon);
+        // First quarter 18 June 2021 03:54 UTC, last quarter 1 July 2021 21:11 UTC.
+        let first = moon(at(2021, 6, 18, 3, 54));
+        assert!((first.illumination - 0.5).abs() < 0.01);
+        assert_eq!(first.phase(), Phase::FirstQuarter);
+        let last = moon(at(2021, 7, 1, 21, 11));
+        assert!((last.illumination - 0.5).abs() < 0.01);
+        assert_eq!(last.phase(), Phase::LastQuarter);
+        assert_eq!(moon(at(2021, 6, 14, 12, 0)).phase(), Phase::WaxingCrescent);
+        assert_eq!(moon(at(2021, 6, 28, 12, 0)).phase(), Phase::WaningGibbous);
+    }
+
+    #[test]
+    fn test_ephemeris() {
+        let noon = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(12, 0, 0).unwrap();
+        let paris = FixedOffset::east_opt(7200).unwrap().from_local_datetime(&noon(2021, 6, 21)).unwrap();
+        let lines = ephemeris(paris, 48.8566, 2.3522);
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("Lever du soleil 05:4"), "{}", lines[0]);
+        assert!(lines[1].starts_with("Durée du jour 16 h 1"), "{}", lines[1]);
+        assert!(lines[2].starts_with("Aube civile 05:0"), "{}", lines[2]);
+        assert!(lines[3].starts_with("Gibbeuse croissante"), "{}", lines[3]);
+        let tromso = ephemeris(Utc.from_utc_datetime(&noon(2021, 12, 21)), 69.6492, 18.9553);
+        assert_eq!(tromso[0], "Nuit polaire");
+        assert_eq!(tromso[1], "Durée du jour 0 h 00");
+    }
+
+    #[test]
+    fn test_parse_coordinates() {
+        assert_eq!(parse_coordinates("50,6292", " 3.0573"), Some((50.6292, 3.0573)));
+        assert_eq!(parse_coordinates("95", "3"), None);
+        assert_eq!(parse_coordinates("", "3"), None);
+        assert_eq!(parse_coordinates("NaN", "3"), None);
+        assert_eq!(parse_coordinates("50", "-inf"), None);
+    }
+}
@@ -0,0 +1,524 @@
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io;
+#[cfg(unix)]
+use std::io::{BufRead, BufReader, Write};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use crate::plugins::state;
+
+pub const POSITIONS_FILE: &str = "podcast_positions.json";
+pub const DEFAULT_PLAYER: &str = "mpv";
+pub const TICK_SECONDS: u64 = 5;
+const VOLUME_STEP: u8 = 10;
+// While an episode plays its position is written to disk at most this often.
+const SAVE_EVERY_SECONDS: f64 = 30.0;
+// The position is read every tick, so the last one known may be that far from the end.
+const END_MARGIN_SECONDS: f64 = 3.0 * TICK_SECONDS as f64;
+
+pub trait AudioBackend: Send {
+    // Starts `url` at `start` seconds, replacing whatever was playing.
+    fn play(&mut self, url: &str, start: f64, volume: u8) -> Result<(), Box<dyn Error>>;
+    fn set_paused(&mut self, paused: bool) -> Result<(), Box<dyn Error>>;
+    // 0 to 100
+    fn set_volume(&mut self, volume: u8) -> Result<(), Box<dyn Error>>;
+    // Seconds from the start of the stream, None while unknown.
+    fn position(&mut self) -> Option<f64>;
+    // Length of the stream in seconds, None for live streams or while unknown.
+    fn duration(&mut self) -> Option<f64>;
+    // Once the player has gone away: Some(true) if it exited cleanly.
+    fn exited(&mut self) -> Option<bool>;
+    fn stop(&mut self);
+}
+
+// mpv, driven through its JSON IPC socket; any player speaking the same protocol will do.
+pub struct Mpv {
+    program: String,
+    socket: PathBuf,
+    child: Option<Child>,
+}
+
+impl Mpv {
+    pub fn new(program: &str, socket: PathBuf) -> Mpv {
+        let program = match program.trim() {
+            "" => DEFAULT_PLAYER,
+            program => program,
+        };
+        Mpv { program: program.to_string(), socket, child: None }
+    }
+
+    // In a directory of the user's own: in the shared /tmp someone else could create the socket first.
+    pub fn socket_path() -> PathBuf {
+        let name = format!("assistant_gui_mpv_{}", std::process::id());
+        match std::env::var_os("XDG_RUNTIME_DIR") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir).join(name),
+            _ => state::data_path(&name),
+        }
+    }
+}
+
+// Sends one command and returns its `data`.
+#[cfg(unix)]
+pub fn ipc_request(socket: &Path, command: Value) -> Result<Value, Box<dyn Error>> {
+    let mut stream = UnixStream::connect(socket)?;
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+    writeln!(stream, "{}", json!({ "command": command }))?;
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err("le lecteur a fermé la connexion".into());
+        }
+        let response: Value = serde_json::from_str(&line)?;
+        // Events go to every client, the reply is the line carrying `error`.
+        if let Some(error) = response.get("error").and_then(Value::as_str) {
+            return match error {
+                "success" => Ok(response["data"].clone()),
+                error => Err(error.to_string().into()),
+            };
+        }
+    }
+}
+
+// mpv listens on a named pipe elsewhere, which is not supported.
+#[cfg(not(unix))]
+pub fn ipc_request(_socket: &Path, _command: Value) -> Result<Value, Box<dyn Error>> {
+    Err("pas de commande du lecteur sur cette plateforme".into())
+}
+
+impl AudioBackend for Mpv {
+    fn play(&mut self, url: &str, start: f64, volume: u8) -> Result<(), Box<dyn Error>> {
+        self.stop();
+        if let Some(dir) = self.socket.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let _ = fs::remove_file(&self.socket);
+        let child = Command::new(&self.program)
+            .arg("--no-video")
+            .arg("--no-terminal")
+            .arg(format!("--input-ipc-server={}", self.socket.display()))
+            .arg(format!("--start=+{:.0}", start))
+            .arg(format!("--volume={}", volume))
+            // Whatever the feed says, the url is never read as an option.
+            .arg("--")
+            .arg(url)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        self.child = Some(child);
+        Ok(())
+    }
+
+    fn set_paused(&mut self, paused: bool) -> Result<(), Box<dyn Error>> {
+        ipc_request(&self.socket, json!(["set_property", "pause", paused])).map(|_| ())
+    }
+
+    fn set_volume(&mut self, volume: u8) -> Result<(), Box<dyn Error>> {
+        ipc_request(&self.socket, json!(["set_property", "volume", volume])).map(|_| ())
+    }
+
+    fn position(&mut self) -> Option<f64> {
+        ipc_request(&self.socket, json!(["get_property", "time-pos"])).ok()?.as_f64()
+    }
+
+    fn duration(&mut self) -> Option<f64> {
+        ipc_request(&self.socket, json!(["get_property", "duration"])).ok()?.as_f64()
+    }
+
+    fn exited(&mut self) -> Option<bool> {
+        match self.child.as_mut()?.try_wait() {
+            Ok(Some(status)) => Some(status.success()),
+            Ok(None) => None,
+            Err(_) => Some(false),
+        }
+    }
+
+    fn stop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        let _ = fs::remove_file(&self.socket);
+    }
+}
+
+impl Drop for Mpv {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+// Where each episode was left, by episode key.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Positions(HashMap<String, f64>);
+
+impl Positions {
+    pub fn load(path: &Path) -> Positions {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .map(Positions)
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(&self.0)?)
+    }
+
+    pub fn get(&self, key: &str) -> Option<f64> {
+        self.0.get(key).copied()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+    pub title: String,
+    pub url: String,
+    // Episodes resume where they were left; live radio has no key.
+    pub resume_key: Option<String>,
+}
+
+pub struct Player {
+    backend: Box<dyn AudioBackend>,
+    positions: Positions,
+    positions_path: PathBuf,
+    track: Option<Track>,
+    paused: bool,
+    volume: u8,
+    position: Option<f64>,
+    duration: Option<f64>,
+    saved_position: f64,
+}
+
+impl std::fmt::Debug for Player {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Player")
+            .field("track", &self.track)
+            .field("paused", &self.paused)
+            .field("volume", &self.volume)
+            .field("position", &self.position)
+            .finish()
+    }
+}
+
+impl Player {
+    pub fn new(backend: Box<dyn AudioBackend>, positions_path: PathBuf, volume: u8) -> Player {
+        Player {
+            backend,
+            positions: Positions::load(&positions_path),
+            positions_path,
+            track: None,
+            paused: false,
+            volume: volume.min(100),
+            position: None,
+            duration: None,
+            saved_position: 0.0,
+        }
+    }
+
+    pub fn play(&mut self, track: Track) -> Result<(), Box<dyn Error>> {
+        self.remember();
+        let start = track.resume_key.as_deref().and_then(|key| self.positions.get(key)).unwrap_or(0.0);
+        self.backend.play(&track.url, start, self.volume)?;
+        self.track = Some(track);
+        self.paused = false;
+        self.position = Some(start);
+        self.duration = None;
+        self.saved_position = start;
+        Ok(())
+    }
+
+    pub fn toggle_pause(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.track.is_none() {
+            return Ok(());
+        }
+        self.backend.set_paused(!self.paused)?;
+        self.paused = !self.paused;
+        if self.paused {
+            self.remember();
+        }
+        Ok(())
+    }
+
+    pub fn stop(&mut self) {
+        self.remember();
+        self.backend.stop();
+        self.track = None;
+        self.paused = false;
+        self.position = None;
+    }
+
+    pub fn change_volume(&mut self, louder: bool) -> Result<(), Box<dyn Error>> {
+        let volume = if louder { self.volume.saturating_add(VOLUME_STEP).min(100) } else { self.volume.saturating_sub(VOLUME_STEP) };
+        if self.track.is_some() {
+            self.backend.set_volume(volume)?;
+        }
+        self.volume = volume;
+        Ok(())
+    }
+
+    // Called every few seconds while something plays.
+    pub fn tick(&mut self) {
+        let key = match &self.track {
+            Some(track) => track.resume_key.clone(),
+            None => return,
+        };
+        if let Some(success) = self.backend.exited() {
+            // A finished episode starts over next time; one cut short by an error or a crash resumes.
+            let at_end = matches!((self.position, self.duration), (Some(position), Some(duration)) if duration - position <= END_MARGIN_SECONDS);
+            match key {
+                Some(key) if success && at_end => {
+                    self.positions.0.remove(&key);
+                    self.save_positions();
+                }
+                _ => self.remember(),
+            }
+            self.track = None;
+            self.position = None;
+            return;
+        }
+        if self.duration.is_none() {
+            self.duration = self.backend.duration();
+        }
+        if let Some(position) = self.backend.position() {
+            self.position = Some(position);
+            if (position - self.saved_position).abs() >= SAVE_EVERY_SECONDS {
+                self.remember();
+            }
+        }
+    }
+
+    fn remember(&mut self) {
+        let key = match self.track.as_ref().and_then(|track| track.resume_key.clone()) {
+            Some(key) => key,
+            None => return,
+        };
+        if let Some(position) = self.backend.position().or(self.position) {
+            self.position = Some(position);
+            self.saved_position = position;
+            self.positions.0.insert(key, position);
+            self.save_positions();
+        }
+    }
+
+    fn save_positions(&self) {
+        if let Err(e) = self.positions.save(&self.positions_path) {
+            log::warn!("Impossible d'enregistrer les positions de lecture : {}", e);
+        }
+    }
+
+    pub fn track(&self) -> Option<&Track> {
+        self.track.as_ref()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn volume(&self) -> u8 {
+        self.volume
+    }
+
+    pub fn position(&self) -> Option<f64> {
+        self.position
+    }
+
+    pub fn saved_position(&self, key: &str) -> Option<f64> {
+        self.positions.get(key)
+    }
+}
+
+pub fn format_position(seconds: f64) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    if seconds >= 3600 {
+        format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(unix)]
+    use std::os::unix::net::UnixListener;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Fake {
+        calls: Arc<Mutex<Vec<String>>>,
+        position: Arc<Mutex<Option<f64>>>,
+        duration: Arc<Mutex<Option<f64>>>,
+        exit: Arc<Mutex<Option<bool>>>,
+    }
+
+    impl AudioBackend for Fake {
+        fn play(&mut self, url: &str, start: f64, volume: u8) -> Result<(), Box<dyn Error>> {
+            self.calls.lock().unwrap().push(format!("play {} {} {}", url, start, volume));
+            *self.position.lock().unwrap() = Some(start);
+            Ok(())
+        }
+
+        fn set_paused(&mut self, paused: bool) -> Result<(), Box<dyn Error>> {
+            self.calls.lock().unwrap().push(format!("pause {}", paused));
+            Ok(())
+        }
+
+        fn set_volume(&mut self, volume: u8) -> Result<(), Box<dyn Error>> {
+            self.calls.lock().unwrap().push(format!("volume {}", volume));
+            Ok(())
+        }
+
+        fn position(&mut self) -> Option<f64> {
+            *self.position.lock().unwrap()
+        }
+
+        fn duration(&mut self) -> Option<f64> {
+            *self.duration.lock().unwrap()
+        }
+
+        fn exited(&mut self) -> Option<bool> {
+            *self.exit.lock().unwrap()
+        }
+
+        fn stop(&mut self) {
+            self.calls.lock().unwrap().push("stop".to_string());
+        }
+    }
+
+    fn episode() -> Track {
+        Track {
+            title: "Épisode 12".to_string(),
+            url: "http://example.org/12.mp3".to_string(),
+            resume_key: Some("guid-12".to_string()),
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("assistant_gui_audio_{}_{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_player_remembers_position() {
+        let path = temp_path("positions").join(POSITIONS_FILE);
+        let fake = Fake::default();
+        let (calls, position, exit) = (fake.calls.clone(), fake.position.clone(), fake.exit.clone());
+        let mut player = Player::new(Box::new(fake), path.clone(), 70);
+
+        player.play(episode()).unwrap();
+        *position.lock().unwrap() = Some(125.0);
+        player.tick();
+        assert_eq!(player.position(), Some(125.0));
+        player.toggle_pause().unwrap();
+        assert!(player.is_paused());
+        player.stop();
+        assert!(player.track().is_none());
+        assert_eq!(Positions::load(&path).get("guid-12"), Some(125.0));
+
+        // A new player, as after a restart, resumes the episode.
+        let fake = Fake::default();
+        let restarted_calls = fake.calls.clone();
+        let (restarted_position, duration, restarted_exit) = (fake.position.clone(), fake.duration.clone(), fake.exit.clone());
+        *duration.lock().unwrap() = Some(1800.0);
+        let mut player = Player::new(Box::new(fake), path.clone(), 70);
+        player.play(episode()).unwrap();
+        assert_eq!(restarted_calls.lock().unwrap()[0], "play http://example.org/12.mp3 125 70");
+
+        // The player failing halfway keeps the last position known.
+        *restarted_position.lock().unwrap() = Some(600.0);
+        player.tick();
+        *restarted_position.lock().unwrap() = None;
+        *restarted_exit.lock().unwrap() = Some(false);
+        player.tick();
+        assert!(player.track().is_none());
+        assert_eq!(player.saved_position("guid-12"), Some(600.0));
+
+        // So does a clean exit far from the end, as when the stream is cut.
+        *restarted_exit.lock().unwrap() = None;
+        player.play(episode()).unwrap();
+        player.tick();
+        *restarted_exit.lock().unwrap() = Some(true);
+        player.tick();
+        assert_eq!(player.saved_position("guid-12"), Some(600.0));
+
+        // Played to the end, it starts over.
+        *restarted_exit.lock().unwrap() = None;
+        player.play(episode()).unwrap();
+        *restarted_position.lock().unwrap() = Some(1795.0);
+        player.tick();
+        *restarted_position.lock().unwrap() = None;
+        *restarted_exit.lock().unwrap() = Some(true);
+        player.tick();
+        assert!(player.track().is_none());
+        assert_eq!(player.saved_position("guid-12"), None);
+
+        assert_eq!(*calls.lock().unwrap(), vec!["play http://example.org/12.mp3 0 70", "pause true", "stop"]);
+        assert_eq!(*exit.lock().unwrap(), None);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_player_volume_and_radio() {
+        let fake = Fake::default();
+        let calls = fake.calls.clone();
+        let mut player = Player::new(Box::new(fake), temp_path("radio").join(POSITIONS_FILE), 95);
+        player.change_volume(true).unwrap();
+        assert_eq!(player.volume(), 100);
+        player.play(Track { title: "FIP".to_string(), url: "http://example.org/fip".to_string(), resume_key: None }).unwrap();
+        player.change_volume(false).unwrap();
+        player.stop();
+        assert_eq!(*calls.lock().unwrap(), vec!["play http://example.org/fip 0 100", "volume 90", "stop"]);
+        assert!(!temp_path("radio").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_ipc_request() {
+        let socket = temp_path("socket");
+        let _ = fs::remove_file(&socket);
+        let listener = UnixListener::bind(&socket).unwrap();
+        let server = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for reply in [r#"{"data":42.5,"error":"success"}"#, r#"{"error":"property unavailable"}"#].iter() {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                requests.push(line.trim().to_string());
+                let mut stream = stream;
+                writeln!(stream, r#"{{"event":"playback-restart"}}"#).unwrap();
+                writeln!(stream, "{}", reply).unwrap();
+            }
+            requests
+        });
+
+        let mut mpv = Mpv::new("", socket.clone());
+        assert_eq!(mpv.position(), Some(42.5));
+        assert_eq!(mpv.set_paused(true).unwrap_err().to_string(), "property unavailable");
+        assert_eq!(server.join().unwrap(), vec![
+            r#"{"command":["get_property","time-pos"]}"#,
+            r#"{"command":["set_property","pause",true]}"#,
+        ]);
+        assert_eq!(mpv.exited(), None);
+        assert_eq!(mpv.program, DEFAULT_PLAYER);
+        drop(mpv);
+        assert!(!socket.exists());
+    }
+
+    #[test]
+    fn test_format_position() {
+        assert_eq!(format_position(75.4), "1:15");
+        assert_eq!(format_position(3723.0), "1:02:03");
+    }
+}
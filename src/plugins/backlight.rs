@@ -0,0 +1,92 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const SYSFS_ROOT: &str = "/";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backlight {
+    path: PathBuf,
+}
+
+impl Backlight {
+    // Picks the first device of `<root>/sys/class/backlight`, so tests can point `root` at a fake sysfs tree.
+    pub fn find(root: &Path) -> Option<Backlight> {
+        let mut devices: Vec<PathBuf> = fs::read_dir(root.join("sys/class/backlight"))
+            .ok()?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.join("max_brightness").exists())
+            .collect();
+        devices.sort();
+        devices.into_iter().next().map(|path| Backlight { path })
+    }
+
+    fn read(&self, file_name: &str) -> io::Result<u32> {
+        fs::read_to_string(self.path.join(file_name))?
+            .trim()
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn max_brightness(&self) -> io::Result<u32> {
+        self.read("max_brightness")
+    }
+
+    pub fn brightness(&self) -> io::Result<u32> {
+        self.read("brightness")
+    }
+
+    pub fn percent(&self) -> io::Result<u8> {
+        let max = self.max_brightness()?.max(1);
+        Ok(((self.brightness()? as u64 * 100 + max as u64 / 2) / max as u64).min(100) as u8)
+    }
+
+    pub fn set_percent(&self, percent: u8) -> io::Result<()> {
+        let max = self.max_brightness()? as u64;
+        let value = (max * percent.min(100) as u64 + 50) / 100;
+        fs::write(self.path.join("brightness"), value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_sysfs(name: &str, devices: &[(&str, u32, u32)]) -> PathBuf {
+        let root = std::env::temp_dir().join(["assistant_gui_backlight_", name, "_", &std::process::id().to_string()].concat());
+        let _ = fs::remove_dir_all(&root);
+        for (device, brightness, max_brightness) in devices {
+            let path = root.join("sys/class/backlight").join(device);
+            fs::create_dir_all(&path).unwrap();
+            fs::write(path.join("brightness"), [brightness.to_string(), "\n".to_string()].concat()).unwrap();
+            fs::write(path.join("max_brightness"), [max_brightness.to_string(), "\n".to_string()].concat()).unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn test_find_and_read() {
+        let root = fake_sysfs("read", &[("rpi_backlight", 128, 255), ("10-0045", 10, 31)]);
+        let backlight = Backlight::find(&root).unwrap();
+        assert_eq!(backlight.max_brightness().unwrap(), 31);
+        assert_eq!(backlight.percent().unwrap(), 32);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_set_percent() {
+        let root = fake_sysfs("write", &[("rpi_backlight", 255, 255)]);
+        let backlight = Backlight::find(&root).unwrap();
+        backlight.set_percent(20).unwrap();
+        assert_eq!(backlight.brightness().unwrap(), 51);
+        backlight.set_percent(150).unwrap();
+        assert_eq!(backlight.brightness().unwrap(), 255);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_find_without_backlight() {
+        let root = fake_sysfs("missing", &[]);
+        assert!(Backlight::find(&root).is_none());
+    }
+}
@@ -0,0 +1,407 @@
+use reqwest::blocking::Client;
+use reqwest::{Method, StatusCode};
+use std::collections::HashMap;
+use std::error::Error;
+
+use crate::plugins::calendar::{self, CalendarEvent};
+
+const DAV: &str = "DAV:";
+const CALDAV: &str = "urn:ietf:params:xml:ns:caldav";
+
+const PROPFIND_SYNC_TOKEN: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:">
+  <d:prop>
+    <d:displayname/>
+    <d:sync-token/>
+  </d:prop>
+</d:propfind>"#;
+
+const REPORT_CALENDAR_QUERY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <d:getetag/>
+    <c:calendar-data/>
+  </d:prop>
+  <c:filter>
+    <c:comp-filter name="VCALENDAR">
+      <c:comp-filter name="VEVENT"/>
+    </c:comp-filter>
+  </c:filter>
+</c:calendar-query>"#;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CalDavAccount {
+    pub url: String,
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CalDavCache {
+    pub sync_token: Option<String>,
+    pub resources: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Resource {
+    href: String,
+    status: u16,
+    calendar_data: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+struct MultiStatus {
+    sync_token: Option<String>,
+    resources: Vec<Resource>,
+}
+
+impl CalDavAccount {
+    pub fn is_configured(&self) -> bool {
+        !self.url.trim().is_empty()
+    }
+
+    fn request(&self, client: &Client, method: &str, url: &str, depth: &str, body: String) -> Result<(StatusCode, String), Box<dyn Error>> {
+        let mut request = client
+            .request(Method::from_bytes(method.as_bytes())?, url)
+            .header("Depth", depth)
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .body(body);
+        if !self.username.is_empty() {
+            request = request.basic_auth(&self.username, Some(&self.password));
+        }
+        let response = request.send()?;
+        let status = response.status();
+        Ok((status, response.text()?))
+    }
+
+    fn get(&self, client: &Client, url: &str) -> Result<String, Box<dyn Error>> {
+        let mut request = client.get(url);
+        if !self.username.is_empty() {
+            request = request.basic_auth(&self.username, Some(&self.password));
+        }
+        Ok(request.send()?.error_for_status()?.text()?)
+    }
+
+    fn resolve(&self, href: &str) -> Result<String, Box<dyn Error>> {
+        Ok(reqwest::Url::parse(self.url.trim())?.join(href)?.to_string())
+    }
+}
+
+fn sync_collection_body(sync_token: &str) -> String {
+    [
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<d:sync-collection xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:sync-token>"#,
+        &escape(sync_token),
+        r#"</d:sync-token>
+  <d:sync-level>1</d:sync-level>
+  <d:prop>
+    <d:getetag/>
+    <c:calendar-data/>
+  </d:prop>
+</d:sync-collection>"#,
+    ]
+    .concat()
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn parse_status(status: &str) -> u16 {
+    status
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .unwrap_or(0)
+}
+
+fn parse_multistatus(xml: &str) -> Result<MultiStatus, Box<dyn Error>> {
+    let document = roxmltree::Document::parse(xml)?;
+    let root = document.root_element();
+    let mut multistatus = MultiStatus::default();
+
+    for node in root.children().filter(|n| n.is_element()) {
+        if node.has_tag_name((DAV, "sync-token")) {
+            multistatus.sync_token = node.text().map(|t| t.trim().to_string());
+        }
+        if !node.has_tag_name((DAV, "response")) {
+            continue;
+        }
+        let href = node
+            .children()
+            .find(|n| n.has_tag_name((DAV, "href")))
+            .and_then(|n| n.text())
+            .map(|t| t.trim().to_string())
+            .unwrap_or_default();
+        let mut status = node
+            .children()
+            .find(|n| n.has_tag_name((DAV, "status")))
+            .and_then(|n| n.text())
+            .map(parse_status)
+            .unwrap_or(0);
+        let mut calendar_data = None;
+        for propstat in node.children().filter(|n| n.has_tag_name((DAV, "propstat"))) {
+            let propstat_status = propstat
+                .children()
+                .find(|n| n.has_tag_name((DAV, "status")))
+                .and_then(|n| n.text())
+                .map(parse_status)
+                .unwrap_or(0);
+            if propstat_status != 200 {
+                continue;
+            }
+            status = 200;
+            calendar_data = propstat
+                .descendants()
+                .find(|n| n.has_tag_name((CALDAV, "calendar-data")))
+                .and_then(|n| n.text())
+                .map(|t| t.to_string())
+                .or(calendar_data);
+            if let Some(token) = propstat
+                .descendants()
+                .find(|n| n.has_tag_name((DAV, "sync-token")))
+                .and_then(|n| n.text())
+            {
+                multistatus.sync_token = Some(token.trim().to_string());
+            }
+        }
+        multistatus.resources.push(Resource { href, status, calendar_data });
+    }
+
+    Ok(multistatus)
+}
+
+fn sync_token(account: &CalDavAccount, client: &Client) -> Result<Option<String>, Box<dyn Error>> {
+    let (status, body) = account.request(client, "PROPFIND", account.url.trim(), "0", PROPFIND_SYNC_TOKEN.to_string())?;
+    if !status.is_success() {
+        return Err(format!("PROPFIND a échoué : {}", status).into());
+    }
+    Ok(parse_multistatus(&body)?.sync_token)
+}
+
+fn full_sync(account: &CalDavAccount, client: &Client, cache: &mut CalDavCache) -> Result<(), Box<dyn Error>> {
+    let token = sync_token(account, client)?;
+    let (status, body) = account.request(client, "REPORT", account.url.trim(), "1", REPORT_CALENDAR_QUERY.to_string())?;
+    if !status.is_success() {
+        return Err(format!("REPORT calendar-query a échoué : {}", status).into());
+    }
+    cache.resources = parse_multistatus(&body)?
+        .resources
+        .into_iter()
+        .filter(|resource| resource.status == 200)
+        .filter_map(|resource| {
+            let href = resource.href;
+            resource.calendar_data.map(|data| (href, data))
+        })
+        .collect();
+    cache.sync_token = token;
+    Ok(())
+}
+
+// Returns Ok(false) when the server refused the sync token and a full sync is needed.
+fn incremental_sync(account: &CalDavAccount, client: &Client, cache: &mut CalDavCache, token: &str) -> Result<bool, Box<dyn Error>> {
+    let (status, body) = account.request(client, "REPORT", account.url.trim(), "1", sync_collection_body(token))?;
+    if status == StatusCode::FORBIDDEN || status == StatusCode::CONFLICT || status == StatusCode::PRECONDITION_FAILED {
+        return Ok(false);
+    }
+    if !status.is_success() {
+        return Err(format!("REPORT sync-collection a échoué : {}", status).into());
+    }
+    let multistatus = parse_multistatus(&body)?;
+    for resource in multistatus.resources {
+        if resource.status == 404 {
+            cache.resources.remove(&resource.href);
+        } else if resource.status == 200 {
+            let data = match resource.calendar_data {
+                Some(data) => data,
+                None => account.get(client, &account.resolve(&resource.href)?)?,
+            };
+            cache.resources.insert(resource.href, data);
+        }
+    }
+    cache.sync_token = multistatus.sync_token.or_else(|| Some(token.to_string()));
+    Ok(true)
+}
+
+pub fn synchronize(account: &CalDavAccount, cache: &mut CalDavCache) -> Result<(), Box<dyn Error>> {
+    let client = Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()?;
+    if let Some(token) = cache.sync_token.clone() {
+        if incremental_sync(account, &client, cache, &token)? {
+            return Ok(());
+        }
+    }
+    full_sync(account, &client, cache)
+}
+
+// The requests block: run them on a thread of their own and hand back the updated cache.
+pub async fn synchronize_in_background(account: CalDavAccount, mut cache: CalDavCache) -> Result<CalDavCache, String> {
+    async_std::task::spawn_blocking(move || synchronize(&account, &mut cache).map(|_| cache).map_err(|e| e.to_string())).await
+}
+
+pub fn events(cache: &CalDavCache) -> Vec<CalendarEvent> {
+    let mut hrefs: Vec<&String> = cache.resources.keys().collect();
+    hrefs.sort();
+    hrefs
+        .into_iter()
+        .filter_map(|href| calendar::parse_ics(cache.resources[href].as_bytes()).ok())
+        .flatten()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    fn event(uid: &str, summary: &str) -> String {
+        [
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:", uid,
+            "\r\nSUMMARY:", summary,
+            "\r\nDTSTART:20210104T090000\r\nDTEND:20210104T100000\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+        ]
+        .concat()
+    }
+
+    fn response(href: &str, data: &str) -> String {
+        [
+            "<d:response><d:href>", href,
+            "</d:href><d:propstat><d:prop><d:getetag>\"1\"</d:getetag><c:calendar-data>", data,
+            "</c:calendar-data></d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>",
+        ]
+        .concat()
+    }
+
+    fn multistatus(body: &str) -> String {
+        [
+            r#"<?xml version="1.0"?><d:multistatus xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">"#,
+            body,
+            "</d:multistatus>",
+        ]
+        .concat()
+    }
+
+    // A stand-in CalDAV server answering with canned multistatus documents.
+    fn serve(requests: Arc<Mutex<Vec<String>>>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                let mut authorized = false;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let lower = line.to_lowercase();
+                    if let Some(value) = lower.strip_prefix("content-length:") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                    if lower.starts_with("authorization: basic") {
+                        authorized = true;
+                    }
+                    if line == "\r\n" {
+                        break;
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let body = String::from_utf8(body).unwrap();
+                let method = request_line.split_whitespace().next().unwrap().to_string();
+                requests.lock().unwrap().push([method.as_str(), " ", &body].concat());
+
+                let (status, answer) = if !authorized {
+                    ("401 Unauthorized", String::new())
+                } else if method == "PROPFIND" {
+                    ("207 Multi-Status", multistatus(r#"<d:response><d:href>/cal/</d:href><d:propstat><d:prop><d:sync-token>token-1</d:sync-token></d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>"#))
+                } else if body.contains("calendar-query") {
+                    ("207 Multi-Status", multistatus(&[response("/cal/a.ics", &event("a", "Premier")), response("/cal/b.ics", &event("b", "Second"))].concat()))
+                } else if body.contains("token-1") {
+                    ("207 Multi-Status", multistatus(&[
+                        response("/cal/c.ics", &event("c", "Troisième")),
+                        "<d:response><d:href>/cal/a.ics</d:href><d:status>HTTP/1.1 404 Not Found</d:status></d:response>".to_string(),
+                        "<d:sync-token>token-2</d:sync-token>".to_string(),
+                    ].concat()))
+                } else {
+                    ("403 Forbidden", String::new())
+                };
+                let reply = ["HTTP/1.1 ", status, "\r\nContent-Type: application/xml\r\nContent-Length: ", &answer.len().to_string(), "\r\nConnection: close\r\n\r\n", &answer].concat();
+                stream.write_all(reply.as_bytes()).unwrap();
+            }
+        });
+        ["http://", &address.to_string(), "/cal/"].concat()
+    }
+
+    fn summaries(cache: &CalDavCache) -> Vec<String> {
+        events(cache).into_iter().map(|e| e.summary).collect()
+    }
+
+    #[test]
+    fn test_parse_multistatus() {
+        let parsed = parse_multistatus(&multistatus(&[
+            response("/cal/a.ics", "DATA"),
+            "<d:response><d:href>/cal/b.ics</d:href><d:status>HTTP/1.1 404 Not Found</d:status></d:response>".to_string(),
+            "<d:sync-token>http://example.com/sync/2</d:sync-token>".to_string(),
+        ].concat())).unwrap();
+        assert_eq!(parsed.sync_token, Some("http://example.com/sync/2".to_string()));
+        assert_eq!(parsed.resources[0].calendar_data, Some("DATA".to_string()));
+        assert_eq!(parsed.resources[1].status, 404);
+    }
+
+    #[test]
+    fn test_synchronize() {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let account = CalDavAccount {
+            url: serve(requests.clone()),
+            username: "julien".to_string(),
+            password: "secret".to_string(),
+        };
+        let mut cache = CalDavCache::default();
+
+        synchronize(&account, &mut cache).unwrap();
+        assert_eq!(cache.sync_token, Some("token-1".to_string()));
+        assert_eq!(summaries(&cache), vec!["Premier", "Second"]);
+
+        synchronize(&account, &mut cache).unwrap();
+        assert_eq!(cache.sync_token, Some("token-2".to_string()));
+        assert_eq!(summaries(&cache), vec!["Second", "Troisième"]);
+
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].starts_with("PROPFIND"));
+        assert!(requests[1].starts_with("REPORT") && requests[1].contains("calendar-query"));
+        assert!(requests[2].starts_with("REPORT") && requests[2].contains("sync-collection"));
+    }
+
+    #[test]
+    fn test_synchronize_without_credentials() {
+        let account = CalDavAccount {
+            url: serve(Arc::new(Mutex::new(Vec::new()))),
+            ..CalDavAccount::default()
+        };
+        assert!(synchronize(&account, &mut CalDavCache::default()).is_err());
+    }
+
+    #[test]
+    fn test_synchronize_in_background() {
+        let account = CalDavAccount {
+            url: serve(Arc::new(Mutex::new(Vec::new()))),
+            username: "julien".to_string(),
+            password: "secret".to_string(),
+        };
+        let cache = async_std::task::block_on(synchronize_in_background(account.clone(), CalDavCache::default())).unwrap();
+        assert_eq!(summaries(&cache), vec!["Premier", "Second"]);
+
+        let account = CalDavAccount { username: String::new(), ..account };
+        assert!(async_std::task::block_on(synchronize_in_background(account, cache)).is_err());
+    }
+}
@@ -0,0 +1,704 @@
+use chrono::prelude::*;
+use chrono::Duration;
+use ical::parser::ical::component::IcalEvent;
+use ical::property::Property;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<NaiveDateTime>,
+    // Weekdays with their position in the month or the year: 0 for all of them, -1 for the last one.
+    pub by_day: Vec<(i32, Weekday)>,
+    pub by_month: Vec<u32>,
+    pub by_month_day: Vec<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub summary: String,
+    pub location: Option<String>,
+    pub start: NaiveDateTime,
+    pub duration: Duration,
+    pub all_day: bool,
+    pub rrule: Option<RecurrenceRule>,
+    pub exdates: Vec<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Occurrence {
+    pub summary: String,
+    pub location: Option<String>,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub all_day: bool,
+}
+
+pub fn parse_paths(value: &str) -> Vec<PathBuf> {
+    value
+        .split(';')
+        .map(|path| path.trim())
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+// Each day is expanded again on every frame, so the page shows a month at most.
+pub const MAX_DAYS: i64 = 31;
+
+pub fn parse_days(value: &str) -> i64 {
+    value.trim().parse::<i64>().unwrap_or(7).clamp(1, MAX_DAYS)
+}
+
+pub fn last_modified(paths: &[PathBuf]) -> Option<SystemTime> {
+    paths
+        .iter()
+        .filter_map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        .max()
+}
+
+// A missing or broken file is reported without hiding the other calendars.
+pub fn load_calendars(paths: &[PathBuf]) -> (Vec<CalendarEvent>, Vec<String>) {
+    let mut events = Vec::new();
+    let mut errors = Vec::new();
+    for path in paths {
+        let loaded = File::open(path)
+            .map_err(|e| e.into())
+            .and_then(|file| parse_ics(BufReader::new(file)));
+        match loaded {
+            Ok(mut loaded) => events.append(&mut loaded),
+            Err(e) => errors.push([path.display().to_string(), " : ".to_string(), e.to_string()].concat()),
+        }
+    }
+    (events, errors)
+}
+
+pub fn parse_ics<B: std::io::BufRead>(reader: B) -> Result<Vec<CalendarEvent>, Box<dyn Error>> {
+    let mut events = Vec::new();
+    for calendar in ical::IcalParser::new(reader) {
+        for event in calendar?.events {
+            if let Some(event) = parse_event(&event) {
+                events.push(event);
+            }
+        }
+    }
+    Ok(events)
+}
+
+fn parse_event(event: &IcalEvent) -> Option<CalendarEvent> {
+    let property = |name: &str| event.properties.iter().find(|p| p.name == name);
+    let value = |name: &str| property(name).and_then(|p| p.value.clone());
+
+    let dtstart = property("DTSTART")?;
+    let (start, all_day) = parse_date_property(dtstart)?;
+    let duration = match property("DTEND").and_then(parse_date_property) {
+        Some((end, _)) => end - start,
+        None => match value("DURATION").and_then(|d| parse_duration(&d)) {
+            Some(duration) => duration,
+            None if all_day => Duration::days(1),
+            None => Duration::zero(),
+        },
+    };
+
+    let exdates = event
+        .properties
+        .iter()
+        .filter(|p| p.name == "EXDATE")
+        .flat_map(|p| {
+            let is_date = is_date_value(p);
+            p.value
+                .clone()
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|v| parse_date_time(v, is_date).map(|(d, _)| d))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    Some(CalendarEvent {
+        uid: value("UID").unwrap_or_default(),
+        summary: unescape(&value("SUMMARY").unwrap_or_default()),
+        location: value("LOCATION").map(|l| unescape(&l)),
+        start,
+        duration,
+        all_day,
+        rrule: value("RRULE").and_then(|r| {
+            let rule = parse_rrule(&r);
+            if rule.is_none() {
+                log::warn!("Récurrence non prise en charge, seule la première date est affichée : {}", r);
+            }
+            rule
+        }),
+        exdates,
+    })
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+fn is_date_value(property: &Property) -> bool {
+    property
+        .params
+        .as_ref()
+        .map(|params| {
+            params
+                .iter()
+                .any(|(name, values)| name == "VALUE" && values.iter().any(|v| v == "DATE"))
+        })
+        .unwrap_or(false)
+}
+
+fn parse_date_property(property: &Property) -> Option<(NaiveDateTime, bool)> {
+    parse_date_time(property.value.as_ref()?, is_date_value(property))
+}
+
+// Times carrying a TZID are taken as local time; UTC times are converted to local time.
+fn parse_date_time(value: &str, is_date: bool) -> Option<(NaiveDateTime, bool)> {
+    let value = value.trim();
+    if is_date || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some((date.and_hms_opt(0, 0, 0).unwrap(), true));
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let date_time = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some((Local.from_utc_datetime(&date_time).naive_local(), false));
+    }
+    let date_time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    Some((date_time, false))
+}
+
+fn parse_duration(value: &str) -> Option<Duration> {
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(v) => (true, v),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    // Summed in seconds so that an absurd value is rejected instead of overflowing.
+    let mut seconds: i64 = 0;
+    let mut number = String::new();
+    for c in value.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            _ => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                let unit = match c {
+                    'W' => 7 * 86_400,
+                    'D' => 86_400,
+                    'H' => 3_600,
+                    'M' => 60,
+                    'S' => 1,
+                    _ => return None,
+                };
+                seconds = seconds.checked_add(n.checked_mul(unit)?)?;
+            }
+        }
+    }
+    let duration = Duration::milliseconds(seconds.checked_mul(1_000)?);
+    Some(if negative { -duration } else { duration })
+}
+
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    match value {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+// "2TU", "-1SU" or "MO"
+fn parse_by_day(value: &str) -> Option<(i32, Weekday)> {
+    let split = value.len().checked_sub(2).filter(|split| value.is_char_boundary(*split))?;
+    let (ordinal, day) = value.split_at(split);
+    let ordinal = match ordinal {
+        "" => 0,
+        ordinal => ordinal.parse().ok().filter(|n: &i32| *n != 0 && n.abs() <= 53)?,
+    };
+    Some((ordinal, parse_weekday(day)?))
+}
+
+fn parse_list<T: std::str::FromStr>(value: &str, valid: impl Fn(&T) -> bool) -> Option<Vec<T>> {
+    value.split(',').map(|item| item.trim().parse().ok().filter(|item| valid(item))).collect()
+}
+
+// Returns None for the parts this calendar does not expand (BYSETPOS, BYWEEKNO...), rather than
+// showing wrong dates.
+pub fn parse_rrule(value: &str) -> Option<RecurrenceRule> {
+    let mut rule = RecurrenceRule {
+        frequency: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        by_day: Vec::new(),
+        by_month: Vec::new(),
+        by_month_day: Vec::new(),
+    };
+    let mut frequency = None;
+    for part in value.split(';') {
+        let mut key_value = part.splitn(2, '=');
+        let key = key_value.next()?;
+        let value = key_value.next().unwrap_or("");
+        match key {
+            "FREQ" => {
+                frequency = match value {
+                    "DAILY" => Some(Frequency::Daily),
+                    "WEEKLY" => Some(Frequency::Weekly),
+                    "MONTHLY" => Some(Frequency::Monthly),
+                    "YEARLY" => Some(Frequency::Yearly),
+                    _ => None,
+                }
+            }
+            "INTERVAL" => rule.interval = value.parse().ok().filter(|i| *i > 0)?,
+            "COUNT" => rule.count = value.parse().ok(),
+            "UNTIL" => rule.until = parse_date_time(value, false).map(|(d, all_day)| {
+                if all_day { d + Duration::days(1) - Duration::seconds(1) } else { d }
+            }),
+            "BYDAY" => rule.by_day = value.split(',').map(|d| parse_by_day(d.trim())).collect::<Option<_>>()?,
+            "BYMONTH" => rule.by_month = parse_list(value, |m: &u32| (1..=12).contains(m))?,
+            "BYMONTHDAY" => rule.by_month_day = parse_list(value, |d: &i32| *d != 0 && d.abs() <= 31)?,
+            "BYSETPOS" | "BYYEARDAY" | "BYWEEKNO" | "BYHOUR" | "BYMINUTE" | "BYSECOND" => return None,
+            _ => {}
+        }
+    }
+    rule.frequency = frequency?;
+    // Positions only make sense within a month or a year.
+    if matches!(rule.frequency, Frequency::Daily | Frequency::Weekly) && rule.by_day.iter().any(|(ordinal, _)| *ordinal != 0) {
+        return None;
+    }
+    Some(rule)
+}
+
+// Duration::days panics past about 10^14 days.
+fn days(n: i64) -> Option<Duration> {
+    n.checked_mul(86_400_000).map(Duration::milliseconds)
+}
+
+fn month_length(year: i32, month: u32) -> u32 {
+    (28..=31).rev().find(|day| NaiveDate::from_ymd_opt(year, month, *day).is_some()).unwrap_or(28)
+}
+
+// Whether `date` is one of the BYDAY weekdays, counting positions from `first` over `length` days.
+fn matches_by_day(by_day: &[(i32, Weekday)], date: NaiveDate, first: NaiveDate, length: i64) -> bool {
+    let offset = (date - first).num_days();
+    let from_start = offset / 7 + 1;
+    let from_end = -((length - 1 - offset) / 7 + 1);
+    by_day.iter().any(|(ordinal, weekday)| {
+        *weekday == date.weekday() && (*ordinal == 0 || *ordinal as i64 == from_start || *ordinal as i64 == from_end)
+    })
+}
+
+fn matches_month_day(by_month_day: &[i32], date: NaiveDate) -> bool {
+    let length = month_length(date.year(), date.month()) as i32;
+    by_month_day.iter().any(|day| *day == date.day() as i32 || length + 1 + *day == date.day() as i32)
+}
+
+impl RecurrenceRule {
+    // Dates of a month matching BYMONTHDAY and BYDAY, or the day of DTSTART without them.
+    fn month_dates(&self, start: NaiveDate, year: i32, month: u32) -> Vec<NaiveDate> {
+        let first = match NaiveDate::from_ymd_opt(year, month, 1) {
+            Some(first) => first,
+            None => return Vec::new(),
+        };
+        if self.by_month_day.is_empty() && self.by_day.is_empty() {
+            return NaiveDate::from_ymd_opt(year, month, start.day()).into_iter().collect();
+        }
+        let length = month_length(year, month);
+        (1..=length)
+            .filter_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+            .filter(|date| self.by_month_day.is_empty() || matches_month_day(&self.by_month_day, *date))
+            .filter(|date| self.by_day.is_empty() || matches_by_day(&self.by_day, *date, first, length as i64))
+            .collect()
+    }
+
+    fn keeps(&self, date: NaiveDate) -> bool {
+        (self.by_month.is_empty() || self.by_month.contains(&date.month()))
+            && (self.by_month_day.is_empty() || matches_month_day(&self.by_month_day, date))
+    }
+
+    // First day of the `k`th period after DTSTART and the dates it holds.
+    fn period(&self, start: NaiveDate, k: i64) -> Option<(NaiveDate, Vec<NaiveDate>)> {
+        let step = k.checked_mul(self.interval as i64)?;
+        match self.frequency {
+            Frequency::Daily => {
+                let date = start.checked_add_signed(days(step)?)?;
+                let weekdays: Vec<Weekday> = self.by_day.iter().map(|(_, weekday)| *weekday).collect();
+                let kept = self.keeps(date) && (weekdays.is_empty() || weekdays.contains(&date.weekday()));
+                Some((date, if kept { vec![date] } else { Vec::new() }))
+            }
+            Frequency::Weekly => {
+                let monday = start - Duration::days(start.weekday().num_days_from_monday() as i64);
+                let week = monday.checked_add_signed(days(step.checked_mul(7)?)?)?;
+                let mut weekdays: Vec<i64> = if self.by_day.is_empty() {
+                    vec![start.weekday().num_days_from_monday() as i64]
+                } else {
+                    self.by_day.iter().map(|(_, d)| d.num_days_from_monday() as i64).collect()
+                };
+                weekdays.sort_unstable();
+                let dates = weekdays.into_iter().map(|d| week + Duration::days(d)).filter(|date| self.keeps(*date)).collect();
+                Some((week, dates))
+            }
+            Frequency::Monthly => {
+                let index = (start.year() as i64 * 12 + start.month0() as i64).checked_add(step)?;
+                let (year, month) = (i32::try_from(index.div_euclid(12)).ok()?, index.rem_euclid(12) as u32 + 1);
+                let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+                let dates = if self.by_month.is_empty() || self.by_month.contains(&month) {
+                    self.month_dates(start, year, month)
+                } else {
+                    Vec::new()
+                };
+                Some((first, dates))
+            }
+            Frequency::Yearly => {
+                let year = i32::try_from((start.year() as i64).checked_add(step)?).ok()?;
+                let first = NaiveDate::from_ymd_opt(year, 1, 1)?;
+                let dates = if self.by_month.is_empty() && self.by_month_day.is_empty() && !self.by_day.is_empty() {
+                    // BYDAY alone counts positions over the whole year.
+                    let length = (NaiveDate::from_ymd_opt(year + 1, 1, 1)? - first).num_days();
+                    (0..length)
+                        .map(|day| first + Duration::days(day))
+                        .filter(|date| matches_by_day(&self.by_day, *date, first, length))
+                        .collect()
+                } else if self.by_month.is_empty() {
+                    self.month_dates(start, year, start.month())
+                } else {
+                    self.by_month.iter().flat_map(|month| self.month_dates(start, year, *month)).collect()
+                };
+                Some((first, dates))
+            }
+        }
+    }
+
+    // The period holding `date`, counted from the one holding DTSTART.
+    fn period_index(&self, start: NaiveDate, date: NaiveDate) -> i64 {
+        let periods = match self.frequency {
+            Frequency::Daily => (date - start).num_days(),
+            Frequency::Weekly => (date - start + Duration::days(start.weekday().num_days_from_monday() as i64)).num_days().div_euclid(7),
+            Frequency::Monthly => (date.year() as i64 - start.year() as i64) * 12 + date.month0() as i64 - start.month0() as i64,
+            Frequency::Yearly => date.year() as i64 - start.year() as i64,
+        };
+        periods.div_euclid(self.interval as i64)
+    }
+}
+
+impl CalendarEvent {
+    fn occurrence(&self, start: NaiveDateTime) -> Occurrence {
+        Occurrence {
+            summary: self.summary.clone(),
+            location: self.location.clone(),
+            start,
+            end: start.checked_add_signed(self.duration).unwrap_or(start),
+            all_day: self.all_day,
+        }
+    }
+
+    // Start times of the recurrence set before `to`, in order, before EXDATE is applied.
+    fn starts(&self, from: NaiveDateTime, to: NaiveDateTime) -> Vec<NaiveDateTime> {
+        let rule = match &self.rrule {
+            Some(rule) => rule,
+            None if self.start < to => return vec![self.start],
+            None => return Vec::new(),
+        };
+        let start = self.start.date();
+        // Without COUNT nothing needs counting from DTSTART: start a period before the window.
+        let mut k = match (rule.count, from.checked_sub_signed(self.duration)) {
+            (None, Some(earliest)) => (rule.period_index(start, earliest.date()) - 1).max(0),
+            _ => 0,
+        };
+        let mut counted = 0;
+        let mut starts = Vec::new();
+        while let Some((first, mut dates)) = rule.period(start, k) {
+            if first.and_hms_opt(0, 0, 0).unwrap() >= to {
+                break;
+            }
+            dates.sort_unstable();
+            dates.dedup();
+            for date in dates {
+                let occurrence = date.and_time(self.start.time());
+                if occurrence < self.start {
+                    continue;
+                }
+                if matches!(rule.until, Some(until) if occurrence > until) || matches!(rule.count, Some(count) if counted >= count) || occurrence >= to {
+                    return starts;
+                }
+                counted += 1;
+                starts.push(occurrence);
+            }
+            k += 1;
+        }
+        starts
+    }
+
+    pub fn occurrences_between(&self, from: NaiveDateTime, to: NaiveDateTime) -> Vec<Occurrence> {
+        self.starts(from, to)
+            .into_iter()
+            .filter(|start| !matches!(start.checked_add_signed(self.duration), Some(end) if end <= from) || *start >= from)
+            .filter(|start| !self.exdates.contains(start))
+            .map(|start| self.occurrence(start))
+            .collect()
+    }
+}
+
+pub fn events_between(events: &[CalendarEvent], from: NaiveDateTime, to: NaiveDateTime) -> Vec<Occurrence> {
+    let mut occurrences: Vec<Occurrence> = events
+        .iter()
+        .flat_map(|event| event.occurrences_between(from, to))
+        .collect();
+    occurrences.sort_by(|a, b| a.start.cmp(&b.start).then(a.summary.cmp(&b.summary)));
+    occurrences
+}
+
+pub fn upcoming_days(events: &[CalendarEvent], today: NaiveDate, days: i64) -> Vec<(NaiveDate, Vec<Occurrence>)> {
+    (0..days.clamp(1, MAX_DAYS))
+        .map(|offset| {
+            let day = today + Duration::days(offset);
+            let from = day.and_hms_opt(0, 0, 0).unwrap();
+            (day, events_between(events, from, from + Duration::days(1)))
+        })
+        .collect()
+}
+
+pub fn next_event(events: &[CalendarEvent], now: NaiveDateTime) -> Option<Occurrence> {
+    events_between(events, now, now + Duration::days(366))
+        .into_iter()
+        .find(|occurrence| occurrence.start >= now)
+}
+
+pub fn format_day(day: NaiveDate) -> String {
+    Utc.from_utc_datetime(&day.and_hms_opt(0, 0, 0).unwrap())
+        .format_localized("%A %e %B %Y", Locale::fr_FR)
+        .to_string()
+}
+
+pub fn format_occurrence(occurrence: &Occurrence) -> String {
+    let time = if occurrence.all_day {
+        "Toute la journée".to_string()
+    } else {
+        [occurrence.start.format("%H:%M").to_string(), occurrence.end.format("%H:%M").to_string()].join(" - ")
+    };
+    match &occurrence.location {
+        Some(location) if !location.is_empty() => [time, occurrence.summary.clone(), ["(", location, ")"].concat()].join("  "),
+        _ => [time, occurrence.summary.clone()].join("  "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ICS: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+BEGIN:VEVENT\r
+UID:standup\r
+SUMMARY:Réunion d'équipe\r
+DTSTART:20210104T093000\r
+DTEND:20210104T094500\r
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE,FR;COUNT=6\r
+EXDATE:20210106T093000\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:birthday\r
+SUMMARY:Anniversaire\r
+DTSTART;VALUE=DATE:20200229\r
+RRULE:FREQ=YEARLY\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:dentist\r
+SUMMARY:Dentiste\r
+LOCATION:Lille\r
+DTSTART:20210105T140000\r
+DURATION:PT1H30M\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_ics() {
+        let events = parse_ics(ICS.as_bytes()).unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].summary, "Réunion d'équipe");
+        assert_eq!(events[0].duration, Duration::minutes(15));
+        assert!(events[1].all_day);
+        assert_eq!(events[2].duration, Duration::minutes(90));
+        assert_eq!(events[2].location, Some("Lille".to_string()));
+    }
+
+    #[test]
+    fn test_weekly_rrule_with_exdate() {
+        let events = parse_ics(ICS.as_bytes()).unwrap();
+        let starts: Vec<NaiveDateTime> = events[0]
+            .occurrences_between(at(2021, 1, 1, 0, 0), at(2021, 2, 1, 0, 0))
+            .iter()
+            .map(|o| o.start)
+            .collect();
+        assert_eq!(starts, vec![
+            at(2021, 1, 4, 9, 30),
+            at(2021, 1, 8, 9, 30),
+            at(2021, 1, 11, 9, 30),
+            at(2021, 1, 13, 9, 30),
+            at(2021, 1, 15, 9, 30),
+        ]);
+    }
+
+    #[test]
+    fn test_yearly_rrule_skips_invalid_dates() {
+        let events = parse_ics(ICS.as_bytes()).unwrap();
+        let starts: Vec<NaiveDateTime> = events[1]
+            .occurrences_between(at(2021, 1, 1, 0, 0), at(2025, 1, 1, 0, 0))
+            .iter()
+            .map(|o| o.start)
+            .collect();
+        assert_eq!(starts, vec![at(2024, 2, 29, 0, 0)]);
+    }
+
+    #[test]
+    fn test_parse_rrule() {
+        let rule = parse_rrule("FREQ=DAILY;INTERVAL=2;UNTIL=20210110").unwrap();
+        assert_eq!(rule.frequency, Frequency::Daily);
+        assert_eq!(rule.interval, 2);
+        assert_eq!(rule.until, Some(at(2021, 1, 10, 23, 59) + Duration::seconds(59)));
+        assert!(parse_rrule("INTERVAL=2").is_none());
+    }
+
+    fn expand(rule: &str, dtstart: NaiveDateTime, from: NaiveDateTime, to: NaiveDateTime) -> Vec<NaiveDateTime> {
+        let event = CalendarEvent {
+            uid: String::new(),
+            summary: String::new(),
+            location: None,
+            start: dtstart,
+            duration: Duration::hours(1),
+            all_day: false,
+            rrule: parse_rrule(rule),
+            exdates: Vec::new(),
+        };
+        event.occurrences_between(from, to).iter().map(|o| o.start).collect()
+    }
+
+    #[test]
+    fn test_old_rule_without_end() {
+        let starts = expand("FREQ=DAILY;INTERVAL=3", at(1990, 1, 1, 8, 0), at(2060, 3, 1, 0, 0), at(2060, 3, 7, 0, 0));
+        assert_eq!(starts, vec![at(2060, 3, 3, 8, 0), at(2060, 3, 6, 8, 0)]);
+        let starts = expand("FREQ=WEEKLY;BYDAY=TU,TH", at(1990, 1, 2, 8, 0), at(2060, 3, 1, 0, 0), at(2060, 3, 7, 0, 0));
+        assert_eq!(starts, vec![at(2060, 3, 2, 8, 0), at(2060, 3, 4, 8, 0)]);
+        assert!(expand("FREQ=DAILY;COUNT=3", at(1990, 1, 1, 8, 0), at(2060, 3, 1, 0, 0), at(2060, 3, 7, 0, 0)).is_empty());
+    }
+
+    #[test]
+    fn test_by_day_positions() {
+        // Second Tuesday of the month
+        let starts = expand("FREQ=MONTHLY;BYDAY=2TU", at(2021, 1, 12, 18, 0), at(2021, 1, 1, 0, 0), at(2021, 5, 1, 0, 0));
+        assert_eq!(starts, vec![at(2021, 1, 12, 18, 0), at(2021, 2, 9, 18, 0), at(2021, 3, 9, 18, 0), at(2021, 4, 13, 18, 0)]);
+        // Last Sunday of March, summer time in Europe
+        let starts = expand("FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU", at(2021, 3, 28, 2, 0), at(2021, 1, 1, 0, 0), at(2024, 1, 1, 0, 0));
+        assert_eq!(starts, vec![at(2021, 3, 28, 2, 0), at(2022, 3, 27, 2, 0), at(2023, 3, 26, 2, 0)]);
+        // Last day of the month, every other month
+        let starts = expand("FREQ=MONTHLY;INTERVAL=2;BYMONTHDAY=-1;COUNT=3", at(2021, 1, 31, 9, 0), at(2021, 1, 1, 0, 0), at(2022, 1, 1, 0, 0));
+        assert_eq!(starts, vec![at(2021, 1, 31, 9, 0), at(2021, 3, 31, 9, 0), at(2021, 5, 31, 9, 0)]);
+        // 20th Monday of the year
+        let starts = expand("FREQ=YEARLY;BYDAY=20MO", at(2021, 5, 17, 9, 0), at(2021, 1, 1, 0, 0), at(2023, 1, 1, 0, 0));
+        assert_eq!(starts, vec![at(2021, 5, 17, 9, 0), at(2022, 5, 16, 9, 0)]);
+    }
+
+    #[test]
+    fn test_rejected_rules() {
+        assert!(parse_rrule("FREQ=MONTHLY;BYDAY=MO,TU;BYSETPOS=-1").is_none());
+        assert!(parse_rrule("FREQ=WEEKLY;BYDAY=2TU").is_none());
+        assert!(parse_rrule("FREQ=MONTHLY;BYDAY=0TU").is_none());
+        assert!(parse_rrule("FREQ=YEARLY;BYMONTH=13").is_none());
+        assert_eq!(parse_rrule("FREQ=MONTHLY;BYDAY=+1MO,-1FR").unwrap().by_day, vec![(1, Weekday::Mon), (-1, Weekday::Fri)]);
+    }
+
+    #[test]
+    fn test_parse_duration_overflow() {
+        assert_eq!(parse_duration("P1DT2H"), Some(Duration::hours(26)));
+        assert_eq!(parse_duration("-PT15M"), Some(Duration::minutes(-15)));
+        assert_eq!(parse_duration("P99999999999999W"), None);
+        assert_eq!(parse_duration("P99999999999999999999D"), None);
+        // Valid, but past the last representable date once added to DTSTART.
+        let events = parse_ics(ICS.replace("DURATION:PT1H30M", "DURATION:P9999999999W").as_bytes()).unwrap();
+        assert_eq!(next_event(&events, at(2021, 1, 5, 10, 0)).unwrap().summary, "Dentiste");
+    }
+
+    #[test]
+    fn test_next_event() {
+        let events = parse_ics(ICS.as_bytes()).unwrap();
+        let next = next_event(&events, at(2021, 1, 5, 10, 0)).unwrap();
+        assert_eq!(next.summary, "Dentiste");
+        assert_eq!(next.end, at(2021, 1, 5, 15, 30));
+        let next = next_event(&events, at(2021, 1, 5, 14, 30)).unwrap();
+        assert_eq!(next.start, at(2021, 1, 8, 9, 30));
+    }
+
+    #[test]
+    fn test_upcoming_days() {
+        let events = parse_ics(ICS.as_bytes()).unwrap();
+        let days = upcoming_days(&events, NaiveDate::from_ymd_opt(2021, 1, 4).unwrap(), 3);
+        assert_eq!(days.len(), 3);
+        assert_eq!(days[0].1.len(), 1);
+        assert_eq!(days[1].1[0].summary, "Dentiste");
+        assert!(days[2].1.is_empty());
+    }
+
+    #[test]
+    fn test_format_day() {
+        assert_eq!(format_day(NaiveDate::from_ymd_opt(2021, 1, 4).unwrap()), "lundi  4 janvier 2021");
+    }
+
+    #[test]
+    fn test_parse_days() {
+        assert_eq!(parse_days(""), 7);
+        assert_eq!(parse_days(" 3 "), 3);
+        assert_eq!(parse_days("0"), 1);
+        assert_eq!(parse_days("100000000"), MAX_DAYS);
+        assert_eq!(parse_days("-5"), 1);
+    }
+
+    #[test]
+    fn test_load_calendars_keeps_readable_files() {
+        let dir = std::env::temp_dir().join(["assistant_gui_calendar_", &std::process::id().to_string()].concat());
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("good.ics"), ICS).unwrap();
+        std::fs::write(dir.join("bad.ics"), "BEGIN:VCALENDAR\nBEGIN:VEVENT\n").unwrap();
+        let paths = vec![dir.join("missing.ics"), dir.join("bad.ics"), dir.join("good.ics")];
+        let (events, errors) = load_calendars(&paths);
+        assert_eq!(events, parse_ics(ICS.as_bytes()).unwrap());
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with(&dir.join("missing.ics").display().to_string()));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_paths() {
+        assert_eq!(parse_paths(" a.ics; ;b.ics"), vec![PathBuf::from("a.ics"), PathBuf::from("b.ics")]);
+    }
+}
@@ -0,0 +1,116 @@
+use chrono::prelude::*;
+
+pub const PAGES: [&str; 12] = ["home", "clock", "calendar", "meteo", "history", "departures", "music", "news", "todo", "notes", "mqtt", "system"];
+pub const DEFAULT_DWELL_SECONDS: u64 = 30;
+const MIN_DWELL_SECONDS: u64 = 5;
+// The carousel waits this long after the last touch before moving on again.
+const RESUME_AFTER_SECONDS: i64 = 60;
+
+pub fn label(page: &str) -> &'static str {
+    match page {
+        "home" => "Accueil",
+        "clock" => "Horloge",
+        "calendar" => "Agenda",
+        "meteo" => "Meteo",
+        "history" => "Historique météo",
+        "departures" => "Départs",
+        "music" => "Musique",
+        "news" => "Actualités",
+        "todo" => "Tâches",
+        "notes" => "Notes",
+        "mqtt" => "Maison",
+        "system" => "Système",
+        _ => "",
+    }
+}
+
+// "45" for every page, or per page as in "45;meteo=30;news=60": pages left out get the bare
+// value, or the default without one.
+pub fn parse_dwell(value: &str, page: &str) -> std::time::Duration {
+    let mut seconds = DEFAULT_DWELL_SECONDS;
+    for entry in value.split(';') {
+        match entry.split_once('=') {
+            Some((name, page_seconds)) if name.trim() == page => {
+                if let Ok(page_seconds) = page_seconds.trim().parse() {
+                    seconds = page_seconds;
+                    break;
+                }
+            }
+            Some(_) => {}
+            None => seconds = entry.trim().parse().unwrap_or(seconds),
+        }
+    }
+    std::time::Duration::from_secs(seconds.max(MIN_DWELL_SECONDS))
+}
+
+// Keeps the selection in the order of `PAGES` whatever order the boxes are ticked in.
+pub fn toggle_page(pages: &mut Vec<String>, page: &str, selected: bool) {
+    pages.retain(|p| p != page);
+    if selected {
+        pages.push(page.to_string());
+        pages.sort_by_key(|p| PAGES.iter().position(|known| known == p));
+    }
+}
+
+pub fn next_page<'a>(pages: &'a [String], current: &str) -> Option<&'a str> {
+    let next = match pages.iter().position(|page| page == current) {
+        Some(index) => (index + 1) % pages.len(),
+        None => 0,
+    };
+    pages.get(next).map(|page| page.as_str())
+}
+
+pub fn is_paused(last_activity: Option<DateTime<Local>>, now: DateTime<Local>) -> bool {
+    match last_activity {
+        Some(last_activity) => now - last_activity < chrono::Duration::seconds(RESUME_AFTER_SECONDS),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pages(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_next_page_wraps_around() {
+        let pages = pages(&["clock", "meteo", "news"]);
+        assert_eq!(next_page(&pages, "clock"), Some("meteo"));
+        assert_eq!(next_page(&pages, "news"), Some("clock"));
+        assert_eq!(next_page(&pages, "parameter"), Some("clock"));
+        assert_eq!(next_page(&[], "clock"), None);
+    }
+
+    #[test]
+    fn test_toggle_page_keeps_menu_order() {
+        let mut selected = Vec::new();
+        toggle_page(&mut selected, "news", true);
+        toggle_page(&mut selected, "clock", true);
+        toggle_page(&mut selected, "meteo", true);
+        toggle_page(&mut selected, "meteo", true);
+        assert_eq!(selected, pages(&["clock", "meteo", "news"]));
+        toggle_page(&mut selected, "clock", false);
+        assert_eq!(selected, pages(&["meteo", "news"]));
+    }
+
+    #[test]
+    fn test_parse_dwell() {
+        assert_eq!(parse_dwell("45", "meteo"), std::time::Duration::from_secs(45));
+        assert_eq!(parse_dwell("", "meteo"), std::time::Duration::from_secs(DEFAULT_DWELL_SECONDS));
+        assert_eq!(parse_dwell("1", "meteo"), std::time::Duration::from_secs(MIN_DWELL_SECONDS));
+        assert_eq!(parse_dwell("meteo=30;news=60", "news"), std::time::Duration::from_secs(60));
+        assert_eq!(parse_dwell("meteo=30;news=60", "clock"), std::time::Duration::from_secs(DEFAULT_DWELL_SECONDS));
+        assert_eq!(parse_dwell("news=60; 20 ;meteo=abc", "meteo"), std::time::Duration::from_secs(20));
+    }
+
+    #[test]
+    fn test_is_paused_after_interaction() {
+        let now = Local::now();
+        assert!(!is_paused(None, now));
+        assert!(is_paused(Some(now - chrono::Duration::seconds(10)), now));
+        assert!(!is_paused(Some(now - chrono::Duration::seconds(RESUME_AFTER_SECONDS)), now));
+    }
+}
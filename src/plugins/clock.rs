@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+pub fn hand_rotation(n: u32, total: u32) -> f32 {
+    let turns = n as f32 / total as f32;
+    2.0 * std::f32::consts::PI * turns
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClockFace {
+    #[default]
+    Digital,
+    Analog,
+}
+
+impl ClockFace {
+    pub const ALL: [ClockFace; 2] = [
+        ClockFace::Digital,
+        ClockFace::Analog,
+    ];
+}
+
+impl std::fmt::Display for ClockFace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ClockFace::Digital => "Numérique",
+                ClockFace::Analog => "Analogique",
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hand_rotation() {
+        assert_eq!(hand_rotation(0, 60), 0.0);
+        assert_eq!(hand_rotation(15, 60), std::f32::consts::FRAC_PI_2);
+        assert_eq!(hand_rotation(6, 12), std::f32::consts::PI);
+    }
+}
@@ -0,0 +1,333 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde::Deserialize;
+use std::error::Error;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use crate::plugins::{layout, state};
+use crate::plugins::remote::{self, Level, Notification, RemoteCommand};
+
+pub const SYSTEM_CONFIG_PATH: &str = "/etc/assistant_gui/config.toml";
+pub const DEFAULT_PAGE: &str = "clock";
+pub const DEFAULT_LOG_LEVEL: &str = "warn";
+
+pub const PAGES: [&str; 15] = ["home", "clock", "calendar", "meteo", "history", "alerts", "departures", "podcast", "music", "news", "todo", "notes", "mqtt", "system", "parameter"];
+pub const LOG_LEVELS: [&str; 6] = ["off", "error", "warn", "info", "debug", "trace"];
+
+// Options given on the command line; anything left out falls back to the system config file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Cli {
+    pub size: Option<(u32, u32)>,
+    pub fullscreen: bool,
+    pub kiosk: bool,
+    pub page: Option<String>,
+    pub config: Option<PathBuf>,
+    pub data_dir: Option<PathBuf>,
+    pub log_level: Option<String>,
+    pub status_address: Option<String>,
+    pub control_socket: Option<PathBuf>,
+    // Set by the `remote` subcommand: send this to the running assistant instead of starting one.
+    pub remote: Option<RemoteCommand>,
+}
+
+// System-wide configuration shared by every user of a display, e.g.
+//
+//     size = "800x480"
+//     kiosk = true
+//     page = "clock"
+//
+//     [settings]
+//     searchcity_value = "Lille"
+//
+// The `settings` table gives defaults for the per-user saved state.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SystemConfig {
+    pub size: Option<String>,
+    pub fullscreen: bool,
+    pub kiosk: bool,
+    pub page: Option<String>,
+    pub data_dir: Option<PathBuf>,
+    pub log_level: Option<String>,
+    pub status_address: Option<String>,
+    pub control_socket: Option<PathBuf>,
+    pub settings: Option<toml::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LaunchConfig {
+    pub size: (u32, u32),
+    pub fullscreen: bool,
+    pub kiosk: bool,
+    pub page: String,
+    pub data_dir: Option<PathBuf>,
+    pub log_level: String,
+    pub status_address: Option<String>,
+    pub control_socket: Option<PathBuf>,
+    pub settings: serde_json::Value,
+}
+
+impl Default for LaunchConfig {
+    fn default() -> LaunchConfig {
+        LaunchConfig::resolve(Cli::default(), SystemConfig::default())
+    }
+}
+
+impl LaunchConfig {
+    pub fn resolve(cli: Cli, system: SystemConfig) -> LaunchConfig {
+        let kiosk = cli.kiosk || system.kiosk;
+        let size = cli.size
+            .or_else(|| system.size.as_deref().and_then(parse_size))
+            .unwrap_or((layout::DEFAULT_WIDTH, layout::DEFAULT_HEIGHT));
+        let page = cli.page
+            .or(system.page)
+            .filter(|page| PAGES.contains(&page.as_str()))
+            .unwrap_or_else(|| DEFAULT_PAGE.to_string());
+        let log_level = cli.log_level
+            .or(system.log_level)
+            .filter(|level| LOG_LEVELS.contains(&level.as_str()))
+            .unwrap_or_else(|| DEFAULT_LOG_LEVEL.to_string());
+        let settings = system.settings
+            .and_then(|settings| serde_json::to_value(settings).ok())
+            .unwrap_or(serde_json::Value::Null);
+
+        LaunchConfig {
+            size,
+            fullscreen: kiosk || cli.fullscreen || system.fullscreen,
+            kiosk,
+            page,
+            data_dir: cli.data_dir.or(system.data_dir),
+            log_level,
+            status_address: cli.status_address.or(system.status_address),
+            control_socket: cli.control_socket.or(system.control_socket),
+            settings,
+        }
+    }
+
+    pub fn control_socket(&self) -> PathBuf {
+        match &self.control_socket {
+            Some(path) => path.clone(),
+            None => state::data_path(remote::SOCKET_FILE_NAME),
+        }
+    }
+}
+
+pub fn parse_size(value: &str) -> Option<(u32, u32)> {
+    let mut parts = value.trim().splitn(2, ['x', 'X']);
+    let width: u32 = parts.next()?.trim().parse().ok()?;
+    let height: u32 = parts.next()?.trim().parse().ok()?;
+    if width == 0 || height == 0 {
+        return None;
+    }
+    Some((width, height))
+}
+
+pub fn parse_args<I, T>(args: I) -> Result<Cli, clap::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let matches = App::new("assistant_gui")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Assistant personnel")
+        .arg(Arg::with_name("size")
+            .long("size")
+            .value_name("LARGEURxHAUTEUR")
+            .help("Taille de la fenêtre, par exemple 800x480")
+            .validator(|value| match parse_size(&value) {
+                Some(_) => Ok(()),
+                None => Err(String::from("taille attendue sous la forme 800x480")),
+            }))
+        .arg(Arg::with_name("fullscreen")
+            .long("fullscreen")
+            .short("f")
+            .help("Démarre en plein écran"))
+        .arg(Arg::with_name("kiosk")
+            .long("kiosk")
+            .short("k")
+            .help("Mode kiosque : plein écran sans bordure"))
+        .arg(Arg::with_name("page")
+            .long("page")
+            .value_name("PAGE")
+            .possible_values(&PAGES)
+            .help("Page affichée au démarrage"))
+        .arg(Arg::with_name("config")
+            .long("config")
+            .short("c")
+            .value_name("FICHIER")
+            .help("Fichier de configuration système (par défaut /etc/assistant_gui/config.toml)"))
+        .arg(Arg::with_name("data-dir")
+            .long("data-dir")
+            .value_name("DOSSIER")
+            .help("Dossier des données utilisateur"))
+        .arg(Arg::with_name("log-level")
+            .long("log-level")
+            .value_name("NIVEAU")
+            .possible_values(&LOG_LEVELS)
+            .help("Niveau de journalisation"))
+        .arg(Arg::with_name("status-address")
+            .long("status-address")
+            .value_name("ADRESSE")
+            .help("Active l'API HTTP d'état, par exemple 0.0.0.0:8080"))
+        .arg(Arg::with_name("control-socket")
+            .long("control-socket")
+            .value_name("FICHIER")
+            .help("Socket de commande (par défaut control.sock dans le dossier des données)"))
+        .subcommand(SubCommand::with_name("remote")
+            .about("Pilote un assistant déjà lancé")
+            .subcommand(SubCommand::with_name("page")
+                .about("Affiche une page")
+                .arg(Arg::with_name("PAGE")
+                    .required(true)
+                    .possible_values(&PAGES)))
+            .subcommand(SubCommand::with_name("refresh")
+                .about("Recharge les données d'une page, ou de la page affichée")
+                .arg(Arg::with_name("PAGE")
+                    .possible_values(&PAGES)))
+            .subcommand(SubCommand::with_name("notify")
+                .about("Affiche une notification")
+                .arg(Arg::with_name("TITLE")
+                    .required(true))
+                .arg(Arg::with_name("BODY"))
+                .arg(Arg::with_name("level")
+                    .long("level")
+                    .value_name("NIVEAU")
+                    .possible_values(&Level::NAMES)
+                    .default_value("info"))
+                .arg(Arg::with_name("duration")
+                    .long("duration")
+                    .value_name("SECONDES")
+                    .validator(|value| value.parse::<u64>().map(|_| ()).map_err(|e| e.to_string())))))
+        .get_matches_from_safe(args)?;
+
+    Ok(Cli {
+        size: matches.value_of("size").and_then(parse_size),
+        fullscreen: matches.is_present("fullscreen"),
+        kiosk: matches.is_present("kiosk"),
+        page: matches.value_of("page").map(String::from),
+        config: matches.value_of_os("config").map(PathBuf::from),
+        data_dir: matches.value_of_os("data-dir").map(PathBuf::from),
+        log_level: matches.value_of("log-level").map(String::from),
+        status_address: matches.value_of("status-address").map(String::from),
+        control_socket: matches.value_of_os("control-socket").map(PathBuf::from),
+        remote: matches.subcommand_matches("remote").and_then(remote_command),
+    })
+}
+
+fn remote_command(matches: &ArgMatches) -> Option<RemoteCommand> {
+    match matches.subcommand() {
+        ("page", Some(page)) => Some(RemoteCommand::ShowPage {
+            page: page.value_of("PAGE")?.to_string(),
+        }),
+        ("refresh", Some(refresh)) => Some(RemoteCommand::Refresh {
+            page: refresh.value_of("PAGE").map(String::from),
+        }),
+        ("notify", Some(notify)) => Some(RemoteCommand::Notify(Notification {
+            title: notify.value_of("TITLE")?.to_string(),
+            body: notify.value_of("BODY").unwrap_or("").to_string(),
+            level: notify.value_of("level").and_then(Level::from_name).unwrap_or_default(),
+            duration: notify.value_of("duration")
+                .and_then(|duration| duration.parse().ok())
+                .map_or(remote::DEFAULT_DURATION_SECONDS, remote::clamp_duration),
+        })),
+        _ => None,
+    }
+}
+
+pub fn parse_system_config(contents: &str) -> Result<SystemConfig, Box<dyn Error>> {
+    Ok(toml::from_str(contents)?)
+}
+
+// A missing file is fine at the default location, but not when it was asked for with --config.
+pub fn load_system_config(cli: &Cli) -> Result<SystemConfig, Box<dyn Error>> {
+    let path = match &cli.config {
+        Some(path) => path.clone(),
+        None => {
+            let path = Path::new(SYSTEM_CONFIG_PATH);
+            if !path.exists() {
+                return Ok(SystemConfig::default());
+            }
+            path.to_path_buf()
+        }
+    };
+    parse_system_config(&std::fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("800x480"), Some((800, 480)));
+        assert_eq!(parse_size(" 1920X1080 "), Some((1920, 1080)));
+        assert_eq!(parse_size("800"), None);
+        assert_eq!(parse_size("0x480"), None);
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let cli = parse_args(vec!["assistant_gui", "--size", "800x480", "-f", "--page", "todo", "--data-dir", "/tmp/assistant"]).unwrap();
+        assert_eq!(cli.size, Some((800, 480)));
+        assert!(cli.fullscreen);
+        assert_eq!(cli.page.as_deref(), Some("todo"));
+        assert_eq!(cli.data_dir, Some(PathBuf::from("/tmp/assistant")));
+        assert_eq!(cli.log_level, None);
+        assert!(parse_args(vec!["assistant_gui", "--page", "inconnue"]).is_err());
+        assert!(parse_args(vec!["assistant_gui", "--size", "grand"]).is_err());
+    }
+
+    #[test]
+    fn test_command_line_overrides_system_config() {
+        let system = parse_system_config(
+            "size = \"800x480\"\nfullscreen = true\npage = \"meteo\"\nlog_level = \"info\"\n\n[settings]\nsearchcity_value = \"Lille\"\n",
+        ).unwrap();
+        let cli = parse_args(vec!["assistant_gui", "--page", "notes"]).unwrap();
+        let launch = LaunchConfig::resolve(cli, system);
+        assert_eq!(launch.size, (800, 480));
+        assert!(launch.fullscreen);
+        assert_eq!(launch.page, "notes");
+        assert_eq!(launch.log_level, "info");
+        assert_eq!(launch.settings["searchcity_value"], "Lille");
+    }
+
+    #[test]
+    fn test_remote_subcommand() {
+        let cli = parse_args(vec!["assistant_gui", "remote", "page", "meteo"]).unwrap();
+        assert_eq!(cli.remote, Some(RemoteCommand::ShowPage { page: "meteo".to_string() }));
+        let cli = parse_args(vec!["assistant_gui", "remote", "refresh"]).unwrap();
+        assert_eq!(cli.remote, Some(RemoteCommand::Refresh { page: None }));
+        let cli = parse_args(vec!["assistant_gui", "--control-socket", "/tmp/a.sock", "remote", "notify", "Sonnette", "Quelqu'un sonne", "--level", "warning", "--duration", "30"]).unwrap();
+        assert_eq!(cli.control_socket, Some(PathBuf::from("/tmp/a.sock")));
+        assert_eq!(cli.remote, Some(RemoteCommand::Notify(Notification {
+            title: "Sonnette".to_string(),
+            body: "Quelqu'un sonne".to_string(),
+            level: Level::Warning,
+            duration: 30,
+        })));
+        assert!(parse_args(vec!["assistant_gui", "remote", "page", "ailleurs"]).is_err());
+        assert_eq!(parse_args(vec!["assistant_gui"]).unwrap().remote, None);
+    }
+
+    #[test]
+    fn test_kiosk_implies_fullscreen() {
+        let launch = LaunchConfig::resolve(parse_args(vec!["assistant_gui", "--kiosk"]).unwrap(), SystemConfig::default());
+        assert!(launch.kiosk);
+        assert!(launch.fullscreen);
+        let system = parse_system_config("kiosk = true\n").unwrap();
+        assert!(LaunchConfig::resolve(Cli::default(), system).fullscreen);
+    }
+
+    #[test]
+    fn test_defaults() {
+        let launch = LaunchConfig::default();
+        assert_eq!(launch.size, (layout::DEFAULT_WIDTH, layout::DEFAULT_HEIGHT));
+        assert!(!launch.fullscreen);
+        assert!(!launch.kiosk);
+        assert_eq!(launch.status_address, None);
+        assert_eq!(launch.control_socket(), state::data_path(remote::SOCKET_FILE_NAME));
+        assert_eq!(launch.page, DEFAULT_PAGE);
+        assert_eq!(launch.log_level, DEFAULT_LOG_LEVEL);
+        assert!(launch.settings.is_null());
+    }
+}
@@ -0,0 +1,58 @@
+use std::fmt;
+use std::ptr;
+use x11_dl::xlib;
+// x11-dl names the XFixes bindings `Xlib` as well.
+use x11_dl::xfixes::Xlib as XFixes;
+
+// iced gives no hold on the window's cursor, so the pointer is hidden for the whole X screen
+// through XFixes. The server shows it again by itself if the application goes away.
+pub struct Cursor {
+    xlib: xlib::Xlib,
+    xfixes: XFixes,
+    display: *mut xlib::Display,
+    hidden: bool,
+}
+
+impl fmt::Debug for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cursor").field("hidden", &self.hidden).finish()
+    }
+}
+
+impl Cursor {
+    // None without an X server (Wayland, framebuffer) or without the XFixes library.
+    pub fn open() -> Option<Cursor> {
+        let xlib = xlib::Xlib::open().ok()?;
+        let xfixes = XFixes::open().ok()?;
+        let display = unsafe { (xlib.XOpenDisplay)(ptr::null()) };
+        if display.is_null() {
+            return None;
+        }
+        Some(Cursor { xlib, xfixes, display, hidden: false })
+    }
+
+    pub fn set_hidden(&mut self, hidden: bool) {
+        if hidden == self.hidden {
+            return;
+        }
+        unsafe {
+            let root = (self.xlib.XDefaultRootWindow)(self.display);
+            if hidden {
+                (self.xfixes.XFixesHideCursor)(self.display, root);
+            } else {
+                (self.xfixes.XFixesShowCursor)(self.display, root);
+            }
+            (self.xlib.XFlush)(self.display);
+        }
+        self.hidden = hidden;
+    }
+}
+
+impl Drop for Cursor {
+    fn drop(&mut self) {
+        self.set_hidden(false);
+        unsafe {
+            (self.xlib.XCloseDisplay)(self.display);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use serde::{Deserialize, Serialize};
+
+pub const COLUMNS: [u8; 3] = [1, 2, 3];
+pub const DEFAULT_COLUMNS: u8 = 2;
+pub const HEADLINES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Widget {
+    Clock,
+    Weather,
+    Headlines,
+    NextEvent,
+}
+
+impl Widget {
+    pub const ALL: [Widget; 4] = [
+        Widget::Clock,
+        Widget::Weather,
+        Widget::Headlines,
+        Widget::NextEvent,
+    ];
+
+    // Page opened when the widget is pressed.
+    pub fn page(self) -> &'static str {
+        match self {
+            Widget::Clock => "clock",
+            Widget::Weather => "meteo",
+            Widget::Headlines => "news",
+            Widget::NextEvent => "calendar",
+        }
+    }
+}
+
+impl std::fmt::Display for Widget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Widget::Clock => "Horloge",
+                Widget::Weather => "Météo",
+                Widget::Headlines => "Titres",
+                Widget::NextEvent => "Prochain événement",
+            }
+        )
+    }
+}
+
+pub fn default_widgets() -> Vec<Widget> {
+    Widget::ALL.to_vec()
+}
+
+pub fn default_columns() -> u8 {
+    DEFAULT_COLUMNS
+}
+
+// Hand-edited settings may hold any number; the grid only knows the choices of `COLUMNS`.
+pub fn clamp_columns(columns: u8) -> u8 {
+    columns.max(COLUMNS[0]).min(COLUMNS[COLUMNS.len() - 1])
+}
+
+pub fn toggle(widgets: &mut Vec<Widget>, widget: Widget, shown: bool) {
+    let position = widgets.iter().position(|w| *w == widget);
+    match (position, shown) {
+        (None, true) => widgets.push(widget),
+        (Some(index), false) => {
+            widgets.remove(index);
+        }
+        _ => {}
+    }
+}
+
+pub fn move_up(widgets: &mut [Widget], widget: Widget) {
+    if let Some(index) = widgets.iter().position(|w| *w == widget) {
+        if index > 0 {
+            widgets.swap(index, index - 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle_and_move_up() {
+        let mut widgets = vec![Widget::Clock, Widget::Weather];
+        toggle(&mut widgets, Widget::NextEvent, true);
+        toggle(&mut widgets, Widget::NextEvent, true);
+        toggle(&mut widgets, Widget::Clock, false);
+        assert_eq!(widgets, vec![Widget::Weather, Widget::NextEvent]);
+        move_up(&mut widgets, Widget::NextEvent);
+        move_up(&mut widgets, Widget::NextEvent);
+        move_up(&mut widgets, Widget::Headlines);
+        assert_eq!(widgets, vec![Widget::NextEvent, Widget::Weather]);
+    }
+
+    #[test]
+    fn test_clamp_columns() {
+        assert_eq!(clamp_columns(0), 1);
+        assert_eq!(clamp_columns(2), 2);
+        assert_eq!(clamp_columns(200), 3);
+    }
+
+    #[test]
+    fn test_serialization() {
+        let json = serde_json::to_string(&default_widgets()).unwrap();
+        assert_eq!(json, r#"["Clock","Weather","Headlines","NextEvent"]"#);
+        let widgets: Vec<Widget> = serde_json::from_str(&json).unwrap();
+        assert_eq!(widgets, default_widgets());
+    }
+}
@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use crate::plugins::weather::WeatherReport;
+
+// One JSON observation per line, only ever appended to.
+pub const FILE_NAME: &str = "weather_history.jsonl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Observation {
+    // Unix timestamp of the measurement as reported by the provider.
+    pub time: i64,
+    pub temperature: f32,
+    #[serde(default)]
+    pub humidity: Option<f32>,
+    #[serde(default)]
+    pub pressure: Option<f32>,
+}
+
+impl Observation {
+    pub fn from_report(report: &WeatherReport) -> Observation {
+        Observation {
+            time: report.time,
+            temperature: report.temperature,
+            humidity: report.humidity,
+            pressure: report.pressure,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Range {
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+impl Range {
+    pub const ALL: [Range; 3] = [Range::Day, Range::Week, Range::Month];
+
+    pub fn seconds(self) -> i64 {
+        match self {
+            Range::Day => 86400,
+            Range::Week => 7 * 86400,
+            Range::Month => 30 * 86400,
+        }
+    }
+}
+
+impl std::fmt::Display for Range {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Range::Day => "24 heures",
+                Range::Week => "7 jours",
+                Range::Month => "30 jours",
+            }
+        )
+    }
+}
+
+pub fn append(path: &Path, observation: &Observation) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let line = serde_json::to_string(observation).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let mut file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)
+}
+
+// Observations since `since`, in order; the same measurement fetched twice is kept once
+// and a line cut short by a crash is skipped.
+pub fn load(path: &Path, since: i64) -> io::Result<Vec<Observation>> {
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut observations: Vec<Observation> = io::BufReader::new(file)
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Observation>(&line.ok()?).ok())
+        .filter(|observation| observation.time >= since)
+        .collect();
+    observations.sort_by_key(|observation| observation.time);
+    observations.dedup_by_key(|observation| observation.time);
+    Ok(observations)
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Series {
+    pub min: f32,
+    pub max: f32,
+    // x and y between 0 and 1, y = 0 for the maximum so it can be drawn top-down.
+    pub points: Vec<(f32, f32)>,
+}
+
+pub fn series<F: Fn(&Observation) -> Option<f32>>(observations: &[Observation], from: i64, to: i64, value: F) -> Option<Series> {
+    let values: Vec<(i64, f32)> = observations
+        .iter()
+        .filter_map(|observation| value(observation).map(|v| (observation.time, v)))
+        .collect();
+    if values.is_empty() || to <= from {
+        return None;
+    }
+    let min = values.iter().map(|(_, v)| *v).fold(f32::INFINITY, f32::min);
+    let max = values.iter().map(|(_, v)| *v).fold(f32::NEG_INFINITY, f32::max);
+    let points = values
+        .iter()
+        .map(|(time, v)| {
+            let x = (time - from) as f32 / (to - from) as f32;
+            let y = if max > min { (max - v) / (max - min) } else { 0.5 };
+            (x.clamp(0.0, 1.0), y)
+        })
+        .collect();
+    Some(Series { min, max, points })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observation(time: i64, temperature: f32) -> Observation {
+        Observation { time, temperature, humidity: None, pressure: None }
+    }
+
+    #[test]
+    fn test_from_report() {
+        let weather = json::parse(r#"{"dt": 1620000000, "main": {"temp": 12.0, "humidity": 81, "pressure": 1012}}"#).unwrap();
+        let observation = Observation::from_report(&crate::plugins::weather::parse_openweathermap(&weather).unwrap());
+        assert_eq!(observation.time, 1620000000);
+        assert_eq!(observation.temperature, 12.0);
+        assert_eq!(observation.humidity, Some(81.0));
+        assert_eq!(observation.pressure, Some(1012.0));
+    }
+
+    #[test]
+    fn test_append_and_load() {
+        let path = std::env::temp_dir().join(format!("assistant_gui_history_{}", std::process::id())).join(FILE_NAME);
+        append(&path, &observation(100, 10.0)).unwrap();
+        append(&path, &observation(300, 12.0)).unwrap();
+        append(&path, &observation(300, 12.0)).unwrap();
+        fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"{\"time\": 40").unwrap();
+        assert_eq!(load(&path, 0).unwrap(), vec![observation(100, 10.0), observation(300, 12.0)]);
+        assert_eq!(load(&path, 200).unwrap(), vec![observation(300, 12.0)]);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert!(load(&path, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_series() {
+        let observations = vec![
+            observation(0, 10.0),
+            Observation { humidity: Some(50.0), ..observation(50, 20.0) },
+            observation(100, 15.0),
+        ];
+        let temperature = series(&observations, 0, 100, |o| Some(o.temperature)).unwrap();
+        assert_eq!((temperature.min, temperature.max), (10.0, 20.0));
+        assert_eq!(temperature.points, vec![(0.0, 1.0), (0.5, 0.0), (1.0, 0.5)]);
+        let humidity = series(&observations, 0, 100, |o| o.humidity).unwrap();
+        assert_eq!(humidity.points, vec![(0.5, 0.5)]);
+        assert!(series(&observations, 0, 100, |o| o.pressure).is_none());
+    }
+}
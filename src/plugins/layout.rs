@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_WIDTH: u32 = 1024;
+pub const DEFAULT_HEIGHT: u32 = 600;
+
+// Below this width the menu collapses after a page is chosen, to leave the room to the content.
+const COMPACT_WIDTH: u32 = 640;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Layout {
+    pub width: u32,
+    pub height: u32,
+    pub font_scale: FontScale,
+}
+
+impl Layout {
+    pub fn new(width: u32, height: u32, font_scale: FontScale) -> Layout {
+        Layout {
+            width: width.max(1),
+            height: height.max(1),
+            font_scale,
+        }
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        if self.height > self.width {
+            Orientation::Portrait
+        } else {
+            Orientation::Landscape
+        }
+    }
+
+    pub fn is_compact(&self) -> bool {
+        self.width < COMPACT_WIDTH
+    }
+
+    // Sizes were designed for a 1024x600 screen, so the shortest side is compared to 600.
+    pub fn scale(&self) -> f32 {
+        match self.font_scale.factor() {
+            Some(factor) => factor,
+            None => (self.width.min(self.height) as f32 / DEFAULT_HEIGHT as f32).clamp(0.5, 2.0),
+        }
+    }
+
+    pub fn text_size(&self, base: u16) -> u16 {
+        ((base as f32 * self.scale()).round() as u16).max(8)
+    }
+
+    pub fn menu_width(&self) -> u16 {
+        ((120.0 * self.scale()).round() as u16).min(self.width as u16 / 3)
+    }
+
+    pub fn menu_button_height(&self) -> u32 {
+        (100.0 * self.scale()).round() as u32
+    }
+
+    pub fn clock_size(&self, menu_visible: bool) -> u16 {
+        let content_width = self.width.saturating_sub(if menu_visible { self.menu_width() as u32 } else { 0 });
+        (content_width.min(self.height / 2) as f32 * 0.9) as u16
+    }
+
+    pub fn grid_columns(&self, card_width: u16, menu_visible: bool) -> usize {
+        let content_width = self.width.saturating_sub(if menu_visible { self.menu_width() as u32 } else { 0 });
+        ((content_width as usize) / (card_width as usize + 15)).max(1)
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Layout {
+        Layout::new(DEFAULT_WIDTH, DEFAULT_HEIGHT, FontScale::default())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FontScale {
+    #[default]
+    Auto,
+    Small,
+    Normal,
+    Large,
+    ExtraLarge,
+}
+
+impl FontScale {
+    pub const ALL: [FontScale; 5] = [
+        FontScale::Auto,
+        FontScale::Small,
+        FontScale::Normal,
+        FontScale::Large,
+        FontScale::ExtraLarge,
+    ];
+
+    pub fn factor(self) -> Option<f32> {
+        match self {
+            FontScale::Auto => None,
+            FontScale::Small => Some(0.75),
+            FontScale::Normal => Some(1.0),
+            FontScale::Large => Some(1.25),
+            FontScale::ExtraLarge => Some(1.5),
+        }
+    }
+}
+
+impl std::fmt::Display for FontScale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                FontScale::Auto => "Automatique",
+                FontScale::Small => "Petite",
+                FontScale::Normal => "Normale",
+                FontScale::Large => "Grande",
+                FontScale::ExtraLarge => "Très grande",
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_orientation() {
+        assert_eq!(Layout::new(1024, 600, FontScale::Auto).orientation(), Orientation::Landscape);
+        assert_eq!(Layout::new(320, 480, FontScale::Auto).orientation(), Orientation::Portrait);
+    }
+
+    #[test]
+    fn test_auto_scale_follows_screen_size() {
+        assert_eq!(Layout::new(1024, 600, FontScale::Auto).text_size(50), 50);
+        assert_eq!(Layout::new(480, 320, FontScale::Auto).text_size(30), 16);
+        assert_eq!(Layout::new(1920, 1080, FontScale::Auto).text_size(20), 36);
+        assert_eq!(Layout::new(1920, 1080, FontScale::Normal).text_size(20), 20);
+        assert_eq!(Layout::new(480, 320, FontScale::Large).text_size(20), 25);
+    }
+
+    #[test]
+    fn test_grid_columns() {
+        let layout = Layout::new(1024, 600, FontScale::Normal);
+        assert_eq!(layout.grid_columns(270, true), 3);
+        assert_eq!(layout.grid_columns(270, false), 3);
+        assert_eq!(Layout::new(320, 480, FontScale::Normal).grid_columns(270, true), 1);
+        assert!(Layout::new(480, 320, FontScale::Auto).is_compact());
+    }
+}
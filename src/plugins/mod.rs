@@ -1,3 +1,30 @@
+pub mod air;
+pub mod alerts;
+pub mod astronomy;
+pub mod audio;
+pub mod backlight;
+pub mod caldav;
+pub mod calendar;
+pub mod carousel;
+pub mod clock;
+pub mod config;
+pub mod cursor;
+pub mod dashboard;
+pub mod history;
+pub mod layout;
+pub mod mpd;
+pub mod mqtt;
 pub mod news;
+pub mod night;
+pub mod notes;
+pub mod podcast;
+pub mod remote;
+pub mod screensaver;
+pub mod sensors;
 pub mod state;
+pub mod status;
+pub mod system;
+pub mod theme;
+pub mod todo;
+pub mod transit;
 pub mod weather;
\ No newline at end of file
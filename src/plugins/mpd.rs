@@ -0,0 +1,603 @@
+use std::error::Error;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub const DEFAULT_PORT: u16 = 6600;
+pub const REFRESH_SECONDS: u64 = 1;
+// After a failure the music page waits 2, 4, 8... seconds, up to this, before trying again.
+const MAX_RETRY_SECONDS: u64 = 60;
+const VOLUME_STEP: u8 = 5;
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Address {
+    Tcp(String, u16),
+    Unix(PathBuf),
+}
+
+impl Address {
+    // "host", "host:port", "[IPv6]:port", a bare IPv6 address or the path of MPD's socket;
+    // empty means the local server.
+    pub fn parse(value: &str) -> Address {
+        let value = value.trim();
+        if value.starts_with('/') {
+            return Address::Unix(PathBuf::from(value));
+        }
+        if let Some(bracketed) = value.strip_prefix('[') {
+            if let Some((host, rest)) = bracketed.split_once(']') {
+                let port = rest.strip_prefix(':').and_then(|port| port.parse().ok()).unwrap_or(DEFAULT_PORT);
+                return Address::Tcp(host.to_string(), port);
+            }
+        }
+        // More than one colon without brackets: an IPv6 address with no port.
+        if value.matches(':').count() > 1 {
+            return Address::Tcp(value.to_string(), DEFAULT_PORT);
+        }
+        match value.rsplit_once(':').and_then(|(host, port)| Some((host, port.parse().ok()?))) {
+            Some((host, port)) => Address::Tcp(host.to_string(), port),
+            None if value.is_empty() => Address::Tcp("localhost".to_string(), DEFAULT_PORT),
+            None => Address::Tcp(value.to_string(), DEFAULT_PORT),
+        }
+    }
+}
+
+enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PlayState {
+    Play,
+    Pause,
+    #[default]
+    Stop,
+}
+
+impl std::fmt::Display for PlayState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                PlayState::Play => "Lecture",
+                PlayState::Pause => "En pause",
+                PlayState::Stop => "Arrêté",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Status {
+    pub state: PlayState,
+    // None when MPD has no mixer.
+    pub volume: Option<u8>,
+    // Seconds
+    pub elapsed: Option<f64>,
+    pub duration: Option<f64>,
+    // Position of the current song in the queue.
+    pub song: Option<u32>,
+    pub queue_length: u32,
+    // Changes whenever the queue does.
+    pub queue_version: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Song {
+    pub file: String,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub duration: Option<f64>,
+    pub position: Option<u32>,
+}
+
+impl Song {
+    // "Artiste — Titre", or the file name for untagged files and streams.
+    pub fn label(&self) -> String {
+        match (&self.artist, &self.title) {
+            (Some(artist), Some(title)) => [artist.as_str(), " — ", title.as_str()].concat(),
+            (None, Some(title)) => title.clone(),
+            _ => self.file.rsplit('/').next().unwrap_or(&self.file).to_string(),
+        }
+    }
+}
+
+// What the music page shows, read in one go.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snapshot {
+    pub status: Status,
+    pub song: Option<Song>,
+    pub queue: Vec<Song>,
+    pub playlists: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    TogglePause,
+    Next,
+    Previous,
+    VolumeDown,
+    VolumeUp,
+}
+
+pub struct Client {
+    stream: BufReader<Stream>,
+    pub version: String,
+}
+
+impl Client {
+    pub fn connect(address: &Address) -> Result<Client, Box<dyn Error>> {
+        let stream = match address {
+            Address::Tcp(host, port) => {
+                let socket_address = (host.as_str(), *port)
+                    .to_socket_addrs()?
+                    .next()
+                    .ok_or_else(|| format!("adresse inconnue : {}", host))?;
+                let stream = TcpStream::connect_timeout(&socket_address, TIMEOUT)?;
+                stream.set_read_timeout(Some(TIMEOUT))?;
+                Stream::Tcp(stream)
+            }
+            #[cfg(unix)]
+            Address::Unix(path) => {
+                let stream = UnixStream::connect(path)?;
+                stream.set_read_timeout(Some(TIMEOUT))?;
+                Stream::Unix(stream)
+            }
+            #[cfg(not(unix))]
+            Address::Unix(_) => return Err("socket Unix indisponible sur cette plateforme".into()),
+        };
+        let mut stream = BufReader::new(stream);
+        let mut greeting = String::new();
+        stream.read_line(&mut greeting)?;
+        let version = greeting
+            .trim_end()
+            .strip_prefix("OK MPD ")
+            .ok_or_else(|| format!("ce n'est pas un serveur MPD : {}", greeting.trim_end()))?
+            .to_string();
+        let client = Client { stream, version };
+        log::debug!("Connecté à MPD {}", client.version);
+        Ok(client)
+    }
+
+    // Sends one command and returns the `key: value` lines of the response.
+    pub fn command(&mut self, command: &str) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+        writeln!(self.stream.get_mut(), "{}", command)?;
+        let mut pairs = Vec::new();
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.stream.read_line(&mut line)? == 0 {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "connexion fermée par MPD").into());
+            }
+            let line = line.trim_end_matches('\n');
+            if line == "OK" {
+                return Ok(pairs);
+            }
+            if let Some(error) = line.strip_prefix("ACK ") {
+                // "ACK [50@0] {play} No such song": keep the message only.
+                let message = error.split_once("} ").map_or(error, |(_, message)| message);
+                return Err(message.to_string().into());
+            }
+            if let Some((key, value)) = line.split_once(": ") {
+                pairs.push((key.to_string(), value.to_string()));
+            }
+        }
+    }
+
+    pub fn status(&mut self) -> Result<Status, Box<dyn Error>> {
+        Ok(parse_status(&self.command("status")?))
+    }
+
+    pub fn current_song(&mut self) -> Result<Option<Song>, Box<dyn Error>> {
+        Ok(parse_songs(&self.command("currentsong")?).into_iter().next())
+    }
+
+    pub fn queue(&mut self) -> Result<Vec<Song>, Box<dyn Error>> {
+        Ok(parse_songs(&self.command("playlistinfo")?))
+    }
+
+    pub fn playlists(&mut self) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut playlists: Vec<String> = self
+            .command("listplaylists")?
+            .into_iter()
+            .filter(|(key, _)| key == "playlist")
+            .map(|(_, name)| name)
+            .collect();
+        playlists.sort();
+        Ok(playlists)
+    }
+
+    // Runs every second on the music page: the queue is only read again when it changed
+    // and the stored playlists when there is no previous snapshot.
+    pub fn snapshot(&mut self, previous: Option<&Snapshot>) -> Result<Snapshot, Box<dyn Error>> {
+        let status = self.status()?;
+        let song = self.current_song()?;
+        let queue = match previous {
+            Some(previous) if previous.status.queue_version == status.queue_version => previous.queue.clone(),
+            _ => self.queue()?,
+        };
+        let playlists = match previous {
+            Some(previous) => previous.playlists.clone(),
+            None => self.playlists()?,
+        };
+        Ok(Snapshot { status, song, queue, playlists })
+    }
+
+    pub fn play_position(&mut self, position: u32) -> Result<(), Box<dyn Error>> {
+        self.command(&format!("play {}", position)).map(|_| ())
+    }
+
+    // Replaces the queue with a stored playlist and starts it.
+    pub fn play_playlist(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+        self.command("clear")?;
+        self.command(&["load ", &quote(name)].concat())?;
+        self.command("play").map(|_| ())
+    }
+
+    pub fn control(&mut self, control: Control) -> Result<(), Box<dyn Error>> {
+        let command = match control {
+            Control::Next => "next".to_string(),
+            Control::Previous => "previous".to_string(),
+            Control::TogglePause => match self.status()?.state {
+                PlayState::Play => "pause 1".to_string(),
+                PlayState::Pause => "pause 0".to_string(),
+                PlayState::Stop => "play".to_string(),
+            },
+            Control::VolumeDown | Control::VolumeUp => {
+                let volume = self.status()?.volume.ok_or("pas de réglage du volume sur ce serveur")?;
+                let volume = match control {
+                    Control::VolumeUp => volume.saturating_add(VOLUME_STEP).min(100),
+                    _ => volume.saturating_sub(VOLUME_STEP),
+                };
+                format!("setvol {}", volume)
+            }
+        };
+        self.command(&command).map(|_| ())
+    }
+}
+
+// The connection stays open between refreshes and is shared with the background tasks,
+// which take turns on it.
+#[derive(Clone, Default)]
+pub struct Connection(Arc<Mutex<Option<(Address, Client)>>>);
+
+impl std::fmt::Debug for Connection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Connection")
+    }
+}
+
+impl Connection {
+    // Connects first when nothing is open or the address changed. MPD drops idle clients,
+    // so a connection found closed is opened again once; refused commands keep it open.
+    pub fn run<T>(&self, address: &Address, mut request: impl FnMut(&mut Client) -> Result<T, Box<dyn Error>>) -> Result<T, Box<dyn Error>> {
+        let mut open = self.0.lock().map_err(|_| "connexion MPD inutilisable")?;
+        let (reused, mut client) = match open.take() {
+            Some((open_address, client)) if open_address == *address => (true, client),
+            _ => (false, Client::connect(address)?),
+        };
+        let closed = |result: &Result<T, Box<dyn Error>>| matches!(result, Err(e) if e.is::<io::Error>());
+        let mut result = request(&mut client);
+        if reused && closed(&result) {
+            client = Client::connect(address)?;
+            result = request(&mut client);
+        }
+        if !closed(&result) {
+            *open = Some((address.clone(), client));
+        }
+        result
+    }
+}
+
+// The requests block: they run on a thread of their own.
+pub async fn run_in_background<T, F>(connection: Connection, address: Address, request: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnMut(&mut Client) -> Result<T, Box<dyn Error>> + Send + 'static,
+{
+    async_std::task::spawn_blocking(move || connection.run(&address, request).map_err(|e| e.to_string())).await
+}
+
+pub fn retry_delay(failures: u32) -> Duration {
+    Duration::from_secs((1u64 << failures.min(6)).min(MAX_RETRY_SECONDS))
+}
+
+// Arguments go between double quotes, with `"` and `\` escaped.
+pub fn quote(argument: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in argument.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+pub fn parse_status(pairs: &[(String, String)]) -> Status {
+    let mut status = Status::default();
+    for (key, value) in pairs {
+        match key.as_str() {
+            "state" => {
+                status.state = match value.as_str() {
+                    "play" => PlayState::Play,
+                    "pause" => PlayState::Pause,
+                    _ => PlayState::Stop,
+                }
+            }
+            // -1 without a mixer
+            "volume" => status.volume = value.parse().ok(),
+            "elapsed" => status.elapsed = value.parse().ok(),
+            "duration" => status.duration = value.parse().ok(),
+            // Servers older than 0.20 only send "time: elapsed:total".
+            "time" if status.duration.is_none() => {
+                if let Some((elapsed, total)) = value.split_once(':') {
+                    status.elapsed = status.elapsed.or_else(|| elapsed.parse().ok());
+                    status.duration = total.parse().ok().filter(|total| *total > 0.0);
+                }
+            }
+            "song" => status.song = value.parse().ok(),
+            "playlistlength" => status.queue_length = value.parse().unwrap_or(0),
+            "playlist" => status.queue_version = value.parse().unwrap_or(0),
+            _ => {}
+        }
+    }
+    status
+}
+
+// Each song starts with its `file` line.
+pub fn parse_songs(pairs: &[(String, String)]) -> Vec<Song> {
+    let mut songs: Vec<Song> = Vec::new();
+    for (key, value) in pairs {
+        if key == "file" {
+            songs.push(Song { file: value.clone(), ..Song::default() });
+            continue;
+        }
+        let song = match songs.last_mut() {
+            Some(song) => song,
+            None => continue,
+        };
+        match key.as_str() {
+            "Title" => song.title = Some(value.clone()),
+            "Artist" => song.artist = Some(value.clone()),
+            "Album" => song.album = Some(value.clone()),
+            "duration" => song.duration = value.parse().ok(),
+            "Time" if song.duration.is_none() => song.duration = value.parse().ok(),
+            "Pos" => song.position = value.parse().ok(),
+            _ => {}
+        }
+    }
+    songs
+}
+
+pub fn format_time(seconds: f64) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    #[cfg(unix)]
+    use std::os::unix::net::UnixListener;
+    use std::thread;
+
+    // Answers like MPD 0.23 and keeps the commands it received.
+    fn serve<S: Read + Write>(stream: S, commands: Arc<Mutex<Vec<String>>>) {
+        let mut stream = BufReader::new(stream);
+        writeln!(stream.get_mut(), "OK MPD 0.23.5").unwrap();
+        let mut line = String::new();
+        while stream.read_line(&mut line).unwrap_or(0) > 0 {
+            let command = line.trim_end().to_string();
+            line.clear();
+            let response = match command.as_str() {
+                "status" => "volume: 48\nrepeat: 0\nrandom: 0\nplaylist: 12\nsong: 1\nplaylistlength: 2\nstate: play\ntime: 63:215\nelapsed: 62.807\nduration: 215.200\n",
+                "currentsong" => "file: jazz/Kind of Blue/02 Freddie Freeloader.flac\nTitle: Freddie Freeloader\nArtist: Miles Davis\nAlbum: Kind of Blue\nTime: 215\nduration: 215.200\nPos: 1\nId: 8\n",
+                "playlistinfo" => "file: jazz/Kind of Blue/01 So What.flac\nTitle: So What\nArtist: Miles Davis\nduration: 562.000\nPos: 0\nId: 7\nfile: jazz/Kind of Blue/02 Freddie Freeloader.flac\nTitle: Freddie Freeloader\nArtist: Miles Davis\nduration: 215.200\nPos: 1\nId: 8\n",
+                "listplaylists" => "playlist: Soirée\nLast-Modified: 2021-05-01T20:00:00Z\nplaylist: Apéro \"jazz\"\nLast-Modified: 2021-04-01T18:00:00Z\n",
+                "play 9" => "ACK [50@0] {play} song doesn't exist: \"9\"\n",
+                _ => "",
+            };
+            commands.lock().unwrap().push(command);
+            let reply = if response.starts_with("ACK") { response.to_string() } else { [response, "OK\n"].concat() };
+            stream.get_mut().write_all(reply.as_bytes()).unwrap();
+        }
+    }
+
+    fn fake_tcp_server() -> (Address, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let commands = Arc::new(Mutex::new(Vec::new()));
+        let received = commands.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                serve(stream.unwrap(), received.clone());
+            }
+        });
+        (Address::Tcp("127.0.0.1".to_string(), port), commands)
+    }
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(Address::parse(""), Address::Tcp("localhost".to_string(), DEFAULT_PORT));
+        assert_eq!(Address::parse(" salon.local "), Address::Tcp("salon.local".to_string(), DEFAULT_PORT));
+        assert_eq!(Address::parse("192.168.1.20:6601"), Address::Tcp("192.168.1.20".to_string(), 6601));
+        assert_eq!(Address::parse("/run/mpd/socket"), Address::Unix(PathBuf::from("/run/mpd/socket")));
+        assert_eq!(Address::parse("::1"), Address::Tcp("::1".to_string(), DEFAULT_PORT));
+        assert_eq!(Address::parse("[::1]:6601"), Address::Tcp("::1".to_string(), 6601));
+        assert_eq!(Address::parse("[fe80::1]"), Address::Tcp("fe80::1".to_string(), DEFAULT_PORT));
+    }
+
+    #[test]
+    fn test_connection_is_kept() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = Address::Tcp("127.0.0.1".to_string(), listener.local_addr().unwrap().port());
+        let accepted = Arc::new(Mutex::new(0));
+        let commands = Arc::new(Mutex::new(Vec::new()));
+        let (counter, received) = (accepted.clone(), commands.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                *counter.lock().unwrap() += 1;
+                let stream = stream.unwrap();
+                if *counter.lock().unwrap() == 1 {
+                    // The first client is answered once, then dropped as MPD does with idle ones.
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut writer = stream;
+                    writeln!(writer, "OK MPD 0.23.5").unwrap();
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    writer.write_all(b"volume: 10\nOK\n").unwrap();
+                    continue;
+                }
+                serve(stream, received.clone());
+            }
+        });
+
+        let connection = Connection::default();
+        assert_eq!(connection.run(&address, |client| client.status()).unwrap().volume, Some(10));
+        assert_eq!(connection.run(&address, |client| client.status()).unwrap().volume, Some(48));
+        assert_eq!(connection.run(&address, |client| client.status()).unwrap().volume, Some(48));
+        assert!(connection.run(&address, |client| client.play_position(9)).is_err());
+        let volume = async_std::task::block_on(run_in_background(connection, address, |client| client.status()));
+        assert_eq!(volume.unwrap().volume, Some(48));
+        assert_eq!(*accepted.lock().unwrap(), 2);
+        assert_eq!(*commands.lock().unwrap(), vec!["status", "status", "play 9", "status"]);
+
+        let unreachable = Address::Tcp("127.0.0.1".to_string(), 1);
+        assert!(Connection::default().run(&unreachable, |client| client.status()).is_err());
+        assert_eq!(retry_delay(1), Duration::from_secs(2));
+        assert_eq!(retry_delay(40), Duration::from_secs(MAX_RETRY_SECONDS));
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let (address, commands) = fake_tcp_server();
+        let mut client = Client::connect(&address).unwrap();
+        assert_eq!(client.version, "0.23.5");
+        let snapshot = client.snapshot(None).unwrap();
+        assert_eq!(snapshot.status, Status {
+            state: PlayState::Play,
+            volume: Some(48),
+            elapsed: Some(62.807),
+            duration: Some(215.2),
+            song: Some(1),
+            queue_length: 2,
+            queue_version: 12,
+        });
+        let song = snapshot.song.clone().unwrap();
+        assert_eq!(song.label(), "Miles Davis — Freddie Freeloader");
+        assert_eq!(song.album.as_deref(), Some("Kind of Blue"));
+        assert_eq!(song.duration, Some(215.2));
+        assert_eq!(snapshot.queue.iter().map(|song| song.position).collect::<Vec<_>>(), vec![Some(0), Some(1)]);
+        assert_eq!(snapshot.queue[0].duration, Some(562.0));
+        assert_eq!(snapshot.playlists, vec!["Apéro \"jazz\"", "Soirée"]);
+        assert_eq!(client.snapshot(Some(&snapshot)).unwrap(), snapshot);
+        let mut outdated = snapshot.clone();
+        outdated.status.queue_version = 11;
+        outdated.queue.clear();
+        assert_eq!(client.snapshot(Some(&outdated)).unwrap().queue, snapshot.queue);
+        assert_eq!(*commands.lock().unwrap(), vec![
+            "status",
+            "currentsong",
+            "playlistinfo",
+            "listplaylists",
+            "status",
+            "currentsong",
+            "status",
+            "currentsong",
+            "playlistinfo",
+        ]);
+    }
+
+    #[test]
+    fn test_controls() {
+        let (address, commands) = fake_tcp_server();
+        let mut client = Client::connect(&address).unwrap();
+        client.control(Control::TogglePause).unwrap();
+        client.control(Control::VolumeUp).unwrap();
+        client.control(Control::Next).unwrap();
+        client.play_playlist("Apéro \"jazz\"").unwrap();
+        assert_eq!(client.play_position(9).unwrap_err().to_string(), "song doesn't exist: \"9\"");
+        // The connection is still usable after an error.
+        client.play_position(0).unwrap();
+        assert_eq!(*commands.lock().unwrap(), vec![
+            "status",
+            "pause 1",
+            "status",
+            "setvol 53",
+            "next",
+            "clear",
+            r#"load "Apéro \"jazz\"""#,
+            "play",
+            "play 9",
+            "play 0",
+        ]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix_socket() {
+        let path = std::env::temp_dir().join(format!("assistant_gui_mpd_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let commands = Arc::new(Mutex::new(Vec::new()));
+        let received = commands.clone();
+        thread::spawn(move || serve(listener.accept().unwrap().0, received));
+
+        let mut client = Client::connect(&Address::parse(path.to_str().unwrap())).unwrap();
+        assert_eq!(client.status().unwrap().volume, Some(48));
+        assert_eq!(*commands.lock().unwrap(), vec!["status"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_parse_old_servers_and_streams() {
+        let pairs: Vec<(String, String)> = vec![("volume", "-1"), ("state", "pause"), ("time", "12:0")]
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        let status = parse_status(&pairs);
+        assert_eq!((status.state, status.volume, status.elapsed, status.duration), (PlayState::Pause, None, Some(12.0), None));
+        let stream = Song { file: "http://icecast.example/fip.mp3".to_string(), ..Song::default() };
+        assert_eq!(stream.label(), "fip.mp3");
+        assert_eq!(format_time(215.2), "3:35");
+    }
+}
@@ -0,0 +1,327 @@
+use rumqttc::{Client, Event, MqttOptions, Outgoing, Packet, QoS};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::plugins::remote::{self, Queue};
+
+pub const DEFAULT_PORT: u16 = 1883;
+pub const DEFAULT_COMMAND_TOPIC: &str = "assistant_gui/command";
+const CLIENT_ID: &str = "assistant_gui";
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+// A value shown on the MQTT page, e.g. `Salon=maison/salon/temperature`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tile {
+    pub label: String,
+    pub topic: String,
+}
+
+// A button publishing a fixed payload, e.g. `Lumière=maison/lumiere/set:ON`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Action {
+    pub label: String,
+    pub topic: String,
+    pub payload: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MqttSettings {
+    pub broker: String,
+    pub username: String,
+    pub password: String,
+    pub command_topic: String,
+    pub tiles: Vec<Tile>,
+}
+
+// Last payload received on each topic, filled by the connection thread.
+#[derive(Debug, Clone, Default)]
+pub struct Readings {
+    pub connected: bool,
+    pub values: HashMap<String, String>,
+}
+
+pub type SharedReadings = Arc<Mutex<Readings>>;
+
+pub fn parse_broker(value: &str) -> Option<(String, u16)> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    let mut parts = value.splitn(2, ':');
+    let host = parts.next().unwrap_or_default().to_string();
+    match parts.next() {
+        Some(port) => port.trim().parse().ok().map(|port| (host, port)),
+        None => Some((host, DEFAULT_PORT)),
+    }
+}
+
+fn labelled(value: &str) -> Vec<(String, String)> {
+    value
+        .split(';')
+        .filter_map(|entry| {
+            let mut parts = entry.splitn(2, '=');
+            let label = parts.next()?.trim();
+            let rest = parts.next()?.trim();
+            if label.is_empty() || rest.is_empty() {
+                None
+            } else {
+                Some((label.to_string(), rest.to_string()))
+            }
+        })
+        .collect()
+}
+
+pub fn parse_tiles(value: &str) -> Vec<Tile> {
+    labelled(value)
+        .into_iter()
+        .map(|(label, topic)| Tile { label, topic })
+        .collect()
+}
+
+pub fn parse_actions(value: &str) -> Vec<Action> {
+    labelled(value)
+        .into_iter()
+        .filter_map(|(label, target)| {
+            let mut parts = target.splitn(2, ':');
+            let topic = parts.next()?.trim().to_string();
+            let payload = parts.next()?.trim().to_string();
+            Some(Action { label, topic, payload })
+        })
+        .collect()
+}
+
+pub fn command_topic(value: &str) -> String {
+    match value.trim() {
+        "" => DEFAULT_COMMAND_TOPIC.to_string(),
+        topic => topic.to_string(),
+    }
+}
+
+// Commands use the same JSON as the control socket; anything else is a tile value.
+pub fn handle_publish(topic: &str, payload: &[u8], command_topic: &str, readings: &SharedReadings, queue: &Queue) {
+    let payload = String::from_utf8_lossy(payload);
+    if topic == command_topic {
+        if let Err(e) = remote::handle_line(&payload, queue) {
+            log::warn!("Commande MQTT refusée : {}", e);
+        }
+    } else if let Ok(mut readings) = readings.lock() {
+        readings.values.insert(topic.to_string(), payload.trim().to_string());
+    }
+}
+
+pub struct MqttConnection {
+    client: Client,
+    stopped: Arc<AtomicBool>,
+    pub readings: SharedReadings,
+}
+
+impl std::fmt::Debug for MqttConnection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MqttConnection")
+            .field("readings", &self.readings)
+            .finish()
+    }
+}
+
+impl MqttConnection {
+    // Returns None when no broker is configured; the connection is retried in the background.
+    pub fn connect(settings: &MqttSettings, queue: Queue) -> Option<MqttConnection> {
+        let (host, port) = parse_broker(&settings.broker)?;
+        let mut options = MqttOptions::new(format!("{}-{}", CLIENT_ID, std::process::id()), host, port);
+        options.set_keep_alive(Duration::from_secs(30));
+        if !settings.username.is_empty() {
+            options.set_credentials(settings.username.clone(), settings.password.clone());
+        }
+
+        let (client, mut connection) = Client::new(options, 10);
+        let readings = SharedReadings::default();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let mut topics: Vec<String> = settings.tiles.iter().map(|tile| tile.topic.clone()).collect();
+        topics.push(command_topic(&settings.command_topic));
+
+        let thread_client = client.clone();
+        let thread_readings = readings.clone();
+        let thread_stopped = stopped.clone();
+        std::thread::spawn(move || {
+            let command_topic = topics.last().cloned().unwrap_or_default();
+            for notification in connection.iter() {
+                if thread_stopped.load(Ordering::Relaxed) {
+                    break;
+                }
+                match notification {
+                    // Subscriptions are renewed on every connection since the session is not kept.
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        if let Ok(mut readings) = thread_readings.lock() {
+                            readings.connected = true;
+                        }
+                        for topic in &topics {
+                            let _ = thread_client.try_subscribe(topic.as_str(), QoS::AtLeastOnce);
+                        }
+                    }
+                    Ok(Event::Incoming(Packet::Publish(publish))) => {
+                        handle_publish(&publish.topic, &publish.payload, &command_topic, &thread_readings, &queue);
+                    }
+                    Ok(Event::Outgoing(Outgoing::Disconnect)) => break,
+                    Ok(_) => {}
+                    Err(e) => {
+                        log::warn!("Connexion MQTT perdue : {}", e);
+                        if let Ok(mut readings) = thread_readings.lock() {
+                            readings.connected = false;
+                        }
+                        std::thread::sleep(RECONNECT_DELAY);
+                    }
+                }
+            }
+        });
+
+        Some(MqttConnection { client, stopped, readings })
+    }
+
+    pub fn publish(&self, action: &Action) -> Result<(), String> {
+        self.client
+            .try_publish(action.topic.as_str(), QoS::AtLeastOnce, false, action.payload.as_bytes())
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn disconnect(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        let _ = self.client.try_disconnect();
+    }
+
+    pub fn value(&self, topic: &str) -> Option<String> {
+        self.readings.lock().ok()?.values.get(topic).cloned()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.readings.lock().map(|readings| readings.connected).unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_parse_settings() {
+        assert_eq!(parse_broker("localhost"), Some(("localhost".to_string(), DEFAULT_PORT)));
+        assert_eq!(parse_broker("192.168.1.2:1884"), Some(("192.168.1.2".to_string(), 1884)));
+        assert_eq!(parse_broker(" "), None);
+        assert_eq!(parse_broker("localhost:port"), None);
+        assert_eq!(parse_tiles("Salon=maison/salon/temperature; Porte = maison/porte ;invalide"), vec![
+            Tile { label: "Salon".to_string(), topic: "maison/salon/temperature".to_string() },
+            Tile { label: "Porte".to_string(), topic: "maison/porte".to_string() },
+        ]);
+        assert_eq!(parse_actions("Lumière=maison/lumiere/set:ON;Sans charge=maison/x"), vec![
+            Action { label: "Lumière".to_string(), topic: "maison/lumiere/set".to_string(), payload: "ON".to_string() },
+        ]);
+        assert_eq!(command_topic(""), DEFAULT_COMMAND_TOPIC);
+    }
+
+    #[test]
+    fn test_handle_publish() {
+        let readings = SharedReadings::default();
+        let queue = Queue::default();
+        handle_publish("maison/porte", b"ouverte\n", DEFAULT_COMMAND_TOPIC, &readings, &queue);
+        handle_publish(DEFAULT_COMMAND_TOPIC, br#"{"command": "show_page", "page": "mqtt"}"#, DEFAULT_COMMAND_TOPIC, &readings, &queue);
+        handle_publish(DEFAULT_COMMAND_TOPIC, b"n'importe quoi", DEFAULT_COMMAND_TOPIC, &readings, &queue);
+        assert_eq!(readings.lock().unwrap().values.get("maison/porte").map(String::as_str), Some("ouverte"));
+        assert_eq!(remote::drain(&queue), vec![remote::RemoteCommand::ShowPage { page: "mqtt".to_string() }]);
+    }
+
+    // Just enough of an MQTT 3.1.1 broker to accept one client.
+    fn read_packet(stream: &mut TcpStream) -> Option<(u8, Vec<u8>)> {
+        let mut header = [0u8; 1];
+        stream.read_exact(&mut header).ok()?;
+        let (mut length, mut shift) = (0usize, 0);
+        loop {
+            let mut byte = [0u8; 1];
+            stream.read_exact(&mut byte).ok()?;
+            length += ((byte[0] & 0x7F) as usize) << shift;
+            shift += 7;
+            if byte[0] & 0x80 == 0 {
+                break;
+            }
+        }
+        let mut body = vec![0u8; length];
+        stream.read_exact(&mut body).ok()?;
+        Some((header[0] >> 4, body))
+    }
+
+    fn publish_packet(topic: &str, payload: &[u8]) -> Vec<u8> {
+        let mut packet = vec![0x30, (2 + topic.len() + payload.len()) as u8, 0, topic.len() as u8];
+        packet.extend_from_slice(topic.as_bytes());
+        packet.extend_from_slice(payload);
+        packet
+    }
+
+    fn fake_broker(retained: Vec<(&'static str, &'static [u8])>) -> (u16, mpsc::Receiver<(String, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (published, received) = mpsc::channel();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            while let Some((kind, body)) = read_packet(&mut stream) {
+                match kind {
+                    1 => stream.write_all(&[0x20, 0x02, 0x00, 0x00]).unwrap(),
+                    3 => {
+                        let length = ((body[0] as usize) << 8) + body[1] as usize;
+                        let topic = String::from_utf8_lossy(&body[2..2 + length]).to_string();
+                        let payload = String::from_utf8_lossy(&body[2 + length + 2..]).to_string();
+                        stream.write_all(&[0x40, 0x02, body[2 + length], body[2 + length + 1]]).unwrap();
+                        let _ = published.send((topic, payload));
+                    }
+                    8 => {
+                        stream.write_all(&[0x90, 0x03, body[0], body[1], 0x01]).unwrap();
+                        let length = ((body[2] as usize) << 8) + body[3] as usize;
+                        let topic = String::from_utf8_lossy(&body[4..4 + length]).to_string();
+                        for (retained_topic, payload) in &retained {
+                            if *retained_topic == topic {
+                                stream.write_all(&publish_packet(retained_topic, payload)).unwrap();
+                            }
+                        }
+                    }
+                    12 => stream.write_all(&[0xD0, 0x00]).unwrap(),
+                    _ => {}
+                }
+            }
+        });
+        (port, received)
+    }
+
+    fn wait_for<F: Fn() -> bool>(condition: F) -> bool {
+        (0..100).any(|_| {
+            std::thread::sleep(Duration::from_millis(20));
+            condition()
+        })
+    }
+
+    #[test]
+    fn test_connection_with_local_broker() {
+        let (port, published) = fake_broker(vec![
+            ("maison/salon/temperature", b"21.5"),
+            (DEFAULT_COMMAND_TOPIC, br#"{"command": "notify", "title": "Sonnette"}"#),
+        ]);
+        let settings = MqttSettings {
+            broker: format!("127.0.0.1:{}", port),
+            tiles: parse_tiles("Salon=maison/salon/temperature"),
+            ..MqttSettings::default()
+        };
+        let queue = Queue::default();
+        let connection = MqttConnection::connect(&settings, queue.clone()).unwrap();
+
+        assert!(wait_for(|| connection.value("maison/salon/temperature").is_some()));
+        assert!(connection.is_connected());
+        assert_eq!(connection.value("maison/salon/temperature"), Some("21.5".to_string()));
+        assert!(wait_for(|| !queue.lock().unwrap().is_empty()));
+        assert!(matches!(remote::drain(&queue)[..], [remote::RemoteCommand::Notify(_)]));
+
+        connection.publish(&parse_actions("Lumière=maison/lumiere/set:ON")[0]).unwrap();
+        assert_eq!(published.recv_timeout(Duration::from_secs(2)).unwrap(), ("maison/lumiere/set".to_string(), "ON".to_string()));
+        connection.disconnect();
+    }
+}
@@ -2,9 +2,11 @@ use std::io::BufReader;
 use rss::Channel;
 use std::error::Error;
 
+use crate::plugins::status::NewsItem;
+
 fn get_news_from_newspaper(url_newspaper:&str) -> Result<Channel, Box<dyn Error>> {
     let content = reqwest::blocking::get(url_newspaper)?;
-    let channel = Channel::read_from(BufReader::new(content)).unwrap();
+    let channel = Channel::read_from(BufReader::new(content))?;
     Ok(channel)
 }
 
@@ -16,9 +18,26 @@ pub fn get_news(newspaper:Newspaper) -> Result<Channel, Box<dyn Error>> {
     }
 }
 
+// The download blocks: it runs on a thread of its own.
+pub async fn fetch_in_background(newspaper: Newspaper) -> Result<Channel, String> {
+    async_std::task::spawn_blocking(move || get_news(newspaper).map_err(|e| e.to_string())).await
+}
+
+pub fn items(channel: &Channel) -> Vec<NewsItem> {
+    channel.items()
+        .iter()
+        .map(|item| NewsItem {
+            title: item.title().unwrap_or("").to_string(),
+            link: item.link().unwrap_or("").to_string(),
+            pub_date: item.pub_date().unwrap_or("").to_string(),
+            description: item.description().unwrap_or("").to_string(),
+        })
+        .collect()
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum Newspaper {
+    #[default]
     Lefigaro,
     Lemonde,
     Marianne,
@@ -32,12 +51,6 @@ impl Newspaper {
     ];
 }
 
-impl Default for Newspaper {
-    fn default() -> Newspaper {
-        Newspaper::Lefigaro
-    }
-}
-
 impl std::fmt::Display for Newspaper {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -53,6 +66,7 @@ impl std::fmt::Display for Newspaper {
 }
 
 #[cfg(test)]
+#[allow(clippy::nonminimal_bool)]
 mod tests {
     use super::*;
 
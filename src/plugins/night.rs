@@ -0,0 +1,107 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::plugins::astronomy::{self, SunTimes};
+
+pub fn parse_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
+
+// The window may wrap past midnight, e.g. 22:00 to 07:00.
+pub fn in_window(time: NaiveTime, start: NaiveTime, end: NaiveTime) -> bool {
+    if start <= end {
+        time >= start && time < end
+    } else {
+        time >= start || time < end
+    }
+}
+
+pub fn is_night(schedule: NightSchedule, now: DateTime<Local>, start: &str, end: &str, coordinates: Option<(f64, f64)>) -> bool {
+    match schedule {
+        NightSchedule::Off => false,
+        NightSchedule::Fixed => match (parse_time(start), parse_time(end)) {
+            (Some(start), Some(end)) => in_window(now.time(), start, end),
+            _ => false,
+        },
+        NightSchedule::Sun => match coordinates {
+            Some((latitude, longitude)) => {
+                match astronomy::sunrise_sunset(now.naive_local().date(), latitude, longitude) {
+                    SunTimes::Rises { rise, set } => {
+                        let now = now.with_timezone(&Utc);
+                        now < rise || now >= set
+                    }
+                    SunTimes::AlwaysUp => false,
+                    SunTimes::AlwaysDown => true,
+                }
+            }
+            None => false,
+        },
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NightSchedule {
+    #[default]
+    Off,
+    Fixed,
+    Sun,
+}
+
+impl NightSchedule {
+    pub const ALL: [NightSchedule; 3] = [
+        NightSchedule::Off,
+        NightSchedule::Fixed,
+        NightSchedule::Sun,
+    ];
+}
+
+impl std::fmt::Display for NightSchedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                NightSchedule::Off => "Désactivé",
+                NightSchedule::Fixed => "Horaires fixes",
+                NightSchedule::Sun => "Lever et coucher du soleil",
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_in_window() {
+        assert!(in_window(time(23, 0), time(22, 0), time(7, 0)));
+        assert!(in_window(time(6, 59), time(22, 0), time(7, 0)));
+        assert!(!in_window(time(7, 0), time(22, 0), time(7, 0)));
+        assert!(in_window(time(13, 0), time(12, 0), time(14, 0)));
+        assert!(!in_window(time(23, 0), time(12, 0), time(14, 0)));
+    }
+
+    #[test]
+    fn test_is_night_fixed() {
+        let evening = Local.from_local_datetime(&NaiveDate::from_ymd_opt(2021, 6, 21).unwrap().and_hms_opt(23, 30, 0).unwrap()).unwrap();
+        assert!(is_night(NightSchedule::Fixed, evening, "22:00", "07:00", None));
+        assert!(!is_night(NightSchedule::Fixed, evening, "22:00", "", None));
+        assert!(!is_night(NightSchedule::Off, evening, "22:00", "07:00", None));
+    }
+
+    #[test]
+    fn test_is_night_sun() {
+        let date = NaiveDate::from_ymd_opt(2021, 6, 21).unwrap();
+        let paris = Some((48.8566, 2.3522));
+        let noon = Utc.from_utc_datetime(&date.and_hms_opt(12, 0, 0).unwrap()).with_timezone(&Local);
+        let midnight = Utc.from_utc_datetime(&date.and_hms_opt(23, 0, 0).unwrap()).with_timezone(&Local);
+        assert!(!is_night(NightSchedule::Sun, noon, "", "", paris));
+        assert!(is_night(NightSchedule::Sun, midnight, "", "", paris));
+        assert!(!is_night(NightSchedule::Sun, midnight, "", "", None));
+    }
+}
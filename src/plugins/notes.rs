@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+
+use crate::plugins::state::{self, LoadError, SaveError};
+
+pub const FILE_NAME: &str = "notes.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Note {
+    pub text: String,
+    pub color: NoteColor,
+    pub pinned: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NoteBoard {
+    pub notes: Vec<Note>,
+}
+
+impl NoteBoard {
+    pub fn add(&mut self, text: &str, color: NoteColor) -> bool {
+        let text = text.trim();
+        if text.is_empty() {
+            return false;
+        }
+        self.notes.push(Note {
+            text: text.to_string(),
+            color,
+            pinned: false,
+        });
+        true
+    }
+
+    pub fn edit(&mut self, index: usize, text: String) {
+        if let Some(note) = self.notes.get_mut(index) {
+            note.text = text;
+        }
+    }
+
+    pub fn cycle_color(&mut self, index: usize) {
+        if let Some(note) = self.notes.get_mut(index) {
+            note.color = note.color.next();
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.notes.len() {
+            self.notes.remove(index);
+        }
+    }
+
+    // Only one note can be pinned to the clock screen at a time.
+    pub fn toggle_pin(&mut self, index: usize) {
+        let pinned = match self.notes.get(index) {
+            Some(note) => !note.pinned,
+            None => return,
+        };
+        for note in self.notes.iter_mut() {
+            note.pinned = false;
+        }
+        self.notes[index].pinned = pinned;
+    }
+
+    pub fn pinned(&self) -> Option<&Note> {
+        self.notes.iter().find(|note| note.pinned)
+    }
+
+    pub async fn load() -> Result<NoteBoard, LoadError> {
+        state::load_json(FILE_NAME).await
+    }
+
+    pub async fn save(self) -> Result<(), SaveError> {
+        state::save_json(FILE_NAME, &self).await
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NoteColor {
+    #[default]
+    Yellow,
+    Green,
+    Blue,
+    Pink,
+    Orange,
+}
+
+impl NoteColor {
+    pub const ALL: [NoteColor; 5] = [
+        NoteColor::Yellow,
+        NoteColor::Green,
+        NoteColor::Blue,
+        NoteColor::Pink,
+        NoteColor::Orange,
+    ];
+
+    pub fn next(self) -> NoteColor {
+        let index = NoteColor::ALL.iter().position(|c| *c == self).unwrap_or(0);
+        NoteColor::ALL[(index + 1) % NoteColor::ALL.len()]
+    }
+}
+
+impl std::fmt::Display for NoteColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                NoteColor::Yellow => "Jaune",
+                NoteColor::Green => "Vert",
+                NoteColor::Blue => "Bleu",
+                NoteColor::Pink => "Rose",
+                NoteColor::Orange => "Orange",
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board() -> NoteBoard {
+        let mut board = NoteBoard::default();
+        board.add("Wifi code: 1234", NoteColor::Yellow);
+        board.add("Arroser les plantes", NoteColor::Green);
+        board
+    }
+
+    #[test]
+    fn test_toggle_pin_keeps_a_single_pinned_note() {
+        let mut board = board();
+        board.toggle_pin(0);
+        assert_eq!(board.pinned().unwrap().text, "Wifi code: 1234");
+        board.toggle_pin(1);
+        assert_eq!(board.pinned().unwrap().text, "Arroser les plantes");
+        assert!(!board.notes[0].pinned);
+        board.toggle_pin(1);
+        assert!(board.pinned().is_none());
+        board.toggle_pin(7);
+        assert!(board.pinned().is_none());
+    }
+
+    #[test]
+    fn test_cycle_color() {
+        let mut board = board();
+        board.cycle_color(1);
+        assert_eq!(board.notes[1].color, NoteColor::Blue);
+        assert_eq!(NoteColor::Orange.next(), NoteColor::Yellow);
+    }
+
+    #[test]
+    fn test_add_edit_remove() {
+        let mut board = board();
+        assert!(!board.add("", NoteColor::Pink));
+        board.edit(0, "Wifi code: 5678".to_string());
+        assert_eq!(board.notes[0].text, "Wifi code: 5678");
+        board.remove(0);
+        assert_eq!(board.notes.len(), 1);
+    }
+}
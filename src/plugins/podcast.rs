@@ -0,0 +1,160 @@
+use reqwest::Url;
+use rss::Channel;
+use std::error::Error;
+use std::io::{BufRead, BufReader};
+
+pub const EPISODES_PER_FEED: usize = 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Episode {
+    pub title: String,
+    pub url: String,
+    pub published: String,
+    // Identifies the episode for its saved position: the guid, or the enclosure url without one.
+    pub key: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Podcast {
+    pub title: String,
+    pub episodes: Vec<Episode>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Station {
+    pub name: String,
+    pub url: String,
+}
+
+// Anything else (a local file, an option for the player) is refused.
+pub fn is_web_url(url: &str) -> bool {
+    matches!(Url::parse(url), Ok(url) if url.scheme() == "http" || url.scheme() == "https")
+}
+
+pub fn parse_feeds(value: &str) -> Vec<String> {
+    value.split(';').map(str::trim).filter(|url| !url.is_empty()).map(str::to_string).collect()
+}
+
+// "FIP=http://...;France Culture=http://..."
+pub fn parse_stations(value: &str) -> Vec<Station> {
+    value
+        .split(';')
+        .filter_map(|station| {
+            let (name, url) = station.split_once('=')?;
+            let (name, url) = (name.trim(), url.trim());
+            if name.is_empty() || !is_web_url(url) {
+                return None;
+            }
+            Some(Station { name: name.to_string(), url: url.to_string() })
+        })
+        .collect()
+}
+
+// Items without an audio enclosure (announcements, articles) or whose enclosure is not on the web are left out.
+pub fn read_podcast<R: BufRead>(reader: R) -> Result<Podcast, Box<dyn Error>> {
+    let channel = Channel::read_from(reader)?;
+    let episodes = channel
+        .items()
+        .iter()
+        .filter_map(|item| {
+            let url = item.enclosure()?.url().trim();
+            if !is_web_url(url) {
+                return None;
+            }
+            Some(Episode {
+                title: item.title().unwrap_or("Sans titre").to_string(),
+                url: url.to_string(),
+                published: item.pub_date().unwrap_or("").to_string(),
+                key: item.guid().map(|guid| guid.value()).unwrap_or(url).to_string(),
+            })
+        })
+        .take(EPISODES_PER_FEED)
+        .collect();
+    Ok(Podcast { title: channel.title().to_string(), episodes })
+}
+
+pub fn get_podcast(url: &str) -> Result<Podcast, Box<dyn Error>> {
+    let content = reqwest::blocking::get(url)?;
+    read_podcast(BufReader::new(content))
+}
+
+// The downloads block: the feeds are read one after the other on a thread of their own.
+pub async fn fetch_in_background(urls: Vec<String>) -> Vec<Result<Podcast, String>> {
+    async_std::task::spawn_blocking(move || {
+        urls.iter()
+            .map(|url| get_podcast(url).map_err(|e| [url.clone(), " : ".to_string(), e.to_string()].concat()))
+            .collect()
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_podcast() {
+        let feed = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0"><channel>
+<title>Les Pieds sur terre</title>
+<link>https://example.org</link>
+<description>Documentaires</description>
+<item>
+  <title>La maison du bout du monde</title>
+  <pubDate>Mon, 03 May 2021 05:00:00 +0200</pubDate>
+  <guid isPermaLink="false">episode-1042</guid>
+  <enclosure url="https://example.org/1042.mp3" length="28000000" type="audio/mpeg"/>
+</item>
+<item>
+  <title>Annonce</title>
+  <link>https://example.org/annonce</link>
+</item>
+<item>
+  <enclosure url="https://example.org/1041.mp3" length="27000000" type="audio/mpeg"/>
+</item>
+<item>
+  <title>Piège</title>
+  <enclosure url="--script=/tmp/evil.lua" length="1" type="audio/mpeg"/>
+</item>
+<item>
+  <title>Fichier local</title>
+  <enclosure url="file:///etc/passwd" length="1" type="audio/mpeg"/>
+</item>
+</channel></rss>"#;
+        let podcast = read_podcast(feed.as_bytes()).unwrap();
+        assert_eq!(podcast.title, "Les Pieds sur terre");
+        assert_eq!(podcast.episodes, vec![
+            Episode {
+                title: "La maison du bout du monde".to_string(),
+                url: "https://example.org/1042.mp3".to_string(),
+                published: "Mon, 03 May 2021 05:00:00 +0200".to_string(),
+                key: "episode-1042".to_string(),
+            },
+            Episode {
+                title: "Sans titre".to_string(),
+                url: "https://example.org/1041.mp3".to_string(),
+                published: String::new(),
+                key: "https://example.org/1041.mp3".to_string(),
+            },
+        ]);
+        assert!(read_podcast("pas un flux".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_is_web_url() {
+        assert!(is_web_url("http://icecast.example/fip.mp3"));
+        assert!(is_web_url("https://example.org/1042.mp3"));
+        assert!(!is_web_url("--script=/tmp/evil.lua"));
+        assert!(!is_web_url("file:///etc/passwd"));
+        assert!(!is_web_url(""));
+    }
+
+    #[test]
+    fn test_parse_settings() {
+        assert_eq!(parse_feeds(" https://a.org/feed.xml ;; https://b.org/rss "), vec!["https://a.org/feed.xml", "https://b.org/rss"]);
+        assert_eq!(parse_stations("FIP=https://icecast.example/fip.mp3; =nothing;Radio sans url=;Option=--script=x.lua;France Inter = https://icecast.example/inter.mp3"), vec![
+            Station { name: "FIP".to_string(), url: "https://icecast.example/fip.mp3".to_string() },
+            Station { name: "France Inter".to_string(), url: "https://icecast.example/inter.mp3".to_string() },
+        ]);
+    }
+}
@@ -0,0 +1,238 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::VecDeque;
+use std::error::Error;
+#[cfg(unix)]
+use std::io::{BufRead, BufReader, Write};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::plugins::config;
+
+pub const SOCKET_FILE_NAME: &str = "control.sock";
+pub const DEFAULT_DURATION_SECONDS: u64 = 10;
+pub const MAX_DURATION_SECONDS: u64 = 3600;
+
+// One JSON object per line, e.g. `{"command": "show_page", "page": "meteo"}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum RemoteCommand {
+    ShowPage { page: String },
+    Refresh {
+        #[serde(default)]
+        page: Option<String>,
+    },
+    Notify(Notification),
+}
+
+impl RemoteCommand {
+    pub fn validate(&self) -> Result<(), String> {
+        let page = match self {
+            RemoteCommand::ShowPage { page } => Some(page),
+            RemoteCommand::Refresh { page } => page.as_ref(),
+            RemoteCommand::Notify(notification) => {
+                if notification.title.trim().is_empty() {
+                    return Err(String::from("notification without title"));
+                }
+                None
+            }
+        };
+        match page {
+            Some(page) if !config::PAGES.contains(&page.as_str()) => Err(format!("unknown page {}", page)),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    #[serde(default)]
+    pub level: Level,
+    #[serde(default = "default_duration", deserialize_with = "deserialize_duration")]
+    pub duration: u64,
+}
+
+impl Notification {
+    pub fn expires(&self, shown: DateTime<Local>) -> DateTime<Local> {
+        shown + chrono::Duration::seconds(clamp_duration(self.duration) as i64)
+    }
+}
+
+fn default_duration() -> u64 {
+    DEFAULT_DURATION_SECONDS
+}
+
+// A banner stays between a second and an hour, whatever the sender asked for.
+pub fn clamp_duration(seconds: u64) -> u64 {
+    seconds.clamp(1, MAX_DURATION_SECONDS)
+}
+
+fn deserialize_duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    u64::deserialize(deserializer).map(clamp_duration)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Level {
+    #[default]
+    Info,
+    Warning,
+    Error,
+}
+
+impl Level {
+    pub const NAMES: [&'static str; 3] = ["info", "warning", "error"];
+
+    pub fn from_name(name: &str) -> Option<Level> {
+        match name {
+            "info" => Some(Level::Info),
+            "warning" => Some(Level::Warning),
+            "error" => Some(Level::Error),
+            _ => None,
+        }
+    }
+}
+
+// Commands wait here until the next tick of the GUI picks them up.
+pub type Queue = Arc<Mutex<VecDeque<RemoteCommand>>>;
+
+pub fn drain(queue: &Queue) -> Vec<RemoteCommand> {
+    match queue.lock() {
+        Ok(mut queue) => queue.drain(..).collect(),
+        Err(_e) => Vec::new(),
+    }
+}
+
+// Parses one command and queues it, or tells why it was refused.
+pub fn handle_line(line: &str, queue: &Queue) -> Result<(), String> {
+    let command = serde_json::from_str::<RemoteCommand>(line).map_err(|e| e.to_string())?;
+    command.validate()?;
+    if let Ok(mut queue) = queue.lock() {
+        queue.push_back(command);
+    }
+    Ok(())
+}
+
+// The answer of the control socket and of the status API.
+pub fn reply(result: &Result<(), String>) -> String {
+    match result {
+        Ok(()) => serde_json::json!({"ok": true}).to_string(),
+        Err(e) => serde_json::json!({"ok": false, "error": e}).to_string(),
+    }
+}
+
+#[cfg(unix)]
+pub fn listen(path: &Path, queue: Queue) -> std::io::Result<()> {
+    // A socket left over by a previous run would make bind fail.
+    if path.exists() && UnixStream::connect(path).is_err() {
+        std::fs::remove_file(path)?;
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let queue = queue.clone();
+            std::thread::spawn(move || {
+                let mut writer = match stream.try_clone() {
+                    Ok(writer) => writer,
+                    Err(_e) => return,
+                };
+                for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    if writeln!(writer, "{}", reply(&handle_line(&line, &queue))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+#[cfg(unix)]
+pub fn send(path: &Path, command: &RemoteCommand) -> Result<String, Box<dyn Error>> {
+    let mut stream = UnixStream::connect(path)?;
+    writeln!(stream, "{}", serde_json::to_string(command)?)?;
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    Ok(reply.trim().to_string())
+}
+
+// The control socket is a Unix domain socket; elsewhere only the status API takes commands.
+#[cfg(not(unix))]
+pub fn listen(_path: &Path, _queue: Queue) -> std::io::Result<()> {
+    Err(std::io::Error::new(std::io::ErrorKind::Other, "no control socket on this platform"))
+}
+
+#[cfg(not(unix))]
+pub fn send(_path: &Path, _command: &RemoteCommand) -> Result<String, Box<dyn Error>> {
+    Err("no control socket on this platform".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        let command: RemoteCommand = serde_json::from_str(r#"{"command": "show_page", "page": "meteo"}"#).unwrap();
+        assert_eq!(command, RemoteCommand::ShowPage { page: "meteo".to_string() });
+        let command: RemoteCommand = serde_json::from_str(r#"{"command": "refresh"}"#).unwrap();
+        assert_eq!(command, RemoteCommand::Refresh { page: None });
+        let command: RemoteCommand = serde_json::from_str(r#"{"command": "notify", "title": "Sonnette", "level": "warning"}"#).unwrap();
+        assert_eq!(command, RemoteCommand::Notify(Notification {
+            title: "Sonnette".to_string(),
+            body: String::new(),
+            level: Level::Warning,
+            duration: DEFAULT_DURATION_SECONDS,
+        }));
+    }
+
+    #[test]
+    fn test_handle_line() {
+        let queue = Queue::default();
+        assert_eq!(handle_line(r#"{"command": "show_page", "page": "news"}"#, &queue), Ok(()));
+        assert_eq!(handle_line(r#"{"command": "show_page", "page": "nowhere"}"#, &queue), Err("unknown page nowhere".to_string()));
+        assert!(handle_line(r#"{"command": "notify", "title": " "}"#, &queue).is_err());
+        assert!(handle_line("not json", &queue).is_err());
+        assert_eq!(reply(&Ok(())), r#"{"ok":true}"#);
+        assert_eq!(reply(&Err("unknown page nowhere".to_string())), r#"{"error":"unknown page nowhere","ok":false}"#);
+        assert_eq!(drain(&queue), vec![RemoteCommand::ShowPage { page: "news".to_string() }]);
+        assert!(drain(&queue).is_empty());
+    }
+
+    #[test]
+    fn test_notification_duration_is_bounded() {
+        let command: RemoteCommand = serde_json::from_str(r#"{"command": "notify", "title": "Sonnette", "duration": 18446744073709551615}"#).unwrap();
+        let notification = match command {
+            RemoteCommand::Notify(notification) => notification,
+            _ => unreachable!(),
+        };
+        assert_eq!(notification.duration, MAX_DURATION_SECONDS);
+        let now = Local::now();
+        let notification = Notification { duration: u64::MAX, ..notification };
+        assert_eq!(notification.expires(now), now + chrono::Duration::hours(1));
+        assert_eq!(Notification { duration: 0, ..notification }.expires(now), now + chrono::Duration::seconds(1));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_socket_round_trip() {
+        let path = std::env::temp_dir().join(format!("assistant_gui_remote_{}", std::process::id())).join(SOCKET_FILE_NAME);
+        let queue = Queue::default();
+        listen(&path, queue.clone()).unwrap();
+        let reply = send(&path, &RemoteCommand::Refresh { page: Some("calendar".to_string()) }).unwrap();
+        assert_eq!(reply, r#"{"ok":true}"#);
+        assert_eq!(drain(&queue), vec![RemoteCommand::Refresh { page: Some("calendar".to_string()) }]);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,70 @@
+use chrono::prelude::*;
+use chrono::Duration;
+
+// Seconds the screensaver content takes to move by one pixel, horizontally and vertically.
+const DRIFT_STEP_X: i64 = 2;
+const DRIFT_STEP_Y: i64 = 3;
+
+// Up to a day: longer delays would never trigger on a screen someone walks past.
+const MAX_DELAY_MINUTES: i64 = 24 * 60;
+
+pub fn parse_delay(value: &str) -> Option<Duration> {
+    match value.trim().parse::<i64>() {
+        Ok(minutes) if (1..=MAX_DELAY_MINUTES).contains(&minutes) => Some(Duration::minutes(minutes)),
+        _ => None,
+    }
+}
+
+pub fn is_idle(last_activity: DateTime<Local>, now: DateTime<Local>, delay: Duration) -> bool {
+    now - last_activity >= delay
+}
+
+// Bounces back and forth across `range` so that nothing stays long on the same pixels.
+fn bounce(position: i64, range: u16) -> u16 {
+    if range == 0 {
+        return 0;
+    }
+    let range = range as i64;
+    let position = position.rem_euclid(2 * range);
+    (if position > range { 2 * range - position } else { position }) as u16
+}
+
+pub fn drift_offset(elapsed: Duration, range_x: u16, range_y: u16) -> (u16, u16) {
+    let seconds = elapsed.num_seconds();
+    (bounce(seconds / DRIFT_STEP_X, range_x), bounce(seconds / DRIFT_STEP_Y, range_y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_delay() {
+        assert_eq!(parse_delay("5"), Some(Duration::minutes(5)));
+        assert_eq!(parse_delay(""), None);
+        assert_eq!(parse_delay("0"), None);
+        assert_eq!(parse_delay("cinq"), None);
+        assert_eq!(parse_delay("1440"), Some(Duration::days(1)));
+        assert_eq!(parse_delay("1441"), None);
+        assert_eq!(parse_delay("9223372036854775807"), None);
+    }
+
+    #[test]
+    fn test_is_idle() {
+        let last_activity = Local.from_local_datetime(&NaiveDate::from_ymd_opt(2021, 6, 1).unwrap().and_hms_opt(12, 0, 0).unwrap()).unwrap();
+        assert!(!is_idle(last_activity, last_activity + Duration::seconds(299), Duration::minutes(5)));
+        assert!(is_idle(last_activity, last_activity + Duration::minutes(5), Duration::minutes(5)));
+    }
+
+    #[test]
+    fn test_drift_offset_stays_in_range() {
+        assert_eq!(drift_offset(Duration::seconds(0), 100, 50), (0, 0));
+        assert_eq!(drift_offset(Duration::seconds(60), 100, 50), (30, 20));
+        assert_eq!(drift_offset(Duration::seconds(300), 100, 50), (50, 0));
+        assert_eq!(drift_offset(Duration::seconds(300), 0, 0), (0, 0));
+        for seconds in 0..2000 {
+            let (x, y) = drift_offset(Duration::seconds(seconds), 100, 50);
+            assert!(x <= 100 && y <= 50);
+        }
+    }
+}
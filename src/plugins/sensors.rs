@@ -0,0 +1,230 @@
+use chrono::prelude::*;
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const SAMPLE_SECONDS: u64 = 60;
+// Three hours at one sample a minute.
+pub const HISTORY_LENGTH: usize = 180;
+
+// IIO channel prefixes first (DHT22, BME280...), then hwmon ones, with the divisor to °C, % and hPa.
+const TEMPERATURE: [(&str, f32); 2] = [("in_temp", 1000.0), ("temp1", 1000.0)];
+const HUMIDITY: [(&str, f32); 2] = [("in_humidityrelative", 1000.0), ("humidity1", 1000.0)];
+const PRESSURE: [(&str, f32); 1] = [("in_pressure", 0.1)];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Reading {
+    pub temperature: Option<f32>,
+    pub humidity: Option<f32>,
+    pub pressure: Option<f32>,
+}
+
+impl Reading {
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(temperature) = self.temperature {
+            lines.push(format!("Temp. : {:.1} °C", temperature));
+        }
+        if let Some(humidity) = self.humidity {
+            lines.push(format!("Humidité : {:.0} %", humidity));
+        }
+        if let Some(pressure) = self.pressure {
+            lines.push(format!("Pression : {:.0} hPa", pressure));
+        }
+        lines
+    }
+}
+
+fn read_number(path: &Path) -> Option<f32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+// Uses `<prefix>_input` when the driver provides it, `(<prefix>_raw + offset) * scale` otherwise.
+fn read_channel(dir: &Path, channels: &[(&str, f32)]) -> Option<f32> {
+    channels.iter().find_map(|(prefix, divisor)| {
+        let file = |suffix: &str| dir.join([prefix, "_", suffix].concat());
+        let value = match read_number(&file("input")) {
+            Some(value) => value,
+            None => {
+                let raw = read_number(&file("raw"))?;
+                let offset = read_number(&file("offset")).unwrap_or(0.0);
+                (raw + offset) * read_number(&file("scale")).unwrap_or(1.0)
+            }
+        };
+        Some(value / divisor)
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sensor {
+    path: PathBuf,
+}
+
+impl Sensor {
+    pub fn open(path: &Path) -> Option<Sensor> {
+        let sensor = Sensor { path: path.to_path_buf() };
+        sensor.read().ok().map(|_| sensor)
+    }
+
+    // Looks under `<root>/sys` so tests can point `root` at a fake sysfs tree. hwmon devices
+    // without humidity are skipped, they are usually the CPU or the power supply.
+    pub fn find(root: &Path) -> Option<Sensor> {
+        let devices = |dir: &str| -> Vec<PathBuf> {
+            let mut paths: Vec<PathBuf> = fs::read_dir(root.join(dir))
+                .map(|entries| entries.filter_map(|entry| entry.ok().map(|e| e.path())).collect())
+                .unwrap_or_default();
+            paths.sort();
+            paths
+        };
+        let hwmon = devices("sys/class/hwmon")
+            .into_iter()
+            .filter(|path| path.join("humidity1_input").exists());
+        devices("sys/bus/iio/devices")
+            .into_iter()
+            .chain(hwmon)
+            .find_map(|path| Sensor::open(&path))
+    }
+
+    // An empty setting means automatic detection.
+    pub fn locate(setting: &str, root: &Path) -> Option<Sensor> {
+        match setting.trim() {
+            "" => Sensor::find(root),
+            path => Sensor::open(Path::new(path)),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        fs::read_to_string(self.path.join("name"))
+            .map(|name| name.trim().to_string())
+            .unwrap_or_else(|_e| self.path.display().to_string())
+    }
+
+    pub fn read(&self) -> io::Result<Reading> {
+        let reading = Reading {
+            temperature: read_channel(&self.path, &TEMPERATURE),
+            humidity: read_channel(&self.path, &HUMIDITY),
+            pressure: read_channel(&self.path, &PRESSURE),
+        };
+        if reading == Reading::default() {
+            Err(io::Error::new(io::ErrorKind::NotFound, "no temperature, humidity or pressure channel"))
+        } else {
+            Ok(reading)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    samples: VecDeque<(DateTime<Local>, Reading)>,
+}
+
+impl History {
+    pub fn push(&mut self, time: DateTime<Local>, reading: Reading) {
+        self.samples.push_back((time, reading));
+        while self.samples.len() > HISTORY_LENGTH {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    pub fn latest(&self) -> Option<Reading> {
+        self.samples.back().map(|(_, reading)| *reading)
+    }
+
+    pub fn temperatures(&self) -> Vec<f32> {
+        self.samples.iter().filter_map(|(_, reading)| reading.temperature).collect()
+    }
+}
+
+// Points of a line spanning `width` x `height`, the highest value at the top; a flat series sits in the middle.
+pub fn sparkline_points(values: &[f32], width: f32, height: f32) -> Vec<(f32, f32)> {
+    let min = values.iter().cloned().fold(f32::INFINITY, f32::min);
+    let max = values.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+    let step = if values.len() > 1 { width / (values.len() - 1) as f32 } else { 0.0 };
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let y = if max > min { height - (value - min) / (max - min) * height } else { height / 2.0 };
+            (i as f32 * step, y)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_sysfs(name: &str, devices: &[(&str, &[(&str, &str)])]) -> PathBuf {
+        let root = std::env::temp_dir().join(["assistant_gui_sensors_", name, "_", &std::process::id().to_string()].concat());
+        let _ = fs::remove_dir_all(&root);
+        for (device, files) in devices {
+            let path = root.join(device);
+            fs::create_dir_all(&path).unwrap();
+            for (file, value) in files.iter() {
+                fs::write(path.join(file), [value, "\n"].concat()).unwrap();
+            }
+        }
+        root
+    }
+
+    #[test]
+    fn test_read_iio_bme280() {
+        let root = fake_sysfs("bme280", &[("sys/bus/iio/devices/iio:device0", &[
+            ("name", "bme280"),
+            ("in_temp_input", "21370"),
+            ("in_humidityrelative_input", "45123"),
+            ("in_pressure_input", "101.325"),
+        ])]);
+        let sensor = Sensor::find(&root).unwrap();
+        assert_eq!(sensor.name(), "bme280");
+        let reading = sensor.read().unwrap();
+        assert!((reading.temperature.unwrap() - 21.37).abs() < 0.001);
+        assert!((reading.humidity.unwrap() - 45.123).abs() < 0.001);
+        assert!((reading.pressure.unwrap() - 1013.25).abs() < 0.01);
+        assert_eq!(reading.lines(), vec!["Temp. : 21.4 °C", "Humidité : 45 %", "Pression : 1013 hPa"]);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_read_raw_and_hwmon() {
+        let root = fake_sysfs("raw", &[
+            ("sys/class/hwmon/hwmon0", &[("name", "cpu_thermal"), ("temp1_input", "48000")]),
+            ("sys/class/hwmon/hwmon1", &[("name", "sht3x"), ("temp1_input", "19500"), ("humidity1_input", "60000")]),
+            ("sys/bus/iio/devices/iio:device0", &[("in_temp_raw", "2000"), ("in_temp_offset", "100"), ("in_temp_scale", "10")]),
+        ]);
+        let reading = Sensor::find(&root).unwrap().read().unwrap();
+        assert_eq!(reading, Reading { temperature: Some(21.0), humidity: None, pressure: None });
+
+        fs::remove_dir_all(root.join("sys/bus")).unwrap();
+        let sensor = Sensor::find(&root).unwrap();
+        assert_eq!(sensor.name(), "sht3x");
+        assert_eq!(sensor.read().unwrap().humidity, Some(60.0));
+
+        let configured = Sensor::locate(&root.join("sys/class/hwmon/hwmon0").display().to_string(), Path::new("/nowhere")).unwrap();
+        assert_eq!(configured.read().unwrap().temperature, Some(48.0));
+        assert!(Sensor::locate(&root.join("sys").display().to_string(), &root).is_none());
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_history_and_sparkline() {
+        let mut history = History::default();
+        let now = Local::now();
+        for i in 0..(HISTORY_LENGTH + 10) {
+            history.push(now, Reading { temperature: Some(i as f32), ..Reading::default() });
+        }
+        let temperatures = history.temperatures();
+        assert_eq!(temperatures.len(), HISTORY_LENGTH);
+        assert_eq!(temperatures[0], 10.0);
+        assert_eq!(history.latest().unwrap().temperature, Some((HISTORY_LENGTH + 9) as f32));
+
+        assert_eq!(sparkline_points(&[18.0, 20.0, 19.0], 100.0, 40.0), vec![(0.0, 40.0), (50.0, 0.0), (100.0, 20.0)]);
+        assert_eq!(sparkline_points(&[20.0, 20.0], 100.0, 40.0), vec![(0.0, 20.0), (100.0, 20.0)]);
+        assert!(sparkline_points(&[], 100.0, 40.0).is_empty());
+    }
+}
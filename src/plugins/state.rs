@@ -1,12 +1,112 @@
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use crate::plugins::clock::ClockFace;
+use crate::plugins::dashboard::{self, Widget};
+use crate::plugins::layout::FontScale;
+use crate::plugins::night::NightSchedule;
+use crate::plugins::theme::ThemeChoice;
+use crate::plugins::weather::Provider;
 // Persistence
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SavedState {
+    #[serde(default)]
     pub weatherapi_value: String,
-    pub searchcity_value: String
+    #[serde(default)]
+    pub searchcity_value: String,
+    #[serde(default)]
+    pub weather_provider: Provider,
+    #[serde(default)]
+    pub clock_face: ClockFace,
+    #[serde(default)]
+    pub calendar_files_value: String,
+    #[serde(default)]
+    pub calendar_days_value: String,
+    #[serde(default)]
+    pub calendar_watch: bool,
+    #[serde(default)]
+    pub caldav_url_value: String,
+    #[serde(default)]
+    pub caldav_username_value: String,
+    #[serde(default)]
+    pub caldav_password_value: String,
+    #[serde(default)]
+    pub theme_choice: ThemeChoice,
+    #[serde(default)]
+    pub latitude_value: String,
+    #[serde(default)]
+    pub longitude_value: String,
+    #[serde(default)]
+    pub night_schedule: NightSchedule,
+    #[serde(default)]
+    pub night_start_value: String,
+    #[serde(default)]
+    pub night_end_value: String,
+    #[serde(default = "default_day_brightness")]
+    pub day_brightness: u8,
+    #[serde(default = "default_night_brightness")]
+    pub night_brightness: u8,
+    #[serde(default)]
+    pub font_scale: FontScale,
+    #[serde(default)]
+    pub screensaver_delay_value: String,
+    #[serde(default)]
+    pub carousel_enabled: bool,
+    #[serde(default)]
+    pub carousel_pages: Vec<String>,
+    #[serde(default)]
+    pub carousel_dwell_value: String,
+    #[serde(default = "dashboard::default_widgets")]
+    pub dashboard_widgets: Vec<Widget>,
+    #[serde(default = "dashboard::default_columns")]
+    pub dashboard_columns: u8,
+    #[serde(default)]
+    pub mqtt_broker_value: String,
+    #[serde(default)]
+    pub mqtt_username_value: String,
+    #[serde(default)]
+    pub mqtt_password_value: String,
+    #[serde(default)]
+    pub mqtt_tiles_value: String,
+    #[serde(default)]
+    pub mqtt_actions_value: String,
+    #[serde(default)]
+    pub mqtt_command_topic_value: String,
+    #[serde(default)]
+    pub sensor_path_value: String,
+    #[serde(default)]
+    pub weather_rules_value: String,
+    #[serde(default)]
+    pub gtfs_path_value: String,
+    #[serde(default)]
+    pub gtfs_stops_value: String,
+    #[serde(default)]
+    pub gtfs_realtime_value: String,
+    #[serde(default)]
+    pub podcast_feeds_value: String,
+    #[serde(default)]
+    pub radio_stations_value: String,
+    #[serde(default)]
+    pub audio_player_value: String,
+    #[serde(default = "default_audio_volume")]
+    pub audio_volume: u8,
+    #[serde(default)]
+    pub mpd_address_value: String,
+}
+
+pub fn default_day_brightness() -> u8 {
+    100
+}
+
+pub fn default_night_brightness() -> u8 {
+    20
+}
+
+pub fn default_audio_volume() -> u8 {
+    70
 }
 
 #[derive(Debug, Clone)]
+#[allow(clippy::enum_variant_names)]
 pub enum LoadError {
     FileError,
     OpenFileError,
@@ -14,62 +114,248 @@ pub enum LoadError {
 }
 
 #[derive(Debug, Clone)]
+#[allow(clippy::enum_variant_names)]
 pub enum SaveError {
     FileError,
     WriteError,
     FormatError,
 }
 
-impl SavedState {
-    fn path() -> std::path::PathBuf {
-        let mut path = if let Some(project_dirs) =
-            directories_next::ProjectDirs::from("com", "JulienGabryelewicz", "Assistant")
-        {
-            project_dirs.data_dir().into()
-        } else {
-            std::env::current_dir().unwrap_or(std::path::PathBuf::new())
-        };
-
-        path.push("assistant.json");
-
-        path
+pub const SETTINGS_FILE: &str = "assistant.json";
+
+// Overrides the per-user data directory, see `--data-dir`.
+pub const DATA_DIR_VAR: &str = "ASSISTANT_GUI_DATA_DIR";
+
+pub fn data_path(file_name: &str) -> std::path::PathBuf {
+    let mut path = if let Some(data_dir) = std::env::var_os(DATA_DIR_VAR) {
+        data_dir.into()
+    } else if let Some(project_dirs) =
+        directories_next::ProjectDirs::from("com", "JulienGabryelewicz", "Assistant")
+    {
+        project_dirs.data_dir().into()
+    } else {
+        std::env::current_dir().unwrap_or_default()
+    };
+
+    path.push(file_name);
+
+    path
+}
+
+pub async fn load_json<T: DeserializeOwned>(file_name: &str) -> Result<T, LoadError> {
+    load_json_from(&data_path(file_name)).await
+}
+
+pub async fn load_json_from<T: DeserializeOwned>(path: &std::path::Path) -> Result<T, LoadError> {
+    use async_std::prelude::*;
+    let mut contents = String::new();
+    let mut file = async_std::fs::File::open(path)
+        .await
+        .map_err(|_| LoadError::OpenFileError)?;
+    file.read_to_string(&mut contents)
+        .await
+        .map_err(|_| LoadError::FileError)?;
+    serde_json::from_str(&contents).map_err(|_| LoadError::FormatError)
+}
+
+pub async fn save_json<T: Serialize>(file_name: &str, value: &T) -> Result<(), SaveError> {
+    save_json_to(&data_path(file_name), value).await
+}
+
+pub async fn save_json_to<T: Serialize>(path: &std::path::Path, value: &T) -> Result<(), SaveError> {
+    use async_std::prelude::*;
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|_| SaveError::FormatError)?;
+    if let Some(dir) = path.parent() {
+        async_std::fs::create_dir_all(dir)
+            .await
+            .map_err(|_| SaveError::FileError)?;
     }
 
-    pub async fn load() -> Result<SavedState, LoadError> {
-        use async_std::prelude::*;
-        let mut contents = String::new();
-        let mut file = async_std::fs::File::open(Self::path())
+    // Written next to the target then renamed, so a crash never leaves a truncated file behind.
+    let mut temporary = path.as_os_str().to_os_string();
+    temporary.push(".tmp");
+    {
+        let mut file = async_std::fs::File::create(&temporary)
             .await
-            .map_err(|_| LoadError::OpenFileError)?;
-        file.read_to_string(&mut contents)
+            .map_err(|_| SaveError::FileError)?;
+
+        file.write_all(json.as_bytes())
+            .await
+            .map_err(|_| SaveError::WriteError)?;
+        file.sync_all()
             .await
-            .map_err(|_| LoadError::FileError)?;
-        serde_json::from_str(&contents).map_err(|_| LoadError::FormatError)
+            .map_err(|_| SaveError::WriteError)?;
     }
 
-    pub async fn save(self) -> Result<(), SaveError> {
-        use async_std::prelude::*;
-        let json = serde_json::to_string_pretty(&self)
-            .map_err(|_| SaveError::FormatError)?;
-        let path = Self::path();
-        if let Some(dir) = path.parent() {
-            async_std::fs::create_dir_all(dir)
-                .await
-                .map_err(|_| SaveError::FileError)?;
+    async_std::fs::rename(&temporary, path)
+        .await
+        .map_err(|_| SaveError::FileError)
+}
+
+// A file that exists but cannot be read is renamed to `<file>.bak` before anything replaces it.
+// Returns whether the file may be written: false when it could not be moved aside.
+pub fn set_aside_unreadable(file_name: &str, error: &LoadError) -> bool {
+    if let LoadError::OpenFileError = error {
+        return true;
+    }
+    match back_up(&data_path(file_name)) {
+        Ok(backup) => {
+            log::error!("{} illisible ({:?}), conservé dans {}", file_name, error, backup.display());
+            true
+        }
+        Err(e) => {
+            log::error!("{} illisible ({:?}) et impossible à mettre de côté ({}), il ne sera pas remplacé", file_name, error, e);
+            false
         }
+    }
+}
 
-        {
-            let mut file = async_std::fs::File::create(path)
-                .await
-                .map_err(|_| SaveError::FileError)?;
+pub fn back_up(path: &std::path::Path) -> std::io::Result<std::path::PathBuf> {
+    let mut backup = path.as_os_str().to_os_string();
+    backup.push(".bak");
+    std::fs::rename(path, &backup)?;
+    Ok(backup.into())
+}
 
-            file.write_all(json.as_bytes())
-                .await
-                .map_err(|_| SaveError::WriteError)?;
+// Keys of `overlay` replace those of `base`, objects are merged key by key.
+pub fn merge_json(base: &mut serde_json::Value, overlay: serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                merge_json(base.entry(key).or_insert(serde_json::Value::Null), value);
+            }
         }
+        (base, overlay) => *base = overlay,
+    }
+}
 
-        async_std::task::sleep(std::time::Duration::from_secs(2)).await;
+impl SavedState {
+    // `defaults` comes from the system config; the user's own settings take precedence.
+    pub async fn load(defaults: serde_json::Value) -> Result<SavedState, LoadError> {
+        match load_json::<serde_json::Value>(SETTINGS_FILE).await {
+            Ok(user) => SavedState::merge(&defaults, user),
+            Err(LoadError::OpenFileError) if !defaults.is_null() => Ok(SavedState::from_defaults(&defaults)),
+            Err(e) => Err(e),
+        }
+    }
+
+    // A format error always points at the user file: system defaults that do not fit are dropped.
+    fn merge(defaults: &serde_json::Value, user: serde_json::Value) -> Result<SavedState, LoadError> {
+        let own: SavedState = serde_json::from_value(user.clone()).map_err(|_| LoadError::FormatError)?;
+        let mut merged = defaults.clone();
+        merge_json(&mut merged, user);
+        Ok(serde_json::from_value(merged).unwrap_or(own))
+    }
+
+    // The settings a user gets without a file of their own: the system defaults over the built-in ones.
+    pub fn from_defaults(defaults: &serde_json::Value) -> SavedState {
+        let builtin = || serde_json::from_value(serde_json::json!({})).expect("every setting has a default");
+        if defaults.is_null() {
+            return builtin();
+        }
+        serde_json::from_value(defaults.clone()).unwrap_or_else(|_| builtin())
+    }
+
+    pub fn baseline(defaults: &serde_json::Value) -> serde_json::Value {
+        serde_json::to_value(SavedState::from_defaults(defaults)).unwrap_or(serde_json::Value::Null)
+    }
+
+    // Only the settings that differ from `baseline` go to the user file, so a later change to
+    // the system defaults still reaches every setting the user never touched.
+    pub fn user_settings(&self, baseline: &serde_json::Value) -> serde_json::Value {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::Object(settings)) => settings
+                .into_iter()
+                .filter(|(key, value)| baseline.get(key) != Some(value))
+                .collect::<serde_json::Map<_, _>>()
+                .into(),
+            _ => serde_json::json!({}),
+        }
+    }
+}
+
+pub async fn save_settings(settings: serde_json::Value) -> Result<(), SaveError> {
+    let result = save_json(SETTINGS_FILE, &settings).await;
+
+    // Failures wait too, so a broken disk is retried every other second rather than on every update.
+    async_std::task::sleep(std::time::Duration::from_secs(2)).await;
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_settings_override_system_defaults() {
+        let mut merged = serde_json::json!({"searchcity_value": "Lille", "theme_choice": "Light"});
+        merge_json(&mut merged, serde_json::json!({"searchcity_value": "Paris", "weatherapi_value": "abc"}));
+        let state: SavedState = serde_json::from_value(merged).unwrap();
+        assert_eq!(state.searchcity_value, "Paris");
+        assert_eq!(state.weatherapi_value, "abc");
+        assert_eq!(state.theme_choice, ThemeChoice::Light);
+        assert_eq!(state.day_brightness, default_day_brightness());
+    }
+
+    #[test]
+    fn test_user_settings_keep_only_changes() {
+        let defaults = serde_json::json!({"searchcity_value": "Lille", "theme_choice": "Light"});
+        let baseline = SavedState::baseline(&defaults);
+        let mut state: SavedState = serde_json::from_value(defaults).unwrap();
+        assert_eq!(state.user_settings(&baseline), serde_json::json!({}));
+
+        state.searchcity_value = "Paris".to_string();
+        state.day_brightness = 50;
+        assert_eq!(state.user_settings(&baseline), serde_json::json!({"searchcity_value": "Paris", "day_brightness": 50}));
+
+        // A new system default still applies to the settings the user left alone.
+        let mut merged = serde_json::json!({"searchcity_value": "Lille", "theme_choice": "Dark"});
+        merge_json(&mut merged, state.user_settings(&baseline));
+        let reloaded: SavedState = serde_json::from_value(merged).unwrap();
+        assert_eq!(reloaded.theme_choice, ThemeChoice::Dark);
+        assert_eq!(reloaded.searchcity_value, "Paris");
+
+        let builtin = SavedState::baseline(&serde_json::Value::Null);
+        assert_eq!(reloaded.user_settings(&builtin).get("day_brightness"), Some(&serde_json::json!(50)));
+        assert_eq!(reloaded.user_settings(&builtin).get("audio_volume"), None);
+    }
+
+    #[test]
+    fn test_merge_blames_only_the_user_file() {
+        let defaults = serde_json::json!({"searchcity_value": "Lille", "day_brightness": "bright"});
+        let state = SavedState::merge(&defaults, serde_json::json!({"weatherapi_value": "abc"})).unwrap();
+        assert_eq!(state.weatherapi_value, "abc");
+        assert_eq!(state.searchcity_value, "");
+        assert!(SavedState::merge(&defaults, serde_json::json!({"day_brightness": -1})).is_err());
+        assert_eq!(SavedState::from_defaults(&defaults).day_brightness, default_day_brightness());
+        assert_eq!(SavedState::from_defaults(&serde_json::json!({"searchcity_value": "Lille"})).searchcity_value, "Lille");
+    }
+
+    #[test]
+    fn test_save_json_replaces_file() {
+        let dir = std::env::temp_dir().join(format!("assistant-state-{}", std::process::id()));
+        let path = dir.join("test.json");
+        async_std::task::block_on(save_json_to(&path, &vec![1, 2, 3])).unwrap();
+        async_std::task::block_on(save_json_to(&path, &vec![4])).unwrap();
+        let saved: Vec<u32> = async_std::task::block_on(load_json_from(&path)).unwrap();
+        assert_eq!(saved, vec![4]);
+        assert!(!dir.join("test.json.tmp").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 
-        Ok(())
+    #[test]
+    fn test_back_up() {
+        let dir = std::env::temp_dir().join(format!("assistant-backup-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("todo.json");
+        std::fs::write(&path, "{not json").unwrap();
+        let backup = back_up(&path).unwrap();
+        assert_eq!(backup, dir.join("todo.json.bak"));
+        assert_eq!(std::fs::read_to_string(backup).unwrap(), "{not json");
+        assert!(!path.exists());
+        assert!(back_up(&path).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
     }
 }
@@ -0,0 +1,353 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const ROOT: &str = "/";
+pub const REFRESH_SECONDS: u64 = 5;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CpuTimes {
+    idle: u64,
+    total: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Wifi {
+    pub interface: String,
+    pub quality: f32,
+    pub level: i32,
+}
+
+// Used and total amounts in kibibytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Usage {
+    pub used: u64,
+    pub total: u64,
+}
+
+impl Usage {
+    pub fn percent(&self) -> f32 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.used as f32 * 100.0 / self.total as f32
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SystemStatus {
+    pub load: Option<[f32; 3]>,
+    pub cpu_usage: Option<f32>,
+    pub cpu_temperature: Option<f32>,
+    pub memory: Option<Usage>,
+    pub disk: Option<Usage>,
+    pub uptime: Option<u64>,
+    pub addresses: Vec<String>,
+    pub wifi: Option<Wifi>,
+}
+
+impl SystemStatus {
+    // Label and value of every line of the system page, skipping what could not be read.
+    pub fn lines(&self) -> Vec<(&'static str, String)> {
+        let usage = |usage: &Usage| format!("{} / {} ({:.0} %)", format_kibibytes(usage.used), format_kibibytes(usage.total), usage.percent());
+        let mut lines = Vec::new();
+        if let Some(load) = self.load {
+            lines.push(("Charge", format!("{:.2} {:.2} {:.2}", load[0], load[1], load[2])));
+        }
+        if let Some(cpu_usage) = self.cpu_usage {
+            lines.push(("Processeur", format!("{:.0} %", cpu_usage)));
+        }
+        if let Some(cpu_temperature) = self.cpu_temperature {
+            lines.push(("Température", format!("{:.1} °C", cpu_temperature)));
+        }
+        if let Some(memory) = &self.memory {
+            lines.push(("Mémoire", usage(memory)));
+        }
+        if let Some(disk) = &self.disk {
+            lines.push(("Disque", usage(disk)));
+        }
+        if let Some(uptime) = self.uptime {
+            lines.push(("Allumé depuis", format_uptime(uptime)));
+        }
+        if !self.addresses.is_empty() {
+            lines.push(("Adresses IP", self.addresses.join(", ")));
+        }
+        if let Some(wifi) = &self.wifi {
+            lines.push(("Wi-Fi", format!("{} {} dBm ({:.0} %)", wifi.interface, wifi.level, wifi.quality)));
+        }
+        lines
+    }
+}
+
+pub fn parse_loadavg(content: &str) -> Option<[f32; 3]> {
+    let mut values = content.split_whitespace().map(|value| value.parse().ok());
+    Some([values.next()??, values.next()??, values.next()??])
+}
+
+// First line of /proc/stat: user nice system idle iowait irq softirq steal...
+pub fn parse_cpu_times(content: &str) -> Option<CpuTimes> {
+    let line = content.lines().find(|line| line.starts_with("cpu "))?;
+    let values: Vec<u64> = line.split_whitespace().skip(1).filter_map(|value| value.parse().ok()).collect();
+    if values.len() < 4 {
+        return None;
+    }
+    Some(CpuTimes {
+        idle: values[3] + values.get(4).unwrap_or(&0),
+        total: values.iter().take(8).sum(),
+    })
+}
+
+pub fn cpu_usage(previous: CpuTimes, current: CpuTimes) -> Option<f32> {
+    let total = current.total.checked_sub(previous.total)?;
+    let idle = current.idle.checked_sub(previous.idle)?;
+    if total == 0 {
+        return None;
+    }
+    Some((total - idle.min(total)) as f32 * 100.0 / total as f32)
+}
+
+pub fn parse_meminfo(content: &str) -> Option<Usage> {
+    let field = |name: &str| -> Option<u64> {
+        content
+            .lines()
+            .find(|line| line.starts_with(name))?
+            .split_whitespace()
+            .nth(1)?
+            .parse()
+            .ok()
+    };
+    let total = field("MemTotal:")?;
+    let available = field("MemAvailable:").or_else(|| field("MemFree:"))?;
+    Some(Usage { used: total.saturating_sub(available), total })
+}
+
+pub fn parse_uptime(content: &str) -> Option<u64> {
+    content.split_whitespace().next()?.parse::<f64>().ok().map(|seconds| seconds as u64)
+}
+
+// Local addresses appear in /proc/net/fib_trie as `|-- 192.168.1.20` followed by `/32 host LOCAL`.
+pub fn parse_fib_trie(content: &str) -> Vec<String> {
+    let mut addresses: Vec<String> = Vec::new();
+    let mut last: Option<String> = None;
+    for line in content.lines() {
+        let line = line.trim_start_matches([' ', '|', '+', '-']);
+        if line.contains("host LOCAL") {
+            if let Some(address) = last.take() {
+                if !address.starts_with("127.") && !addresses.contains(&address) {
+                    addresses.push(address);
+                }
+            }
+        } else if line.parse::<std::net::Ipv4Addr>().is_ok() {
+            last = Some(line.to_string());
+        }
+    }
+    addresses
+}
+
+// After two header lines: `wlan0: 0000   57.  -53.  -256 ...`, quality out of 70.
+pub fn parse_wireless(content: &str) -> Option<Wifi> {
+    let line = content.lines().nth(2)?;
+    let mut parts = line.split_whitespace();
+    let interface = parts.next()?.trim_end_matches(':').to_string();
+    let _status = parts.next()?;
+    let quality: f32 = parts.next()?.trim_end_matches('.').parse().ok()?;
+    let level: f32 = parts.next()?.trim_end_matches('.').parse().ok()?;
+    Some(Wifi {
+        interface,
+        quality: (quality * 100.0 / 70.0).min(100.0),
+        level: level as i32,
+    })
+}
+
+pub fn format_uptime(seconds: u64) -> String {
+    let (days, hours, minutes) = (seconds / 86400, seconds % 86400 / 3600, seconds % 3600 / 60);
+    if days > 0 {
+        format!("{} j {:02} h {:02} min", days, hours, minutes)
+    } else {
+        format!("{} h {:02} min", hours, minutes)
+    }
+}
+
+pub fn format_kibibytes(value: u64) -> String {
+    match value {
+        v if v >= 1024 * 1024 => format!("{:.1} Go", v as f64 / (1024.0 * 1024.0)),
+        v if v >= 1024 => format!("{:.0} Mo", v as f64 / 1024.0),
+        v => format!("{} ko", v),
+    }
+}
+
+// Reads everything under `root` so tests can use a fake /proc and /sys.
+#[derive(Debug, Clone)]
+pub struct Monitor {
+    root: PathBuf,
+    previous_cpu: Option<CpuTimes>,
+}
+
+impl Default for Monitor {
+    fn default() -> Monitor {
+        Monitor::new(Path::new(ROOT))
+    }
+}
+
+impl Monitor {
+    pub fn new(root: &Path) -> Monitor {
+        Monitor { root: root.to_path_buf(), previous_cpu: None }
+    }
+
+    fn read(&self, path: &str) -> Option<String> {
+        fs::read_to_string(self.root.join(path)).ok()
+    }
+
+    // Prefers the zone reporting the CPU, the first zone otherwise.
+    fn cpu_temperature(&self) -> Option<f32> {
+        let mut zones: Vec<PathBuf> = fs::read_dir(self.root.join("sys/class/thermal"))
+            .ok()?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.join("temp").exists())
+            .collect();
+        zones.sort();
+        let zone = zones
+            .iter()
+            .find(|path| fs::read_to_string(path.join("type")).map(|kind| kind.contains("cpu")).unwrap_or(false))
+            .or_else(|| zones.first())?;
+        let millidegrees: f32 = fs::read_to_string(zone.join("temp")).ok()?.trim().parse().ok()?;
+        Some(millidegrees / 1000.0)
+    }
+
+    fn disk(&self) -> Option<Usage> {
+        let stats = fs2::statvfs(&self.root).ok()?;
+        Some(Usage {
+            used: (stats.total_space() - stats.free_space()) / 1024,
+            total: stats.total_space() / 1024,
+        })
+    }
+
+    // CPU usage is measured between two samples, the first one has none.
+    pub fn sample(&mut self) -> SystemStatus {
+        let cpu = self.read("proc/stat").as_deref().and_then(parse_cpu_times);
+        let cpu_usage = match (self.previous_cpu, cpu) {
+            (Some(previous), Some(current)) => cpu_usage(previous, current),
+            _ => None,
+        };
+        self.previous_cpu = cpu;
+        SystemStatus {
+            load: self.read("proc/loadavg").as_deref().and_then(parse_loadavg),
+            cpu_usage,
+            cpu_temperature: self.cpu_temperature(),
+            memory: self.read("proc/meminfo").as_deref().and_then(parse_meminfo),
+            disk: self.disk(),
+            uptime: self.read("proc/uptime").as_deref().and_then(parse_uptime),
+            addresses: self.read("proc/net/fib_trie").as_deref().map(parse_fib_trie).unwrap_or_default(),
+            wifi: self.read("proc/net/wireless").as_deref().and_then(parse_wireless),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIB_TRIE: &str = "Main:
+  +-- 0.0.0.0/0 3 0 5
+     |-- 0.0.0.0
+        /0 universe UNICAST
+     +-- 127.0.0.0/8 2 0 2
+        +-- 127.0.0.0/31 1 0 0
+           |-- 127.0.0.1
+              /32 host LOCAL
+     |-- 192.168.1.0
+        /24 link UNICAST
+     |-- 192.168.1.20
+        /32 host LOCAL
+Local:
+  +-- 0.0.0.0/0 3 0 5
+     |-- 192.168.1.20
+        /32 host LOCAL
+";
+
+    const WIRELESS: &str = "Inter-| sta-|   Quality        |   Discarded packets               | Missed | WE
+ face | tus | link level noise |  nwid  crypt   frag  retry   misc | beacon | 22
+ wlan0: 0000   49.  -61.  -256        0      0      0      0     12        0
+";
+
+    fn fake_root(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(["assistant_gui_system_", name, "_", &std::process::id().to_string()].concat());
+        let _ = fs::remove_dir_all(&root);
+        for (file, content) in files {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn test_parsers() {
+        assert_eq!(parse_loadavg("0.52 0.58 0.59 1/389 12345\n"), Some([0.52, 0.58, 0.59]));
+        assert_eq!(parse_loadavg(""), None);
+        assert_eq!(parse_meminfo("MemTotal:        3884328 kB\nMemFree:          120000 kB\nMemAvailable:    2884328 kB\n"), Some(Usage { used: 1000000, total: 3884328 }));
+        assert_eq!(parse_uptime("93784.21 350000.00\n"), Some(93784));
+        assert_eq!(parse_fib_trie(FIB_TRIE), vec!["192.168.1.20"]);
+        assert_eq!(parse_wireless(WIRELESS), Some(Wifi { interface: "wlan0".to_string(), quality: 70.0, level: -61 }));
+        assert_eq!(parse_wireless(&WIRELESS.lines().take(2).collect::<Vec<_>>().join("\n")), None);
+    }
+
+    #[test]
+    fn test_cpu_usage() {
+        let previous = parse_cpu_times("cpu  100 0 100 700 100 0 0 0 0 0\ncpu0 1 2 3 4\n").unwrap();
+        let current = parse_cpu_times("cpu  200 0 200 1200 100 0 0 0 0 0\n").unwrap();
+        assert_eq!(cpu_usage(previous, current), Some(200.0 * 100.0 / 700.0));
+        assert_eq!(cpu_usage(current, current), None);
+        assert_eq!(cpu_usage(current, previous), None);
+    }
+
+    #[test]
+    fn test_formatting() {
+        assert_eq!(format_uptime(93784), "1 j 02 h 03 min");
+        assert_eq!(format_uptime(3000), "0 h 50 min");
+        assert_eq!(format_kibibytes(512), "512 ko");
+        assert_eq!(format_kibibytes(200 * 1024), "200 Mo");
+        assert_eq!(format_kibibytes(3884328), "3.7 Go");
+        assert_eq!(Usage { used: 1, total: 4 }.percent(), 25.0);
+        let status = SystemStatus {
+            memory: Some(Usage { used: 1024, total: 4096 }),
+            addresses: vec!["192.168.1.20".to_string(), "10.0.0.2".to_string()],
+            ..SystemStatus::default()
+        };
+        assert_eq!(status.lines(), vec![
+            ("Mémoire", "1 Mo / 4 Mo (25 %)".to_string()),
+            ("Adresses IP", "192.168.1.20, 10.0.0.2".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_monitor_reads_fake_root() {
+        let root = fake_root("monitor", &[
+            ("proc/loadavg", "1.00 0.50 0.25 1/100 42\n"),
+            ("proc/stat", "cpu  100 0 100 700 100 0 0 0 0 0\n"),
+            ("proc/meminfo", "MemTotal: 1000 kB\nMemAvailable: 250 kB\n"),
+            ("proc/uptime", "60.5 100.0\n"),
+            ("proc/net/fib_trie", FIB_TRIE),
+            ("proc/net/wireless", WIRELESS),
+            ("sys/class/thermal/thermal_zone0/type", "gpu-thermal\n"),
+            ("sys/class/thermal/thermal_zone0/temp", "40000\n"),
+            ("sys/class/thermal/thermal_zone1/type", "cpu-thermal\n"),
+            ("sys/class/thermal/thermal_zone1/temp", "52100\n"),
+        ]);
+        let mut monitor = Monitor::new(&root);
+        let status = monitor.sample();
+        assert_eq!(status.load, Some([1.0, 0.5, 0.25]));
+        assert_eq!(status.cpu_usage, None);
+        assert_eq!(status.cpu_temperature, Some(52.1));
+        assert_eq!(status.memory, Some(Usage { used: 750, total: 1000 }));
+        assert_eq!(status.uptime, Some(60));
+        assert_eq!(status.addresses, vec!["192.168.1.20"]);
+        assert_eq!(status.wifi.unwrap().level, -61);
+        assert!(status.disk.unwrap().total > 0);
+
+        fs::write(root.join("proc/stat"), "cpu  200 0 200 1200 100 0 0 0 0 0\n").unwrap();
+        assert!(monitor.sample().cpu_usage.is_some());
+        assert_eq!(Monitor::new(&root.join("nowhere")).sample().load, None);
+        fs::remove_dir_all(root).unwrap();
+    }
+}
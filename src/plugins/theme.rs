@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+
+use crate::plugins::state;
+
+pub fn parse_hex_color(value: &str) -> Option<[u8; 3]> {
+    let hex = value.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+// A theme file maps palette keys to colors, e.g. `accent = "#6FFFE9"`, and may name
+// a built-in `base` theme to start from.
+pub fn parse_theme_file(path: &Path, contents: &str) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => Ok(serde_json::from_str(contents)?),
+        _ => Ok(toml::from_str(contents)?),
+    }
+}
+
+pub fn load_custom_theme() -> Result<HashMap<String, String>, Box<dyn Error>> {
+    let toml_path = state::data_path("theme.toml");
+    let path = if toml_path.exists() {
+        toml_path
+    } else {
+        state::data_path("theme.json")
+    };
+    let contents = std::fs::read_to_string(&path)?;
+    parse_theme_file(&path, &contents)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThemeChoice {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+    Custom,
+}
+
+impl ThemeChoice {
+    pub const ALL: [ThemeChoice; 4] = [
+        ThemeChoice::Dark,
+        ThemeChoice::Light,
+        ThemeChoice::HighContrast,
+        ThemeChoice::Custom,
+    ];
+
+    pub fn from_name(name: &str) -> Option<ThemeChoice> {
+        match name.trim().to_lowercase().as_str() {
+            "dark" => Some(ThemeChoice::Dark),
+            "light" => Some(ThemeChoice::Light),
+            "high-contrast" | "high_contrast" => Some(ThemeChoice::HighContrast),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ThemeChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ThemeChoice::Dark => "Sombre",
+                ThemeChoice::Light => "Clair",
+                ThemeChoice::HighContrast => "Contraste élevé",
+                ThemeChoice::Custom => "Personnalisé",
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(parse_hex_color("#6FFFE9"), Some([0x6F, 0xFF, 0xE9]));
+        assert_eq!(parse_hex_color("40444b"), Some([0x40, 0x44, 0x4B]));
+        assert_eq!(parse_hex_color("#FFF"), None);
+        assert_eq!(parse_hex_color("#GGGGGG"), None);
+    }
+
+    #[test]
+    fn test_parse_theme_file() {
+        let toml = parse_theme_file(Path::new("theme.toml"), "base = \"light\"\naccent = \"#FF0000\"\n").unwrap();
+        assert_eq!(toml["base"], "light");
+        assert_eq!(toml["accent"], "#FF0000");
+        let json = parse_theme_file(Path::new("theme.json"), r##"{"background": "#000000"}"##).unwrap();
+        assert_eq!(json["background"], "#000000");
+        assert!(parse_theme_file(Path::new("theme.toml"), "accent = 3").is_err());
+    }
+
+    #[test]
+    fn test_theme_choice_from_name() {
+        assert_eq!(ThemeChoice::from_name("High-Contrast"), Some(ThemeChoice::HighContrast));
+        assert_eq!(ThemeChoice::from_name("sepia"), None);
+    }
+}
@@ -0,0 +1,175 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::plugins::state::{self, LoadError, SaveError};
+
+pub const FILE_NAME: &str = "todo.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Task {
+    pub description: String,
+    pub completed: bool,
+    pub due: Option<NaiveDate>,
+    pub priority: Priority,
+}
+
+impl Task {
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        !self.completed && self.due.map(|due| due < today).unwrap_or(false)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TaskList {
+    pub tasks: Vec<Task>,
+}
+
+impl TaskList {
+    pub fn add(&mut self, description: &str, due: Option<NaiveDate>, priority: Priority) -> bool {
+        let description = description.trim();
+        if description.is_empty() {
+            return false;
+        }
+        self.tasks.push(Task {
+            description: description.to_string(),
+            completed: false,
+            due,
+            priority,
+        });
+        true
+    }
+
+    pub fn toggle(&mut self, index: usize, completed: bool) {
+        if let Some(task) = self.tasks.get_mut(index) {
+            task.completed = completed;
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.tasks.len() {
+            self.tasks.remove(index);
+        }
+    }
+
+    pub fn move_up(&mut self, index: usize) {
+        if index > 0 && index < self.tasks.len() {
+            self.tasks.swap(index - 1, index);
+        }
+    }
+
+    pub fn move_down(&mut self, index: usize) {
+        if index + 1 < self.tasks.len() {
+            self.tasks.swap(index, index + 1);
+        }
+    }
+
+    pub fn overdue_count(&self, today: NaiveDate) -> usize {
+        self.tasks.iter().filter(|task| task.is_overdue(today)).count()
+    }
+
+    pub async fn load() -> Result<TaskList, LoadError> {
+        state::load_json(FILE_NAME).await
+    }
+
+    pub async fn save(self) -> Result<(), SaveError> {
+        state::save_json(FILE_NAME, &self).await
+    }
+}
+
+pub fn parse_due_date(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    NaiveDate::parse_from_str(value, "%d/%m/%Y")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d"))
+        .ok()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 3] = [
+        Priority::Low,
+        Priority::Normal,
+        Priority::High,
+    ];
+}
+
+impl std::fmt::Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Priority::Low => "Basse",
+                Priority::Normal => "Normale",
+                Priority::High => "Haute",
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn task_list() -> TaskList {
+        let mut list = TaskList::default();
+        list.add("Courses", Some(date(2021, 3, 1)), Priority::High);
+        list.add("Appeler le plombier", None, Priority::Normal);
+        list.add("Payer les impôts", Some(date(2021, 5, 15)), Priority::Low);
+        list
+    }
+
+    #[test]
+    fn test_add_ignores_empty_description() {
+        let mut list = task_list();
+        assert!(!list.add("   ", None, Priority::Low));
+        assert_eq!(list.tasks.len(), 3);
+    }
+
+    #[test]
+    fn test_reorder_and_remove() {
+        let mut list = task_list();
+        list.move_up(0);
+        list.move_down(2);
+        list.move_down(0);
+        assert_eq!(list.tasks[0].description, "Appeler le plombier");
+        list.move_up(2);
+        assert_eq!(list.tasks[1].description, "Payer les impôts");
+        list.remove(0);
+        list.remove(5);
+        assert_eq!(list.tasks.len(), 2);
+    }
+
+    #[test]
+    fn test_overdue_count() {
+        let mut list = task_list();
+        assert_eq!(list.overdue_count(date(2021, 4, 1)), 1);
+        assert_eq!(list.overdue_count(date(2021, 6, 1)), 2);
+        list.toggle(0, true);
+        assert_eq!(list.overdue_count(date(2021, 6, 1)), 1);
+    }
+
+    #[test]
+    fn test_parse_due_date() {
+        assert_eq!(parse_due_date("15/05/2021"), Some(date(2021, 5, 15)));
+        assert_eq!(parse_due_date("2021-05-15"), Some(date(2021, 5, 15)));
+        assert_eq!(parse_due_date("demain"), None);
+    }
+
+    #[test]
+    fn test_serialization() {
+        let list = task_list();
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(serde_json::from_str::<TaskList>(&json).unwrap(), list);
+    }
+}
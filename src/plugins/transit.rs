@@ -0,0 +1,726 @@
+use chrono::prelude::*;
+use chrono::Duration;
+use prost::Message;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub const REFRESH_SECONDS: u64 = 60;
+pub const DEPARTURES_PER_STOP: usize = 5;
+// Departures further away are left out of the board.
+const HORIZON_MINUTES: i64 = 120;
+
+// Stop ids or stop names separated by `;`; a station also brings its platforms.
+pub fn parse_stops(value: &str) -> Vec<String> {
+    value.split(';').map(str::trim).filter(|stop| !stop.is_empty()).map(String::from).collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct StopRecord {
+    stop_id: String,
+    #[serde(default)]
+    stop_name: String,
+    #[serde(default)]
+    parent_station: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RouteRecord {
+    route_id: String,
+    #[serde(default)]
+    route_short_name: String,
+    #[serde(default)]
+    route_long_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TripRecord {
+    route_id: String,
+    service_id: String,
+    trip_id: String,
+    #[serde(default)]
+    trip_headsign: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct StopTimeRecord {
+    trip_id: String,
+    #[serde(default)]
+    arrival_time: String,
+    #[serde(default)]
+    departure_time: String,
+    stop_id: String,
+    stop_sequence: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct CalendarRecord {
+    service_id: String,
+    monday: u8,
+    tuesday: u8,
+    wednesday: u8,
+    thursday: u8,
+    friday: u8,
+    saturday: u8,
+    sunday: u8,
+    start_date: String,
+    end_date: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CalendarDateRecord {
+    service_id: String,
+    date: String,
+    exception_type: u8,
+}
+
+#[derive(Debug, Clone)]
+struct Service {
+    // Monday first.
+    days: [bool; 7],
+    start: NaiveDate,
+    end: NaiveDate,
+}
+
+#[derive(Debug, Clone)]
+struct Trip {
+    route: String,
+    service_id: String,
+    headsign: String,
+}
+
+#[derive(Debug, Clone)]
+struct StopTime {
+    trip_id: String,
+    stop_id: String,
+    sequence: u32,
+    // Seconds from noon minus 12 hours on the service day; may go past 24:00:00.
+    departure: i64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Feed {
+    // Each configured stop with the stop ids it covers.
+    stops: Vec<(String, Vec<String>)>,
+    trips: HashMap<String, Trip>,
+    services: HashMap<String, Service>,
+    // Dates added (true) or removed (false) by calendar_dates.txt.
+    exceptions: HashMap<(String, NaiveDate), bool>,
+    stop_times: Vec<StopTime>,
+    // Sequence of every stop of the kept trips, for realtime updates giving only a stop_id.
+    sequences: HashMap<String, HashMap<String, u32>>,
+}
+
+// `HH:MM:SS`, hours may exceed 23 for trips running after midnight.
+fn parse_time(value: &str) -> Option<i64> {
+    let mut parts = value.trim().split(':').map(|part| part.parse::<i64>().ok());
+    let (hours, minutes, seconds) = (parts.next()??, parts.next()??, parts.next()??);
+    Some(hours * 3600 + minutes * 60 + seconds)
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%Y%m%d").ok()
+}
+
+fn read_table<R, T, F>(archive: &mut zip::ZipArchive<R>, name: &str, required: bool, mut each: F) -> Result<(), Box<dyn Error>>
+where
+    R: Read + Seek,
+    T: DeserializeOwned,
+    F: FnMut(T),
+{
+    let file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) if !required => return Ok(()),
+        Err(e) => return Err(format!("{} : {}", name, e).into()),
+    };
+    let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(file);
+    for record in reader.deserialize() {
+        each(record.map_err(|e| format!("{} : {}", name, e))?);
+    }
+    Ok(())
+}
+
+impl Feed {
+    pub fn load(path: &Path, selection: &[String]) -> Result<Feed, Box<dyn Error>> {
+        Feed::read(File::open(path)?, selection)
+    }
+
+    // Only the stop times of the selected stops are kept, large feeds would not fit otherwise.
+    pub fn read<R: Read + Seek>(reader: R, selection: &[String]) -> Result<Feed, Box<dyn Error>> {
+        let mut archive = zip::ZipArchive::new(reader)?;
+
+        let mut all_stops = Vec::new();
+        read_table(&mut archive, "stops.txt", true, |stop: StopRecord| all_stops.push(stop))?;
+        let stops: Vec<(String, Vec<String>)> = selection
+            .iter()
+            .filter_map(|wanted| {
+                let wanted_name = wanted.to_lowercase();
+                let matched: Vec<&StopRecord> = all_stops
+                    .iter()
+                    .filter(|stop| &stop.stop_id == wanted || stop.stop_name.to_lowercase() == wanted_name)
+                    .collect();
+                let name = matched.first()?.stop_name.clone();
+                let mut ids: Vec<String> = matched.iter().map(|stop| stop.stop_id.clone()).collect();
+                let platforms = all_stops.iter().filter(|stop| matched.iter().any(|m| m.stop_id == stop.parent_station));
+                ids.extend(platforms.map(|stop| stop.stop_id.clone()));
+                Some((name, ids))
+            })
+            .collect();
+        let wanted: HashSet<&String> = stops.iter().flat_map(|(_, ids)| ids).collect();
+
+        let mut stop_times = Vec::new();
+        read_table(&mut archive, "stop_times.txt", true, |record: StopTimeRecord| {
+            if wanted.contains(&record.stop_id) {
+                let time = if record.departure_time.is_empty() { &record.arrival_time } else { &record.departure_time };
+                if let Some(departure) = parse_time(time) {
+                    stop_times.push(StopTime {
+                        trip_id: record.trip_id,
+                        stop_id: record.stop_id,
+                        sequence: record.stop_sequence,
+                        departure,
+                    });
+                }
+            }
+        })?;
+        let trip_ids: HashSet<&String> = stop_times.iter().map(|stop_time| &stop_time.trip_id).collect();
+
+        let mut sequences: HashMap<String, HashMap<String, u32>> = HashMap::new();
+        read_table(&mut archive, "stop_times.txt", true, |record: StopTimeRecord| {
+            if trip_ids.contains(&record.trip_id) {
+                sequences.entry(record.trip_id).or_default().entry(record.stop_id).or_insert(record.stop_sequence);
+            }
+        })?;
+
+        let mut routes = HashMap::new();
+        read_table(&mut archive, "routes.txt", true, |route: RouteRecord| {
+            let name = if route.route_short_name.is_empty() { route.route_long_name } else { route.route_short_name };
+            routes.insert(route.route_id, name);
+        })?;
+
+        let mut trips = HashMap::new();
+        read_table(&mut archive, "trips.txt", true, |trip: TripRecord| {
+            if trip_ids.contains(&trip.trip_id) {
+                trips.insert(trip.trip_id, Trip {
+                    route: routes.get(&trip.route_id).cloned().unwrap_or(trip.route_id),
+                    service_id: trip.service_id,
+                    headsign: trip.trip_headsign,
+                });
+            }
+        })?;
+
+        // Feeds may describe their services with calendar.txt, calendar_dates.txt or both.
+        let mut services = HashMap::new();
+        read_table(&mut archive, "calendar.txt", false, |calendar: CalendarRecord| {
+            if let (Some(start), Some(end)) = (parse_date(&calendar.start_date), parse_date(&calendar.end_date)) {
+                let days = [
+                    calendar.monday == 1,
+                    calendar.tuesday == 1,
+                    calendar.wednesday == 1,
+                    calendar.thursday == 1,
+                    calendar.friday == 1,
+                    calendar.saturday == 1,
+                    calendar.sunday == 1,
+                ];
+                services.insert(calendar.service_id, Service { days, start, end });
+            }
+        })?;
+        let mut exceptions = HashMap::new();
+        read_table(&mut archive, "calendar_dates.txt", false, |exception: CalendarDateRecord| {
+            if let Some(date) = parse_date(&exception.date) {
+                exceptions.insert((exception.service_id, date), exception.exception_type == 1);
+            }
+        })?;
+
+        Ok(Feed { stops, trips, services, exceptions, stop_times, sequences })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stops.is_empty()
+    }
+
+    fn sequence(&self, trip_id: &str, stop_id: &str) -> Option<u32> {
+        self.sequences.get(trip_id)?.get(stop_id).copied()
+    }
+
+    fn runs(&self, service_id: &str, date: NaiveDate) -> bool {
+        if let Some(added) = self.exceptions.get(&(service_id.to_string(), date)) {
+            return *added;
+        }
+        match self.services.get(service_id) {
+            Some(service) => service.start <= date && date <= service.end && service.days[date.weekday().num_days_from_monday() as usize],
+            None => false,
+        }
+    }
+}
+
+// Subset of gtfs-realtime.proto: trip updates are all the board needs.
+#[derive(Clone, PartialEq, Message)]
+pub struct FeedMessage {
+    #[prost(message, optional, tag = "1")]
+    pub header: Option<FeedHeader>,
+    #[prost(message, repeated, tag = "2")]
+    pub entity: Vec<FeedEntity>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct FeedHeader {
+    #[prost(string, required, tag = "1")]
+    pub gtfs_realtime_version: String,
+    #[prost(uint64, optional, tag = "3")]
+    pub timestamp: Option<u64>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct FeedEntity {
+    #[prost(string, required, tag = "1")]
+    pub id: String,
+    #[prost(bool, optional, tag = "2")]
+    pub is_deleted: Option<bool>,
+    #[prost(message, optional, tag = "3")]
+    pub trip_update: Option<TripUpdate>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct TripUpdate {
+    #[prost(message, required, tag = "1")]
+    pub trip: TripDescriptor,
+    #[prost(message, repeated, tag = "2")]
+    pub stop_time_update: Vec<StopTimeUpdate>,
+    #[prost(int32, optional, tag = "5")]
+    pub delay: Option<i32>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct TripDescriptor {
+    #[prost(string, optional, tag = "1")]
+    pub trip_id: Option<String>,
+    // 3 = CANCELED
+    #[prost(int32, optional, tag = "4")]
+    pub schedule_relationship: Option<i32>,
+    #[prost(string, optional, tag = "5")]
+    pub route_id: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct StopTimeUpdate {
+    #[prost(uint32, optional, tag = "1")]
+    pub stop_sequence: Option<u32>,
+    #[prost(message, optional, tag = "2")]
+    pub arrival: Option<StopTimeEvent>,
+    #[prost(message, optional, tag = "3")]
+    pub departure: Option<StopTimeEvent>,
+    #[prost(string, optional, tag = "4")]
+    pub stop_id: Option<String>,
+    // 1 = SKIPPED
+    #[prost(int32, optional, tag = "5")]
+    pub schedule_relationship: Option<i32>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct StopTimeEvent {
+    #[prost(int32, optional, tag = "1")]
+    pub delay: Option<i32>,
+    #[prost(int64, optional, tag = "2")]
+    pub time: Option<i64>,
+}
+
+const TRIP_CANCELED: i32 = 3;
+const STOP_SKIPPED: i32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Prediction {
+    Scheduled,
+    Predicted(i64),
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Realtime {
+    trips: HashMap<String, TripUpdate>,
+}
+
+impl Realtime {
+    pub fn decode(bytes: &[u8]) -> Result<Realtime, prost::DecodeError> {
+        let message = FeedMessage::decode(bytes)?;
+        let trips = message
+            .entity
+            .into_iter()
+            .filter(|entity| !entity.is_deleted.unwrap_or(false))
+            .filter_map(|entity| entity.trip_update)
+            .filter_map(|update| Some((update.trip.trip_id.clone()?, update)))
+            .collect();
+        Ok(Realtime { trips })
+    }
+
+    // The update of the stop itself wins; otherwise the delay of the last updated stop
+    // before it carries over, as the specification asks.
+    fn prediction(&self, feed: &Feed, stop_time: &StopTime, scheduled: i64) -> Prediction {
+        let update = match self.trips.get(&stop_time.trip_id) {
+            Some(update) => update,
+            None => return Prediction::Scheduled,
+        };
+        if update.trip.schedule_relationship == Some(TRIP_CANCELED) {
+            return Prediction::Cancelled;
+        }
+        let mut delay = update.delay;
+        for stop_update in &update.stop_time_update {
+            let event = stop_update.departure.as_ref().or(stop_update.arrival.as_ref());
+            let sequence = stop_update.stop_sequence.or_else(|| {
+                feed.sequence(&stop_time.trip_id, stop_update.stop_id.as_deref()?)
+            });
+            let same_stop = match sequence {
+                Some(sequence) => sequence == stop_time.sequence,
+                None => stop_update.stop_id.as_ref() == Some(&stop_time.stop_id),
+            };
+            if same_stop {
+                if stop_update.schedule_relationship == Some(STOP_SKIPPED) {
+                    return Prediction::Cancelled;
+                }
+                if let Some(time) = event.and_then(|event| event.time) {
+                    return Prediction::Predicted(time);
+                }
+                if let Some(stop_delay) = event.and_then(|event| event.delay) {
+                    delay = Some(stop_delay);
+                }
+                break;
+            }
+            if matches!(sequence, Some(sequence) if sequence < stop_time.sequence) {
+                if let Some(stop_delay) = event.and_then(|event| event.delay) {
+                    delay = Some(stop_delay);
+                }
+            }
+        }
+        match delay {
+            Some(delay) => Prediction::Predicted(scheduled + delay as i64),
+            None => Prediction::Scheduled,
+        }
+    }
+}
+
+pub fn get_realtime(url: &str) -> Result<Realtime, Box<dyn Error>> {
+    let bytes = reqwest::blocking::get(url)?.error_for_status()?.bytes()?;
+    Ok(Realtime::decode(&bytes)?)
+}
+
+// The feed in memory is read again when the file, its modification time or the stops change.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Source {
+    pub path: PathBuf,
+    pub stops: String,
+    pub modified: Option<SystemTime>,
+}
+
+impl Source {
+    pub fn new(path: &str, stops: &str) -> Source {
+        let path = PathBuf::from(path.trim());
+        let modified = std::fs::metadata(&path).and_then(|metadata| metadata.modified()).ok();
+        Source { path, stops: stops.to_string(), modified }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Update {
+    // Only when the feed had to be read again.
+    pub feed: Option<Feed>,
+    pub realtime: Realtime,
+}
+
+// A large feed takes seconds to read and the realtime URL may be slow to answer:
+// both run on a thread of their own.
+pub async fn fetch_in_background(source: Source, reload: bool, realtime_url: String) -> Result<Update, String> {
+    async_std::task::spawn_blocking(move || {
+        let feed = if reload {
+            Some(Feed::load(&source.path, &parse_stops(&source.stops)).map_err(|e| e.to_string())?)
+        } else {
+            None
+        };
+        let realtime = if realtime_url.is_empty() {
+            Realtime::default()
+        } else {
+            get_realtime(&realtime_url).unwrap_or_else(|e| {
+                log::warn!("Mises à jour GTFS-Realtime indisponibles : {}", e);
+                Realtime::default()
+            })
+        };
+        Ok(Update { feed, realtime })
+    })
+    .await
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Departure {
+    pub route: String,
+    pub headsign: String,
+    // Unix timestamp, predicted when `realtime` is set.
+    pub time: i64,
+    pub minutes: i64,
+    pub realtime: bool,
+}
+
+impl Departure {
+    pub fn wait(&self) -> String {
+        if self.minutes == 0 {
+            String::from("à l'approche")
+        } else {
+            format!("{} min", self.minutes)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Board {
+    pub stop: String,
+    pub departures: Vec<Departure>,
+}
+
+pub fn boards<Tz: TimeZone>(feed: &Feed, realtime: &Realtime, now: DateTime<Tz>) -> Vec<Board> {
+    let today = now.naive_local().date();
+    let timezone = now.timezone();
+    let now = now.timestamp();
+    // Yesterday's service runs past midnight with times such as 25:10:00.
+    let days: Vec<(NaiveDate, i64)> = [today.pred_opt(), Some(today)]
+        .iter()
+        .flatten()
+        .filter_map(|day| {
+            let noon = timezone.from_local_datetime(&day.and_hms_opt(12, 0, 0)?).earliest()?;
+            Some((*day, noon.timestamp() - 12 * 3600))
+        })
+        .collect();
+
+    feed.stops
+        .iter()
+        .map(|(name, ids)| {
+            let mut departures: Vec<Departure> = feed
+                .stop_times
+                .iter()
+                .filter(|stop_time| ids.contains(&stop_time.stop_id))
+                .flat_map(|stop_time| days.iter().map(move |day| (stop_time, day)))
+                .filter_map(|(stop_time, (day, start))| {
+                    let trip = feed.trips.get(&stop_time.trip_id)?;
+                    if !feed.runs(&trip.service_id, *day) {
+                        return None;
+                    }
+                    let scheduled = start + stop_time.departure;
+                    let (time, realtime) = match realtime.prediction(feed, stop_time, scheduled) {
+                        Prediction::Scheduled => (scheduled, false),
+                        Prediction::Predicted(time) => (time, true),
+                        Prediction::Cancelled => return None,
+                    };
+                    if time < now || time > now + Duration::minutes(HORIZON_MINUTES).num_seconds() {
+                        return None;
+                    }
+                    Some(Departure {
+                        route: trip.route.clone(),
+                        headsign: trip.headsign.clone(),
+                        time,
+                        minutes: (time - now) / 60,
+                        realtime,
+                    })
+                })
+                .collect();
+            departures.sort_by_key(|departure| departure.time);
+            departures.truncate(DEPARTURES_PER_STOP);
+            Board { stop: name.clone(), departures }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+
+    const STOPS: &str = "stop_id,stop_name,parent_station,location_type\n\
+        GARE,Gare Lille Flandres,,1\n\
+        GARE_A,Gare Lille Flandres,GARE,0\n\
+        GARE_B,Gare Lille Flandres,GARE,0\n\
+        REP,République,,0\n";
+    const ROUTES: &str = "route_id,agency_id,route_short_name,route_long_name,route_type\n\
+        M1,ILE,1,Quatre Cantons - CHU,1\n\
+        L10,ILE,,Liane 10,3\n";
+    const TRIPS: &str = "route_id,service_id,trip_id,trip_headsign\n\
+        M1,WEEK,T1,CHU Eurasanté\n\
+        M1,WEEK,T2,CHU Eurasanté\n\
+        L10,WEEK,T3,Lomme\n\
+        L10,SUNDAY,T4,Lomme\n\
+        M1,WEEK,T5,Quatre Cantons\n";
+    const STOP_TIMES: &str = "trip_id,arrival_time,departure_time,stop_id,stop_sequence\n\
+        T1,08:04:00,08:05:00,GARE_A,3\n\
+        T1,08:07:00,08:07:00,REP,4\n\
+        T2,08:20:00,08:20:00,GARE_A,3\n\
+        T3,08:10:00,,GARE_B,1\n\
+        T4,08:12:00,08:12:00,GARE_B,1\n\
+        T5,24:30:00,24:30:00,GARE_A,1\n";
+    const CALENDAR: &str = "service_id,monday,tuesday,wednesday,thursday,friday,saturday,sunday,start_date,end_date\n\
+        WEEK,1,1,1,1,1,0,0,20210101,20211231\n\
+        SUNDAY,0,0,0,0,0,0,1,20210101,20211231\n";
+    const CALENDAR_DATES: &str = "service_id,date,exception_type\n\
+        WEEK,20210714,2\n";
+
+    fn feed_zip() -> Cursor<Vec<u8>> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        for (name, contents) in [
+            ("stops.txt", STOPS),
+            ("routes.txt", ROUTES),
+            ("trips.txt", TRIPS),
+            ("stop_times.txt", STOP_TIMES),
+            ("calendar.txt", CALENDAR),
+            ("calendar_dates.txt", CALENDAR_DATES),
+        ]
+        .iter()
+        {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        let mut cursor = writer.finish().unwrap();
+        cursor.set_position(0);
+        cursor
+    }
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<FixedOffset> {
+        let time = NaiveDate::from_ymd_opt(2021, 7, day).unwrap().and_hms_opt(hour, minute, 0).unwrap();
+        FixedOffset::east_opt(7200).unwrap().from_local_datetime(&time).unwrap()
+    }
+
+    fn summary(board: &Board) -> Vec<(String, String, i64, bool)> {
+        board.departures.iter().map(|d| (d.route.clone(), d.headsign.clone(), d.minutes, d.realtime)).collect()
+    }
+
+    #[test]
+    fn test_parse_helpers() {
+        assert_eq!(parse_stops("GARE; République ;"), vec!["GARE".to_string(), "République".to_string()]);
+        assert_eq!(parse_time("25:10:30"), Some(90630));
+        assert_eq!(parse_time("8:05"), None);
+        assert_eq!(parse_date("20210714"), NaiveDate::from_ymd_opt(2021, 7, 14));
+    }
+
+    #[test]
+    fn test_scheduled_departures() {
+        let feed = Feed::read(feed_zip(), &parse_stops("GARE;république;Inconnu")).unwrap();
+        assert!(!feed.is_empty());
+        // Thursday 15 July 2021, 08:00.
+        let boards = boards(&feed, &Realtime::default(), at(15, 8, 0));
+        assert_eq!(boards.len(), 2);
+        assert_eq!(boards[0].stop, "Gare Lille Flandres");
+        assert_eq!(summary(&boards[0]), vec![
+            ("1".to_string(), "CHU Eurasanté".to_string(), 5, false),
+            ("Liane 10".to_string(), "Lomme".to_string(), 10, false),
+            ("1".to_string(), "CHU Eurasanté".to_string(), 20, false),
+        ]);
+        assert_eq!(boards[1].stop, "République");
+        assert_eq!(boards[1].departures[0].wait(), "7 min");
+
+        // Bastille Day is removed from the weekday service; Sunday 18 July runs the Sunday trip.
+        assert!(super::boards(&feed, &Realtime::default(), at(14, 8, 0))[0].departures.is_empty());
+        let sunday = super::boards(&feed, &Realtime::default(), at(18, 8, 0));
+        assert_eq!(summary(&sunday[0]), vec![("Liane 10".to_string(), "Lomme".to_string(), 12, false)]);
+
+        // A trip of Thursday's service leaving at 24:30 is shown on Friday at 00:15.
+        let night = super::boards(&feed, &Realtime::default(), at(16, 0, 15));
+        assert_eq!(summary(&night[0]), vec![("1".to_string(), "Quatre Cantons".to_string(), 15, false)]);
+    }
+
+    #[test]
+    fn test_realtime_updates() {
+        let feed = Feed::read(feed_zip(), &parse_stops("GARE")).unwrap();
+        let now = at(15, 8, 0);
+        let trip = |id: &str, relationship: Option<i32>, stop_time_update: Vec<StopTimeUpdate>, delay: Option<i32>| FeedEntity {
+            id: id.to_string(),
+            is_deleted: None,
+            trip_update: Some(TripUpdate {
+                trip: TripDescriptor { trip_id: Some(id.to_string()), schedule_relationship: relationship, route_id: None },
+                stop_time_update,
+                delay,
+            }),
+        };
+        let message = FeedMessage {
+            header: Some(FeedHeader { gtfs_realtime_version: "2.0".to_string(), timestamp: Some(now.timestamp() as u64) }),
+            entity: vec![
+                // T1: three minutes late from an earlier stop.
+                trip("T1", None, vec![StopTimeUpdate {
+                    stop_sequence: Some(2),
+                    departure: Some(StopTimeEvent { delay: Some(180), time: None }),
+                    ..StopTimeUpdate::default()
+                }], None),
+                // T2: cancelled.
+                trip("T2", Some(TRIP_CANCELED), Vec::new(), None),
+                // T3: predicted time at the stop itself.
+                trip("T3", None, vec![StopTimeUpdate {
+                    stop_id: Some("GARE_B".to_string()),
+                    arrival: Some(StopTimeEvent { delay: None, time: Some(now.timestamp() + 8 * 60) }),
+                    ..StopTimeUpdate::default()
+                }], Some(600)),
+            ],
+        };
+        let realtime = Realtime::decode(&message.encode_to_vec()).unwrap();
+        let boards = boards(&feed, &realtime, now);
+        assert_eq!(summary(&boards[0]), vec![
+            ("1".to_string(), "CHU Eurasanté".to_string(), 8, true),
+            ("Liane 10".to_string(), "Lomme".to_string(), 8, true),
+        ]);
+        assert!(Realtime::decode(b"\xff\xff").is_err());
+    }
+
+    #[test]
+    fn test_realtime_delay_from_stop_id() {
+        // Only République is selected: GARE_A's stop time is not among those kept.
+        let feed = Feed::read(feed_zip(), &parse_stops("République")).unwrap();
+        let now = at(15, 8, 0);
+        let message = FeedMessage {
+            header: Some(FeedHeader { gtfs_realtime_version: "2.0".to_string(), timestamp: Some(now.timestamp() as u64) }),
+            entity: vec![FeedEntity {
+                id: "T1".to_string(),
+                is_deleted: None,
+                trip_update: Some(TripUpdate {
+                    trip: TripDescriptor { trip_id: Some("T1".to_string()), schedule_relationship: None, route_id: None },
+                    // Two minutes late at the earlier stop GARE_A, named without its sequence.
+                    stop_time_update: vec![StopTimeUpdate {
+                        stop_id: Some("GARE_A".to_string()),
+                        departure: Some(StopTimeEvent { delay: Some(120), time: None }),
+                        ..StopTimeUpdate::default()
+                    }],
+                    delay: None,
+                }),
+            }],
+        };
+        let realtime = Realtime::decode(&message.encode_to_vec()).unwrap();
+        let boards = boards(&feed, &realtime, now);
+        assert_eq!(summary(&boards[0]), vec![("1".to_string(), "CHU Eurasanté".to_string(), 9, true)]);
+    }
+
+    #[test]
+    fn test_fetch_in_background() {
+        let dir = std::env::temp_dir().join(format!("assistant-gtfs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("gtfs.zip");
+        std::fs::write(&path, feed_zip().into_inner()).unwrap();
+
+        let source = Source::new(path.to_str().unwrap(), "GARE");
+        assert!(source.modified.is_some());
+        assert_eq!(Source::new(path.to_str().unwrap(), "GARE"), source);
+        let update = async_std::task::block_on(fetch_in_background(source.clone(), true, String::new())).unwrap();
+        assert!(!update.feed.unwrap().is_empty());
+        let update = async_std::task::block_on(fetch_in_background(source, false, String::new())).unwrap();
+        assert!(update.feed.is_none());
+
+        let missing = Source::new(dir.join("absent.zip").to_str().unwrap(), "GARE");
+        assert_eq!(missing.modified, None);
+        assert!(async_std::task::block_on(fetch_in_background(missing, true, String::new())).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_table() {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        writer.start_file("stops.txt", zip::write::FileOptions::default()).unwrap();
+        writer.write_all(STOPS.as_bytes()).unwrap();
+        let error = Feed::read(writer.finish().unwrap(), &parse_stops("GARE")).unwrap_err();
+        assert!(error.to_string().starts_with("stop_times.txt"));
+    }
+}
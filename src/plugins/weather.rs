@@ -1,16 +1,370 @@
+use chrono::DateTime;
 use json::JsonValue;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
 use std::error::Error;
 
-pub fn get_weather_from_search(location: &str, api_key: &str)  -> Result<JsonValue, Box<dyn Error>> {
-    let url1 = "http://api.openweathermap.org/data/2.5/weather?q=";
-    let url2 = "&appid=";
-    let url = [url1, location, url2, api_key].concat();
-    let resp = reqwest::blocking::get(&url)?.text()?;
-    Ok(json::parse(&resp).unwrap())
+use crate::plugins::{air, alerts};
+
+// MET Norway refuses requests that do not identify the application.
+const USER_AGENT: &str = concat!("assistant_gui/", env!("CARGO_PKG_VERSION"));
+// The weather is fetched again this often whatever the page, so that alerts still fire.
+pub const REFRESH_SECONDS: u64 = 10 * 60;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Provider {
+    #[default]
+    OpenWeatherMap,
+    OpenMeteo,
+    MetNorway,
+}
+
+impl Provider {
+    pub const ALL: [Provider; 3] = [Provider::OpenWeatherMap, Provider::OpenMeteo, Provider::MetNorway];
+
+    pub fn needs_api_key(self) -> bool {
+        self == Provider::OpenWeatherMap
+    }
+
+    pub fn is_configured(self, api_key: &str) -> bool {
+        !self.needs_api_key() || !api_key.trim().is_empty()
+    }
+
+    pub fn backend(self, api_key: &str) -> Box<dyn WeatherProvider> {
+        match self {
+            Provider::OpenWeatherMap => Box::new(OpenWeatherMap { api_key: api_key.trim().to_string() }),
+            Provider::OpenMeteo => Box::new(OpenMeteo),
+            Provider::MetNorway => Box::new(MetNorway),
+        }
+    }
+}
+
+impl std::fmt::Display for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Provider::OpenWeatherMap => "OpenWeatherMap (clé API)",
+                Provider::OpenMeteo => "Open-Meteo",
+                Provider::MetNorway => "MET Norway",
+            }
+        )
+    }
+}
+
+// Where to look: the city when one is entered, the coordinates of the parameter page otherwise.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    pub city: String,
+    pub coordinates: Option<(f64, f64)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeatherReport {
+    pub place: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    // Unix timestamp of the measurement, or of the forecast step for forecast-only providers.
+    pub time: i64,
+    // °C
+    pub temperature: f32,
+    pub temperature_min: Option<f32>,
+    pub temperature_max: Option<f32>,
+    // %
+    pub humidity: Option<f32>,
+    // hPa, at sea level
+    pub pressure: Option<f32>,
+    // km/h
+    pub wind: Option<f32>,
+    pub description: String,
+    // Hours until the first rainy hour, for the providers that return an hourly forecast.
+    pub rain_in: Option<u32>,
+}
+
+// Everything a refresh downloads: the report, then One Call, air quality and UV at its coordinates.
+#[derive(Debug, Clone)]
+pub struct Update {
+    pub report: WeatherReport,
+    pub one_call: Option<JsonValue>,
+    pub air_quality: Option<air::AirQuality>,
+    pub uv_index: Option<f32>,
+}
+
+pub fn fetch(provider: Provider, api_key: &str, query: &Query) -> Result<Update, Box<dyn Error>> {
+    let report = provider.backend(api_key).current(query)?;
+    let api_key = api_key.trim();
+    let one_call = if provider == Provider::OpenWeatherMap {
+        alerts::get_one_call(report.latitude, report.longitude, api_key)
+            .map_err(|e| log::warn!("Prévisions et alertes officielles indisponibles : {}", e))
+            .ok()
+    } else {
+        None
+    };
+    // Air quality needs an OpenWeatherMap key whatever the weather provider; the UV index does not.
+    let air_quality = if api_key.is_empty() {
+        None
+    } else {
+        air::get_air_pollution(report.latitude, report.longitude, api_key)
+            .map_err(|e| log::warn!("Qualité de l'air indisponible : {}", e))
+            .ok()
+    };
+    let uv_index = one_call.as_ref().and_then(air::one_call_uv_index).or_else(|| {
+        air::get_uv_index(report.latitude, report.longitude)
+            .map_err(|e| log::warn!("Indice UV indisponible : {}", e))
+            .ok()
+    });
+    Ok(Update { report, one_call, air_quality, uv_index })
+}
+
+// The requests block: run them on a thread of their own.
+pub async fn fetch_in_background(provider: Provider, api_key: String, query: Query) -> Result<Update, String> {
+    async_std::task::spawn_blocking(move || fetch(provider, &api_key, &query).map_err(|e| e.to_string())).await
+}
+
+pub trait WeatherProvider {
+    fn current(&self, query: &Query) -> Result<WeatherReport, Box<dyn Error>>;
+}
+
+pub struct OpenWeatherMap {
+    pub api_key: String,
+}
+
+impl WeatherProvider for OpenWeatherMap {
+    fn current(&self, query: &Query) -> Result<WeatherReport, Box<dyn Error>> {
+        if self.api_key.is_empty() {
+            return Err("OpenWeatherMap a besoin d'une clé API".into());
+        }
+        let mut params = vec![
+            ("units", "metric".to_string()),
+            ("lang", "fr".to_string()),
+            ("appid", self.api_key.clone()),
+        ];
+        match query.coordinates {
+            Some((latitude, longitude)) if query.city.is_empty() => {
+                params.push(("lat", latitude.to_string()));
+                params.push(("lon", longitude.to_string()));
+            }
+            _ => params.push(("q", query.city.clone())),
+        }
+        let url = Url::parse_with_params("https://api.openweathermap.org/data/2.5/weather", &params)?;
+        parse_openweathermap(&json::parse(&reqwest::blocking::get(url)?.text()?)?)
+    }
+}
+
+pub struct OpenMeteo;
+
+impl WeatherProvider for OpenMeteo {
+    fn current(&self, query: &Query) -> Result<WeatherReport, Box<dyn Error>> {
+        let (place, latitude, longitude) = locate(query)?;
+        let url = Url::parse_with_params("https://api.open-meteo.com/v1/forecast", &[
+            ("latitude", latitude.to_string()),
+            ("longitude", longitude.to_string()),
+            ("current", "temperature_2m,relative_humidity_2m,pressure_msl,wind_speed_10m,weather_code".to_string()),
+            ("hourly", "precipitation".to_string()),
+            ("daily", "temperature_2m_max,temperature_2m_min".to_string()),
+            ("forecast_days", "2".to_string()),
+            ("timezone", "auto".to_string()),
+            ("timeformat", "unixtime".to_string()),
+        ])?;
+        parse_open_meteo(&json::parse(&reqwest::blocking::get(url)?.text()?)?, &place, latitude, longitude)
+    }
+}
+
+pub struct MetNorway;
+
+impl WeatherProvider for MetNorway {
+    fn current(&self, query: &Query) -> Result<WeatherReport, Box<dyn Error>> {
+        let (place, latitude, longitude) = locate(query)?;
+        // The terms of service ask for at most four decimals so that responses can be cached.
+        let url = Url::parse_with_params("https://api.met.no/weatherapi/locationforecast/2.0/compact", &[
+            ("lat", format!("{:.4}", latitude)),
+            ("lon", format!("{:.4}", longitude)),
+        ])?;
+        let client = reqwest::blocking::Client::builder().user_agent(USER_AGENT).build()?;
+        let response = client.get(url).send()?.error_for_status()?;
+        parse_met_norway(&json::parse(&response.text()?)?, &place, latitude, longitude)
+    }
+}
+
+// The keyless providers only take coordinates; a city name is looked up with the Open-Meteo geocoder.
+fn locate(query: &Query) -> Result<(String, f64, f64), Box<dyn Error>> {
+    if query.city.is_empty() {
+        let (latitude, longitude) = query.coordinates.ok_or("Ville ou coordonnées manquantes. Veuillez vérifier vos paramètres")?;
+        return Ok((format!("{:.2}, {:.2}", latitude, longitude), latitude, longitude));
+    }
+    let url = Url::parse_with_params("https://geocoding-api.open-meteo.com/v1/search", &[
+        ("name", query.city.as_str()),
+        ("count", "1"),
+        ("language", "fr"),
+        ("format", "json"),
+    ])?;
+    parse_geocoding(&json::parse(&reqwest::blocking::get(url)?.text()?)?)
+        .ok_or_else(|| format!("Ville introuvable : {}", query.city).into())
+}
+
+fn parse_geocoding(response: &JsonValue) -> Option<(String, f64, f64)> {
+    let result = &response["results"][0];
+    Some((result["name"].as_str()?.to_string(), result["latitude"].as_f64()?, result["longitude"].as_f64()?))
+}
+
+// A current weather response requested with `units=metric`.
+pub fn parse_openweathermap(response: &JsonValue) -> Result<WeatherReport, Box<dyn Error>> {
+    if response["cod"] == 401 || response["cod"] == "401" {
+        return Err("Clé API invalide. Veuillez vérifier vos paramètres".into());
+    }
+    let temperature = match response["main"]["temp"].as_f32() {
+        Some(temperature) => temperature,
+        None => return Err(response["message"].as_str().unwrap_or("Réponse OpenWeatherMap inattendue").to_string().into()),
+    };
+    Ok(WeatherReport {
+        place: response["name"].as_str().unwrap_or("").to_string(),
+        latitude: response["coord"]["lat"].as_f64().unwrap_or(0.0),
+        longitude: response["coord"]["lon"].as_f64().unwrap_or(0.0),
+        time: response["dt"].as_i64().unwrap_or(0),
+        temperature,
+        temperature_min: response["main"]["temp_min"].as_f32(),
+        temperature_max: response["main"]["temp_max"].as_f32(),
+        humidity: response["main"]["humidity"].as_f32(),
+        pressure: response["main"]["pressure"].as_f32(),
+        wind: response["wind"]["speed"].as_f32().map(|speed| speed * 3.6),
+        description: capitalize(response["weather"][0]["description"].as_str().unwrap_or("")),
+        rain_in: None,
+    })
+}
+
+// A forecast response requested with `timeformat=unixtime`.
+pub fn parse_open_meteo(response: &JsonValue, place: &str, latitude: f64, longitude: f64) -> Result<WeatherReport, Box<dyn Error>> {
+    let current = &response["current"];
+    let temperature = match current["temperature_2m"].as_f32() {
+        Some(temperature) => temperature,
+        None => return Err(response["reason"].as_str().unwrap_or("Réponse Open-Meteo inattendue").to_string().into()),
+    };
+    let time = current["time"].as_i64().unwrap_or(0);
+    let hourly = &response["hourly"];
+    // Each hourly precipitation covers the hour before its timestamp.
+    let rain_in = hourly["time"]
+        .members()
+        .zip(hourly["precipitation"].members())
+        .filter_map(|(hour, precipitation)| Some((hour.as_i64()?, precipitation.as_f32()?)))
+        .find(|(hour, precipitation)| *hour > time && *precipitation > 0.0)
+        .map(|(hour, _)| ((hour - 3600 - time).max(0) / 3600) as u32);
+    Ok(WeatherReport {
+        place: place.to_string(),
+        latitude,
+        longitude,
+        time,
+        temperature,
+        temperature_min: response["daily"]["temperature_2m_min"][0].as_f32(),
+        temperature_max: response["daily"]["temperature_2m_max"][0].as_f32(),
+        humidity: current["relative_humidity_2m"].as_f32(),
+        pressure: current["pressure_msl"].as_f32(),
+        wind: current["wind_speed_10m"].as_f32(),
+        description: current["weather_code"].as_u32().map(wmo_description).unwrap_or("").to_string(),
+        rain_in,
+    })
+}
+
+// A locationforecast compact response: the first step stands for the current weather and
+// the next 24 hours give the minimum and maximum.
+pub fn parse_met_norway(response: &JsonValue, place: &str, latitude: f64, longitude: f64) -> Result<WeatherReport, Box<dyn Error>> {
+    let steps: Vec<(i64, &JsonValue)> = response["properties"]["timeseries"]
+        .members()
+        .filter_map(|step| Some((DateTime::parse_from_rfc3339(step["time"].as_str()?).ok()?.timestamp(), &step["data"])))
+        .collect();
+    let (time, first) = match steps.first() {
+        Some(first) => *first,
+        None => return Err("Réponse MET Norway inattendue".into()),
+    };
+    let details = &first["instant"]["details"];
+    let temperature = details["air_temperature"].as_f32().ok_or("Réponse MET Norway inattendue")?;
+    let day: Vec<f32> = steps
+        .iter()
+        .take_while(|(step, _)| *step < time + 86400)
+        .filter_map(|(_, data)| data["instant"]["details"]["air_temperature"].as_f32())
+        .collect();
+    let rain_in = steps
+        .iter()
+        .find(|(_, data)| data["next_1_hours"]["details"]["precipitation_amount"].as_f32().unwrap_or(0.0) > 0.0)
+        .map(|(step, _)| ((step - time).max(0) / 3600) as u32);
+    let symbol = first["next_1_hours"]["summary"]["symbol_code"]
+        .as_str()
+        .or_else(|| first["next_6_hours"]["summary"]["symbol_code"].as_str())
+        .unwrap_or("");
+    Ok(WeatherReport {
+        place: place.to_string(),
+        latitude,
+        longitude,
+        time,
+        temperature,
+        temperature_min: day.iter().cloned().fold(None, |min: Option<f32>, t| Some(min.map_or(t, |m| m.min(t)))),
+        temperature_max: day.iter().cloned().fold(None, |max: Option<f32>, t| Some(max.map_or(t, |m| m.max(t)))),
+        humidity: details["relative_humidity"].as_f32(),
+        pressure: details["air_pressure_at_sea_level"].as_f32(),
+        wind: details["wind_speed"].as_f32().map(|speed| speed * 3.6),
+        description: symbol_description(symbol).to_string(),
+        rain_in,
+    })
+}
+
+// WMO weather interpretation codes, as used by Open-Meteo.
+fn wmo_description(code: u32) -> &'static str {
+    match code {
+        0 => "Ciel dégagé",
+        1 => "Peu nuageux",
+        2 => "Partiellement nuageux",
+        3 => "Couvert",
+        45 | 48 => "Brouillard",
+        51..=57 => "Bruine",
+        61..=67 => "Pluie",
+        71..=77 => "Neige",
+        80..=82 => "Averses",
+        85 | 86 => "Averses de neige",
+        95..=99 => "Orage",
+        _ => "",
+    }
+}
+
+// MET Norway symbol codes such as `lightrainshowers_day`.
+fn symbol_description(symbol: &str) -> &'static str {
+    let symbol = symbol.split('_').next().unwrap_or("");
+    match symbol {
+        "clearsky" => "Ciel dégagé",
+        "fair" => "Peu nuageux",
+        "partlycloudy" => "Partiellement nuageux",
+        "cloudy" => "Couvert",
+        "fog" => "Brouillard",
+        _ if symbol.contains("thunder") => "Orage",
+        _ if symbol.contains("sleet") => "Neige fondue",
+        _ if symbol.contains("snow") => "Neige",
+        _ if symbol.contains("showers") => "Averses",
+        _ if symbol.contains("rain") => "Pluie",
+        _ => "",
+    }
+}
+
+fn capitalize(value: &str) -> String {
+    let mut chars = value.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+pub fn format_temperature(temperature: f32) -> String {
+    temperature.floor().to_string()
 }
 
-pub fn calculate_temperature(temperature: f32)  -> String {
-    (temperature - 273.15).floor().to_string()
+pub fn summary(report: &WeatherReport) -> String {
+    let mut parts = Vec::new();
+    if !report.place.is_empty() {
+        parts.push(report.place.clone());
+    }
+    parts.push([format_temperature(report.temperature), "°C".to_string()].concat());
+    if !report.description.is_empty() {
+        parts.push(report.description.clone());
+    }
+    parts.join(" ")
 }
 
 
@@ -19,7 +373,90 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_calculate_temperature() {
-        assert_eq!(calculate_temperature(280.0), "6".to_string())
+    fn test_format_temperature() {
+        assert_eq!(format_temperature(6.85), "6".to_string());
+        assert_eq!(format_temperature(-0.5), "-1".to_string());
+    }
+
+    #[test]
+    fn test_summary() {
+        let weather = json::parse(r#"{"name": "Lille", "main": {"temp": 12.15}, "weather": [{"description": "couvert"}]}"#).unwrap();
+        assert_eq!(summary(&parse_openweathermap(&weather).unwrap()), "Lille 12°C Couvert".to_string());
+    }
+
+    #[test]
+    fn test_parse_openweathermap() {
+        let weather = json::parse(r#"{
+            "coord": {"lon": 3.06, "lat": 50.63}, "dt": 1620000000, "name": "Lille",
+            "weather": [{"main": "Clouds", "description": "nuageux"}],
+            "main": {"temp": 12.5, "temp_min": 10.1, "temp_max": 14.2, "pressure": 1012, "humidity": 81},
+            "wind": {"speed": 5}
+        }"#).unwrap();
+        let report = parse_openweathermap(&weather).unwrap();
+        assert_eq!((report.latitude, report.longitude, report.time), (50.63, 3.06, 1620000000));
+        assert_eq!((report.temperature_min, report.temperature_max), (Some(10.1), Some(14.2)));
+        assert_eq!((report.humidity, report.pressure, report.wind), (Some(81.0), Some(1012.0), Some(18.0)));
+        assert_eq!(report.description, "Nuageux");
+
+        let error = parse_openweathermap(&json::parse(r#"{"cod": 401, "message": "Invalid API key"}"#).unwrap()).unwrap_err();
+        assert!(error.to_string().starts_with("Clé API invalide"));
+        let error = parse_openweathermap(&json::parse(r#"{"cod": "404", "message": "city not found"}"#).unwrap()).unwrap_err();
+        assert_eq!(error.to_string(), "city not found");
+    }
+
+    #[test]
+    fn test_parse_open_meteo() {
+        let forecast = json::parse(r#"{
+            "current": {"time": 1620000000, "temperature_2m": 11.4, "relative_humidity_2m": 70,
+                        "pressure_msl": 1015.2, "wind_speed_10m": 12.6, "weather_code": 61},
+            "hourly": {"time": [1619996400, 1620000000, 1620003600, 1620007200, 1620010800],
+                       "precipitation": [0.4, 0.0, 0.0, 0.0, 1.2]},
+            "daily": {"temperature_2m_max": [15.0], "temperature_2m_min": [7.5]}
+        }"#).unwrap();
+        let report = parse_open_meteo(&forecast, "Lille", 50.63, 3.06).unwrap();
+        assert_eq!(report.place, "Lille");
+        assert_eq!((report.temperature, report.temperature_min, report.temperature_max), (11.4, Some(7.5), Some(15.0)));
+        assert_eq!((report.humidity, report.pressure, report.wind), (Some(70.0), Some(1015.2), Some(12.6)));
+        assert_eq!(report.description, "Pluie");
+        assert_eq!(report.rain_in, Some(2));
+
+        let error = json::parse(r#"{"error": true, "reason": "Latitude must be in range of -90 to 90°."}"#).unwrap();
+        assert_eq!(parse_open_meteo(&error, "", 0.0, 0.0).unwrap_err().to_string(), "Latitude must be in range of -90 to 90°.");
+    }
+
+    #[test]
+    fn test_parse_met_norway() {
+        let forecast = json::parse(r#"{"properties": {"timeseries": [
+            {"time": "2021-05-03T00:00:00Z", "data": {
+                "instant": {"details": {"air_temperature": 8.0, "relative_humidity": 90.5, "air_pressure_at_sea_level": 1009.8, "wind_speed": 2.5}},
+                "next_1_hours": {"summary": {"symbol_code": "partlycloudy_night"}, "details": {"precipitation_amount": 0.0}}}},
+            {"time": "2021-05-03T01:00:00Z", "data": {
+                "instant": {"details": {"air_temperature": 6.5}},
+                "next_1_hours": {"summary": {"symbol_code": "lightrain"}, "details": {"precipitation_amount": 0.3}}}},
+            {"time": "2021-05-03T12:00:00Z", "data": {"instant": {"details": {"air_temperature": 16.0}}}},
+            {"time": "2021-05-04T06:00:00Z", "data": {"instant": {"details": {"air_temperature": 2.0}}}}
+        ]}}"#).unwrap();
+        let report = parse_met_norway(&forecast, "Oslo", 59.91, 10.75).unwrap();
+        assert_eq!(report.time, 1620000000);
+        assert_eq!((report.temperature, report.temperature_min, report.temperature_max), (8.0, Some(6.5), Some(16.0)));
+        assert_eq!((report.humidity, report.pressure, report.wind), (Some(90.5), Some(1009.8), Some(9.0)));
+        assert_eq!(report.description, "Partiellement nuageux");
+        assert_eq!(report.rain_in, Some(1));
+        assert!(parse_met_norway(&json::parse(r#"{"properties": {"timeseries": []}}"#).unwrap(), "", 0.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_descriptions_and_geocoding() {
+        assert_eq!(symbol_description("heavysnowshowersandthunder_day"), "Orage");
+        assert_eq!(symbol_description("lightsleetshowers_night"), "Neige fondue");
+        assert_eq!(symbol_description("rainshowers_polartwilight"), "Averses");
+        assert_eq!(wmo_description(3), "Couvert");
+        let geocoding = json::parse(r#"{"results": [{"name": "Lille", "latitude": 50.63297, "longitude": 3.05858}]}"#).unwrap();
+        assert_eq!(parse_geocoding(&geocoding), Some(("Lille".to_string(), 50.63297, 3.05858)));
+        assert_eq!(parse_geocoding(&json::parse("{}").unwrap()), None);
+        assert_eq!(locate(&Query { city: String::new(), coordinates: Some((50.63, 3.06)) }).unwrap().0, "50.63, 3.06");
+        assert!(locate(&Query::default()).is_err());
+        assert!(Provider::OpenMeteo.is_configured(""));
+        assert!(!Provider::OpenWeatherMap.is_configured(" "));
     }
 }
\ No newline at end of file
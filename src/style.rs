@@ -1,81 +1,397 @@
-use iced::{button, container,text_input, scrollable, Background, Color};
+use iced::{button, container,text_input, progress_bar, scrollable, Background, Color};
+use std::collections::HashMap;
 
-pub enum Button {
+use crate::plugins::air;
+use crate::plugins::notes::NoteColor;
+use crate::plugins::remote::Level;
+use crate::plugins::theme::{self, ThemeChoice};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub background: Color,
+    pub text: Color,
+    pub surface: Color,
+    pub accent: Color,
+    pub active: Color,
+    pub hovered: Color,
+    pub dragging: Color,
+    pub placeholder: Color,
+    pub alert: Color,
+    pub button_text: Color,
+    pub button_hovered_text: Color,
+    pub calendar: Color,
+    pub clock: Color,
+    pub home: Color,
+    pub meteo: Color,
+    pub mqtt: Color,
+    pub news: Color,
+    pub notes: Color,
+    pub parameters: Color,
+    pub todo: Color,
+    pub clock_face: Color,
+    pub clock_hands: Color,
+    pub clock_second_hand: Color,
+    pub clock_ticks: Color,
+}
+
+impl Theme {
+    pub fn dark() -> Theme {
+        Theme {
+            background: Color::BLACK,
+            text: Color::WHITE,
+            surface: Color::from_rgb8(0x40, 0x44, 0x4B),
+            accent: Color::from_rgb8(0x6F, 0xFF, 0xE9),
+            active: Color::from_rgb8(0x72, 0x89, 0xDA),
+            hovered: Color::from_rgb8(0x67, 0x7B, 0xC4),
+            dragging: Color::from_rgb(0.85, 0.85, 0.85),
+            placeholder: Color::from_rgb(0.4, 0.4, 0.4),
+            alert: Color::from_rgb8(0xE5, 0x39, 0x35),
+            button_text: Color::from_rgb8(0xEE, 0xEE, 0xEE),
+            button_hovered_text: Color::WHITE,
+            calendar: Color::from_rgb(0.87, 0.11, 0.42),
+            clock: Color::from_rgb(0.11, 0.87, 0.42),
+            home: Color::from_rgb(0.11, 0.70, 0.70),
+            meteo: Color::from_rgb(0.11, 0.42, 0.87),
+            mqtt: Color::from_rgb(0.42, 0.60, 0.11),
+            news: Color::from_rgb(0.87, 0.42, 0.11),
+            notes: Color::from_rgb(0.87, 0.75, 0.11),
+            parameters: Color::from_rgb(0.5, 0.5, 0.5),
+            todo: Color::from_rgb(0.55, 0.27, 0.87),
+            clock_face: Color::from_rgb8(0x12, 0x93, 0xD8),
+            clock_hands: Color::WHITE,
+            clock_second_hand: Color::from_rgb8(0xEE, 0xEE, 0xEE),
+            clock_ticks: Color::from_rgb8(0xC8, 0xE6, 0xF5),
+        }
+    }
+
+    pub fn light() -> Theme {
+        Theme {
+            background: Color::from_rgb8(0xF5, 0xF5, 0xF5),
+            text: Color::from_rgb8(0x21, 0x21, 0x21),
+            surface: Color::from_rgb8(0xE0, 0xE0, 0xE0),
+            accent: Color::from_rgb8(0x1E, 0x88, 0xE5),
+            active: Color::from_rgb8(0x72, 0x89, 0xDA),
+            hovered: Color::from_rgb8(0x67, 0x7B, 0xC4),
+            dragging: Color::from_rgb(0.35, 0.35, 0.35),
+            placeholder: Color::from_rgb(0.6, 0.6, 0.6),
+            button_text: Color::WHITE,
+            clock_face: Color::WHITE,
+            clock_hands: Color::from_rgb8(0x21, 0x21, 0x21),
+            clock_second_hand: Color::from_rgb8(0xE5, 0x39, 0x35),
+            clock_ticks: Color::from_rgb8(0x61, 0x61, 0x61),
+            ..Theme::dark()
+        }
+    }
+
+    pub fn high_contrast() -> Theme {
+        Theme {
+            background: Color::BLACK,
+            text: Color::WHITE,
+            surface: Color::from_rgb8(0x1A, 0x1A, 0x1A),
+            accent: Color::from_rgb8(0xFF, 0xFF, 0x00),
+            active: Color::from_rgb8(0xFF, 0xFF, 0x00),
+            hovered: Color::from_rgb8(0xFF, 0xD6, 0x00),
+            dragging: Color::WHITE,
+            placeholder: Color::from_rgb(0.7, 0.7, 0.7),
+            alert: Color::from_rgb8(0xFF, 0x40, 0x40),
+            button_text: Color::BLACK,
+            button_hovered_text: Color::BLACK,
+            calendar: Color::from_rgb8(0xFF, 0x80, 0xC0),
+            clock: Color::from_rgb8(0x00, 0xFF, 0x00),
+            home: Color::from_rgb8(0x80, 0xFF, 0xC0),
+            meteo: Color::from_rgb8(0x00, 0xFF, 0xFF),
+            mqtt: Color::from_rgb8(0xC0, 0xFF, 0x40),
+            news: Color::from_rgb8(0xFF, 0xA0, 0x00),
+            notes: Color::from_rgb8(0xFF, 0xFF, 0x00),
+            parameters: Color::WHITE,
+            todo: Color::from_rgb8(0xD0, 0xA0, 0xFF),
+            clock_face: Color::BLACK,
+            clock_hands: Color::WHITE,
+            clock_second_hand: Color::from_rgb8(0xFF, 0xFF, 0x00),
+            clock_ticks: Color::WHITE,
+        }
+    }
+
+    pub fn night() -> Theme {
+        let red = Color::from_rgb8(0xB0, 0x10, 0x10);
+        let dim_red = Color::from_rgb8(0x50, 0x08, 0x08);
+        Theme {
+            background: Color::BLACK,
+            text: red,
+            surface: Color::from_rgb8(0x14, 0x00, 0x00),
+            accent: red,
+            active: dim_red,
+            hovered: red,
+            dragging: red,
+            placeholder: dim_red,
+            alert: red,
+            button_text: red,
+            button_hovered_text: Color::from_rgb8(0xE0, 0x20, 0x20),
+            calendar: Color::from_rgb8(0x20, 0x00, 0x00),
+            clock: Color::from_rgb8(0x20, 0x00, 0x00),
+            home: Color::from_rgb8(0x20, 0x00, 0x00),
+            meteo: Color::from_rgb8(0x20, 0x00, 0x00),
+            mqtt: Color::from_rgb8(0x20, 0x00, 0x00),
+            news: Color::from_rgb8(0x20, 0x00, 0x00),
+            notes: Color::from_rgb8(0x20, 0x00, 0x00),
+            parameters: Color::from_rgb8(0x20, 0x00, 0x00),
+            todo: Color::from_rgb8(0x20, 0x00, 0x00),
+            clock_face: Color::BLACK,
+            clock_hands: red,
+            clock_second_hand: dim_red,
+            clock_ticks: dim_red,
+        }
+    }
+
+    pub fn from_choice(choice: ThemeChoice) -> Theme {
+        match choice {
+            ThemeChoice::Dark => Theme::dark(),
+            ThemeChoice::Light => Theme::light(),
+            ThemeChoice::HighContrast => Theme::high_contrast(),
+            ThemeChoice::Custom => match theme::load_custom_theme() {
+                Ok(overrides) => Theme::from_overrides(&overrides),
+                Err(_e) => Theme::dark(),
+            },
+        }
+    }
+
+    pub fn from_overrides(overrides: &HashMap<String, String>) -> Theme {
+        let mut theme = match overrides.get("base").and_then(|base| ThemeChoice::from_name(base)) {
+            Some(choice) => Theme::from_choice(choice),
+            None => Theme::dark(),
+        };
+        for (key, value) in overrides {
+            if let Some([r, g, b]) = theme::parse_hex_color(value) {
+                theme.set(key, Color::from_rgb8(r, g, b));
+            }
+        }
+        theme
+    }
+
+    fn set(&mut self, key: &str, color: Color) {
+        let field = match key {
+            "background" => &mut self.background,
+            "text" => &mut self.text,
+            "surface" => &mut self.surface,
+            "accent" => &mut self.accent,
+            "active" => &mut self.active,
+            "hovered" => &mut self.hovered,
+            "dragging" => &mut self.dragging,
+            "placeholder" => &mut self.placeholder,
+            "alert" => &mut self.alert,
+            "button_text" => &mut self.button_text,
+            "button_hovered_text" => &mut self.button_hovered_text,
+            "calendar" => &mut self.calendar,
+            "clock" => &mut self.clock,
+            "home" => &mut self.home,
+            "meteo" => &mut self.meteo,
+            "mqtt" => &mut self.mqtt,
+            "news" => &mut self.news,
+            "notes" => &mut self.notes,
+            "parameters" => &mut self.parameters,
+            "todo" => &mut self.todo,
+            "clock_face" => &mut self.clock_face,
+            "clock_hands" => &mut self.clock_hands,
+            "clock_second_hand" => &mut self.clock_second_hand,
+            "clock_ticks" => &mut self.clock_ticks,
+            _ => return,
+        };
+        *field = color;
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::dark()
+    }
+}
+
+pub enum ButtonKind {
+    Calendar,
     Clock,
+    Home,
     Meteo,
+    Mqtt,
     News,
+    Notes,
     Parameters,
+    Todo,
 }
 
-const SURFACE: Color = Color::from_rgb(
-    0x40 as f32 / 255.0,
-    0x44 as f32 / 255.0,
-    0x4B as f32 / 255.0,
-);
-
-const ACCENT: Color = Color::from_rgb(
-    0x6F as f32 / 255.0,
-    0xFF as f32 / 255.0,
-    0xE9 as f32 / 255.0,
-);
-
-const ACTIVE: Color = Color::from_rgb(
-    0x72 as f32 / 255.0,
-    0x89 as f32 / 255.0,
-    0xDA as f32 / 255.0,
-);
-
-const HOVERED: Color = Color::from_rgb(
-    0x67 as f32 / 255.0,
-    0x7B as f32 / 255.0,
-    0xC4 as f32 / 255.0,
-);
+pub struct Button(pub ButtonKind, pub Theme);
 
 impl button::StyleSheet for Button {
     fn active(&self) -> button::Style {
+        let Button(kind, theme) = self;
         button::Style {
-            background: Some(Background::Color(match self {
-                Button::Clock => Color::from_rgb(0.11, 0.87, 0.42),
-                Button::Meteo => Color::from_rgb(0.11, 0.42, 0.87),
-                Button::News => Color::from_rgb(0.87, 0.42, 0.11),
-                Button::Parameters => Color::from_rgb(0.5, 0.5, 0.5),
+            background: Some(Background::Color(match kind {
+                ButtonKind::Calendar => theme.calendar,
+                ButtonKind::Clock => theme.clock,
+                ButtonKind::Home => theme.home,
+                ButtonKind::Meteo => theme.meteo,
+                ButtonKind::Mqtt => theme.mqtt,
+                ButtonKind::News => theme.news,
+                ButtonKind::Notes => theme.notes,
+                ButtonKind::Parameters => theme.parameters,
+                ButtonKind::Todo => theme.todo,
             })),
-            text_color: Color::from_rgb8(0xEE, 0xEE, 0xEE),
+            text_color: theme.button_text,
             ..button::Style::default()
         }
     }
 
     fn hovered(&self) -> button::Style {
         button::Style {
-            text_color: Color::WHITE,
+            text_color: self.1.button_hovered_text,
             ..self.active()
         }
     }
 }
 
-pub struct Container;
+pub struct Container(pub Theme);
 
 impl container::StyleSheet for Container {
     fn style(&self) -> container::Style {
         container::Style {
-            background: Color::from_rgb(0.0, 0.0, 0.0).into(),
+            background: self.0.background.into(),
+            text_color: self.0.text.into(),
+            ..container::Style::default()
+        }
+    }
+}
+
+pub struct Badge(pub Theme);
+
+impl container::StyleSheet for Badge {
+    fn style(&self) -> container::Style {
+        container::Style {
+            background: self.0.alert.into(),
+            text_color: Color::WHITE.into(),
+            border_radius: 10.0,
+            ..container::Style::default()
+        }
+    }
+}
+
+pub struct Tile(pub Theme);
+
+impl container::StyleSheet for Tile {
+    fn style(&self) -> container::Style {
+        container::Style {
+            background: self.0.mqtt.into(),
+            text_color: self.0.button_text.into(),
+            border_radius: 4.0,
+            ..container::Style::default()
+        }
+    }
+}
+
+pub struct Banner(pub Level, pub Theme);
+
+impl container::StyleSheet for Banner {
+    fn style(&self) -> container::Style {
+        container::Style {
+            background: match self.0 {
+                Level::Info => self.1.active,
+                Level::Warning => Color::from_rgb8(0xF5, 0x9E, 0x0B),
+                Level::Error => self.1.alert,
+            }
+            .into(),
             text_color: Color::WHITE.into(),
+            border_radius: 4.0,
             ..container::Style::default()
         }
     }
 }
 
-pub struct Scrollable;
+// Fixed colors so that the bands read the same as on the official air quality and UV scales.
+pub struct Severity(pub air::Severity);
+
+impl container::StyleSheet for Severity {
+    fn style(&self) -> container::Style {
+        let (background, text) = match self.0 {
+            air::Severity::Low => (Color::from_rgb8(0x4C, 0xAF, 0x50), Color::from_rgb8(0x21, 0x21, 0x21)),
+            air::Severity::Moderate => (Color::from_rgb8(0xFD, 0xD8, 0x35), Color::from_rgb8(0x21, 0x21, 0x21)),
+            air::Severity::High => (Color::from_rgb8(0xFB, 0x8C, 0x00), Color::from_rgb8(0x21, 0x21, 0x21)),
+            air::Severity::VeryHigh => (Color::from_rgb8(0xE5, 0x39, 0x35), Color::WHITE),
+            air::Severity::Extreme => (Color::from_rgb8(0x8E, 0x24, 0xAA), Color::WHITE),
+        };
+        container::Style {
+            background: background.into(),
+            text_color: text.into(),
+            border_radius: 4.0,
+            ..container::Style::default()
+        }
+    }
+}
+
+pub struct Note(pub NoteColor, pub Theme);
+
+impl Note {
+    fn color(&self) -> Color {
+        match self.0 {
+            NoteColor::Yellow => Color::from_rgb8(0xFF, 0xF1, 0x76),
+            NoteColor::Green => Color::from_rgb8(0xC5, 0xE1, 0xA5),
+            NoteColor::Blue => Color::from_rgb8(0x81, 0xD4, 0xFA),
+            NoteColor::Pink => Color::from_rgb8(0xF8, 0xBB, 0xD0),
+            NoteColor::Orange => Color::from_rgb8(0xFF, 0xCC, 0x80),
+        }
+    }
+}
+
+impl container::StyleSheet for Note {
+    fn style(&self) -> container::Style {
+        container::Style {
+            background: self.color().into(),
+            text_color: Color::from_rgb8(0x21, 0x21, 0x21).into(),
+            border_radius: 4.0,
+            ..container::Style::default()
+        }
+    }
+}
+
+impl text_input::StyleSheet for Note {
+    fn active(&self) -> text_input::Style {
+        text_input::Style {
+            background: self.color().into(),
+            border_radius: 2.0,
+            border_width: 0.0,
+            border_color: Color::TRANSPARENT,
+        }
+    }
+
+    fn focused(&self) -> text_input::Style {
+        text_input::Style {
+            border_width: 1.0,
+            border_color: Color::from_rgb8(0x21, 0x21, 0x21),
+            ..self.active()
+        }
+    }
+
+    fn placeholder_color(&self) -> Color {
+        Color::from_rgb(0.4, 0.4, 0.4)
+    }
+
+    fn value_color(&self) -> Color {
+        Color::from_rgb8(0x21, 0x21, 0x21)
+    }
+
+    fn selection_color(&self) -> Color {
+        self.1.active
+    }
+}
+
+pub struct Scrollable(pub Theme);
 
 impl scrollable::StyleSheet for Scrollable {
     fn active(&self) -> scrollable::Scrollbar {
         scrollable::Scrollbar {
-            background: SURFACE.into(),
+            background: self.0.surface.into(),
             border_radius: 2.0,
             border_width: 0.0,
             border_color: Color::TRANSPARENT,
             scroller: scrollable::Scroller {
-                color: ACTIVE,
+                color: self.0.active,
                 border_radius: 2.0,
                 border_width: 0.0,
                 border_color: Color::TRANSPARENT,
@@ -87,9 +403,9 @@ impl scrollable::StyleSheet for Scrollable {
         let active = self.active();
 
         scrollable::Scrollbar {
-            background: Color { a: 0.5, ..SURFACE }.into(),
+            background: Color { a: 0.5, ..self.0.surface }.into(),
             scroller: scrollable::Scroller {
-                color: HOVERED,
+                color: self.0.hovered,
                 ..active.scroller
             },
             ..active
@@ -101,7 +417,7 @@ impl scrollable::StyleSheet for Scrollable {
 
         scrollable::Scrollbar {
             scroller: scrollable::Scroller {
-                color: Color::from_rgb(0.85, 0.85, 0.85),
+                color: self.0.dragging,
                 ..hovered.scroller
             },
             ..hovered
@@ -109,12 +425,24 @@ impl scrollable::StyleSheet for Scrollable {
     }
 }
 
-pub struct TextInput;
+pub struct ProgressBar(pub Theme);
+
+impl progress_bar::StyleSheet for ProgressBar {
+    fn style(&self) -> progress_bar::Style {
+        progress_bar::Style {
+            background: self.0.surface.into(),
+            bar: self.0.accent.into(),
+            border_radius: 2.0,
+        }
+    }
+}
+
+pub struct TextInput(pub Theme);
 
 impl text_input::StyleSheet for TextInput {
     fn active(&self) -> text_input::Style {
         text_input::Style {
-            background: SURFACE.into(),
+            background: self.0.surface.into(),
             border_radius: 2.0,
             border_width: 0.0,
             border_color: Color::TRANSPARENT,
@@ -124,7 +452,7 @@ impl text_input::StyleSheet for TextInput {
     fn focused(&self) -> text_input::Style {
         text_input::Style {
             border_width: 1.0,
-            border_color: ACCENT,
+            border_color: self.0.accent,
             ..self.active()
         }
     }
@@ -132,20 +460,38 @@ impl text_input::StyleSheet for TextInput {
     fn hovered(&self) -> text_input::Style {
         text_input::Style {
             border_width: 1.0,
-            border_color: Color { a: 0.3, ..ACCENT },
+            border_color: Color { a: 0.3, ..self.0.accent },
             ..self.focused()
         }
     }
 
     fn placeholder_color(&self) -> Color {
-        Color::from_rgb(0.4, 0.4, 0.4)
+        self.0.placeholder
     }
 
     fn value_color(&self) -> Color {
-        Color::WHITE
+        self.0.text
     }
 
     fn selection_color(&self) -> Color {
-        ACTIVE
+        self.0.active
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_overrides() {
+        let mut overrides = HashMap::new();
+        overrides.insert("base".to_string(), "light".to_string());
+        overrides.insert("accent".to_string(), "#FF0000".to_string());
+        overrides.insert("unknown".to_string(), "#00FF00".to_string());
+        overrides.insert("text".to_string(), "not a color".to_string());
+        let theme = Theme::from_overrides(&overrides);
+        assert_eq!(theme.accent, Color::from_rgb8(0xFF, 0x00, 0x00));
+        assert_eq!(theme.text, Theme::light().text);
+        assert_eq!(theme.background, Theme::light().background);
     }
 }
@@ -0,0 +1,102 @@
+use chrono::prelude::*;
+use iced::canvas::{self, Cache, Cursor, Geometry, LineCap, Path, Stroke};
+use iced::{Point, Rectangle, Vector};
+
+use crate::plugins::clock::hand_rotation;
+use crate::style;
+
+#[derive(Debug)]
+pub struct AnalogClock {
+    now: DateTime<Local>,
+    theme: style::Theme,
+    cache: Cache,
+}
+
+impl AnalogClock {
+    pub fn update(&mut self, now: DateTime<Local>) {
+        if now.second() != self.now.second() || now.minute() != self.now.minute() {
+            self.now = now;
+            self.cache.clear();
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: style::Theme) {
+        self.theme = theme;
+        self.cache.clear();
+    }
+}
+
+impl Default for AnalogClock {
+    fn default() -> AnalogClock {
+        AnalogClock {
+            now: Local::now(),
+            theme: style::Theme::default(),
+            cache: Cache::default(),
+        }
+    }
+}
+
+impl<Message> canvas::Program<Message> for AnalogClock {
+    fn draw(&self, bounds: Rectangle, _cursor: Cursor) -> Vec<Geometry> {
+        let clock = self.cache.draw(bounds.size(), |frame| {
+            let center = frame.center();
+            let radius = frame.width().min(frame.height()) / 2.0;
+
+            let background = Path::circle(center, radius);
+            frame.fill(&background, self.theme.clock_face);
+
+            let short_hand = Path::line(Point::ORIGIN, Point::new(0.0, -0.5 * radius));
+            let long_hand = Path::line(Point::ORIGIN, Point::new(0.0, -0.8 * radius));
+            let minute_tick = Path::line(Point::new(0.0, -0.92 * radius), Point::new(0.0, -0.97 * radius));
+            let hour_tick = Path::line(Point::new(0.0, -0.85 * radius), Point::new(0.0, -0.97 * radius));
+
+            let thin_stroke = Stroke {
+                width: radius / 100.0,
+                color: self.theme.clock_second_hand,
+                line_cap: LineCap::Round,
+                ..Stroke::default()
+            };
+
+            let wide_stroke = Stroke {
+                width: thin_stroke.width * 3.0,
+                color: self.theme.clock_hands,
+                ..thin_stroke
+            };
+
+            let tick_stroke = Stroke {
+                color: self.theme.clock_ticks,
+                ..thin_stroke
+            };
+
+            frame.translate(Vector::new(center.x, center.y));
+
+            for minute in 0..60 {
+                frame.with_save(|frame| {
+                    frame.rotate(hand_rotation(minute, 60));
+                    if minute % 5 == 0 {
+                        frame.stroke(&hour_tick, wide_stroke.with_color(self.theme.clock_ticks));
+                    } else {
+                        frame.stroke(&minute_tick, tick_stroke);
+                    }
+                });
+            }
+
+            frame.with_save(|frame| {
+                frame.rotate(hand_rotation(self.now.hour() * 60 + self.now.minute(), 12 * 60));
+                frame.stroke(&short_hand, wide_stroke);
+            });
+
+            frame.with_save(|frame| {
+                frame.rotate(hand_rotation(self.now.minute(), 60));
+                frame.stroke(&long_hand, wide_stroke);
+            });
+
+            frame.with_save(|frame| {
+                frame.rotate(hand_rotation(self.now.second(), 60));
+                frame.stroke(&long_hand, thin_stroke);
+            });
+        });
+
+        vec![clock]
+    }
+}
@@ -0,0 +1,3 @@
+pub mod analog_clock;
+pub mod sparkline;
+pub mod weather_chart;
\ No newline at end of file
@@ -0,0 +1,59 @@
+use iced::canvas::{self, Cache, Cursor, Geometry, LineCap, Path, Stroke};
+use iced::{Point, Rectangle};
+
+use crate::plugins::sensors::sparkline_points;
+use crate::style;
+
+#[derive(Debug, Default)]
+pub struct Sparkline {
+    values: Vec<f32>,
+    theme: style::Theme,
+    cache: Cache,
+}
+
+impl Sparkline {
+    pub fn update(&mut self, values: Vec<f32>) {
+        if values != self.values {
+            self.values = values;
+            self.cache.clear();
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: style::Theme) {
+        self.theme = theme;
+        self.cache.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.len() < 2
+    }
+}
+
+impl<Message> canvas::Program<Message> for Sparkline {
+    fn draw(&self, bounds: Rectangle, _cursor: Cursor) -> Vec<Geometry> {
+        let line = self.cache.draw(bounds.size(), |frame| {
+            let stroke = Stroke {
+                width: 2.0,
+                color: self.theme.accent,
+                line_cap: LineCap::Round,
+                ..Stroke::default()
+            };
+            // Keeps the stroke inside the frame at the extremes.
+            let margin = stroke.width;
+            let points = sparkline_points(&self.values, frame.width() - 2.0 * margin, frame.height() - 2.0 * margin);
+            let path = Path::new(|builder| {
+                for (i, (x, y)) in points.iter().enumerate() {
+                    let point = Point::new(x + margin, y + margin);
+                    if i == 0 {
+                        builder.move_to(point);
+                    } else {
+                        builder.line_to(point);
+                    }
+                }
+            });
+            frame.stroke(&path, stroke);
+        });
+
+        vec![line]
+    }
+}
@@ -0,0 +1,110 @@
+use iced::canvas::{self, Cache, Cursor, Frame, Geometry, LineCap, Path, Stroke, Text};
+use iced::{Point, Rectangle};
+
+use crate::plugins::history::{self, Observation};
+use crate::style;
+
+// Room on the left for the minimum and maximum of each curve.
+const LABEL_WIDTH: f32 = 70.0;
+const TITLE_HEIGHT: f32 = 24.0;
+
+#[derive(Debug, Default)]
+pub struct WeatherChart {
+    observations: Vec<Observation>,
+    from: i64,
+    to: i64,
+    theme: style::Theme,
+    cache: Cache,
+}
+
+impl WeatherChart {
+    pub fn update(&mut self, observations: Vec<Observation>, from: i64, to: i64) {
+        self.observations = observations;
+        self.from = from;
+        self.to = to;
+        self.cache.clear();
+    }
+
+    pub fn set_theme(&mut self, theme: style::Theme) {
+        self.theme = theme;
+        self.cache.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.observations.is_empty()
+    }
+
+    fn draw_panel(&self, frame: &mut Frame, bounds: Rectangle, title: &str, unit: &str, series: Option<history::Series>) {
+        frame.fill_text(Text {
+            content: title.to_string(),
+            position: Point::new(bounds.x, bounds.y),
+            color: self.theme.text,
+            size: 18.0,
+            ..Text::default()
+        });
+        let series = match series {
+            Some(series) => series,
+            None => return,
+        };
+
+        let plot = Rectangle {
+            x: bounds.x + LABEL_WIDTH,
+            y: bounds.y + TITLE_HEIGHT,
+            width: (bounds.width - LABEL_WIDTH).max(1.0),
+            height: (bounds.height - TITLE_HEIGHT - 10.0).max(1.0),
+        };
+        for (value, y) in [(series.max, plot.y), (series.min, plot.y + plot.height - 14.0)].iter() {
+            frame.fill_text(Text {
+                content: format!("{:.0} {}", value, unit),
+                position: Point::new(bounds.x, *y),
+                color: self.theme.placeholder,
+                size: 14.0,
+                ..Text::default()
+            });
+        }
+
+        let axis = Path::line(Point::new(plot.x, plot.y + plot.height), Point::new(plot.x + plot.width, plot.y + plot.height));
+        frame.stroke(&axis, Stroke { width: 1.0, color: self.theme.surface, ..Stroke::default() });
+
+        let line = Path::new(|builder| {
+            for (i, (x, y)) in series.points.iter().enumerate() {
+                let point = Point::new(plot.x + x * plot.width, plot.y + y * plot.height);
+                if i == 0 {
+                    builder.move_to(point);
+                } else {
+                    builder.line_to(point);
+                }
+            }
+        });
+        frame.stroke(&line, Stroke {
+            width: 2.0,
+            color: self.theme.accent,
+            line_cap: LineCap::Round,
+            ..Stroke::default()
+        });
+    }
+}
+
+impl<Message> canvas::Program<Message> for WeatherChart {
+    fn draw(&self, bounds: Rectangle, _cursor: Cursor) -> Vec<Geometry> {
+        let chart = self.cache.draw(bounds.size(), |frame| {
+            let panel_height = frame.height() / 3.0;
+            let panels: [(&str, &str, Option<history::Series>); 3] = [
+                ("Température", "°C", history::series(&self.observations, self.from, self.to, |o| Some(o.temperature))),
+                ("Humidité", "%", history::series(&self.observations, self.from, self.to, |o| o.humidity)),
+                ("Pression", "hPa", history::series(&self.observations, self.from, self.to, |o| o.pressure)),
+            ];
+            for (i, (title, unit, series)) in panels.iter().enumerate() {
+                let bounds = Rectangle {
+                    x: 0.0,
+                    y: i as f32 * panel_height,
+                    width: frame.width(),
+                    height: panel_height,
+                };
+                self.draw_panel(frame, bounds, title, unit, series.clone());
+            }
+        });
+
+        vec![chart]
+    }
+}